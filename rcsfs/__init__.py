@@ -57,7 +57,7 @@ For detailed documentation, see:
 """
 
 from pathlib import Path
-from typing import NotRequired, Optional, TypedDict, Union
+from typing import Any, Literal, NotRequired, Optional, TypedDict, Union
 
 # Import from the Rust extension module
 try:
@@ -72,12 +72,32 @@ except ImportError:
 
 # Import from the Rust extension module
 from ._rcsfs import (
+    CSFError,
+    CSFFormatError,
+    CSFHeaderError,
+    CSFIOError,
+    CSFParquetError,
+    CSFSchemaError,
+    Csf,
+    CsfHeader,
+    ConversionResult,
+    ParquetInfo,
+    RowGroupInfo,
+    ColumnChunkInfo,
+    ColumnInfo,
     CSFDescriptorGenerator,
+    Orbital,
     CSFProcessor as _CSFProcessor,
     convert_csfs as _convert_csfs,
+    sort_orbitals,
     get_parquet_info as _get_parquet_info,
+    parquet_to_csfs as _parquet_to_csfs,
     py_generate_descriptors_from_parquet as _generate_descriptors_from_parquet,
     py_read_peel_subshells as _read_peel_subshells,
+    validate_csf,
+    validate_csfs as _validate_csfs,
+    generate_csfs as _generate_csfs,
+    analyze_excitations as _analyze_excitations,
 )
 
 
@@ -85,6 +105,9 @@ from ._rcsfs import (
 # Type Definitions
 #///////////////////////////////////////////////////////////////////////////////
 
+# Parquet codecs accepted by the `compression` argument of the writers
+ParquetCompression = Literal["uncompressed", "snappy", "gzip", "brotli", "lz4", "zstd"]
+
 
 class ConversionStats(TypedDict):
     """Statistics returned from CSF conversion operations."""
@@ -99,6 +122,136 @@ class ConversionStats(TypedDict):
     total_lines: NotRequired[int]
     csf_count: NotRequired[int]
     truncated_count: NotRequired[int]
+    block_count: NotRequired[int]
+    block_csf_counts: NotRequired[list[int]]
+    electron_count: NotRequired[Optional[int]]
+    electron_count_mismatch_count: NotRequired[int]
+    parity_mismatch_count: NotRequired[int]
+    unparsed_count: NotRequired[int]
+
+
+class ExcitationStats(TypedDict):
+    """Statistics returned from excitation-rank analysis."""
+
+    success: bool
+    input_file: str
+    output_file: str
+    header_file: NotRequired[str]
+    csf_count: int
+    kept_count: int
+    rank_counts: list[int]
+    unmatched_count: int
+    unparsed_count: int
+
+
+class ParquetColumnInfo(TypedDict):
+    """Column of a Parquet file, with its storage summed over all row groups."""
+
+    name: str
+    data_type: str
+    nullable: bool
+    codec: Optional[str]
+    encodings: list[str]
+    compressed_size: int
+    uncompressed_size: int
+
+
+class ParquetColumnChunkInfo(TypedDict):
+    """One column chunk of a row group, with its min/max statistics."""
+
+    name: str
+    codec: str
+    encodings: list[str]
+    compressed_size: int
+    uncompressed_size: int
+    null_count: Optional[int]
+    min: Any
+    max: Any
+
+
+class ParquetRowGroupInfo(TypedDict):
+    """One row group of a Parquet file."""
+
+    num_rows: int
+    total_byte_size: int
+    compressed_size: int
+    columns: list[ParquetColumnChunkInfo]
+
+
+class CsfTableMetadata(TypedDict):
+    """CSF header and provenance stored in the metadata of tables written by rCSFs."""
+
+    rcsfs_version: Optional[str]
+    header_lines: list[str]
+    core_subshells: list[str]
+    peel_subshells: list[str]
+    conversion_stats: dict[str, Any]
+    conversion: Optional[dict[str, Any]]
+
+
+class ParquetInfoDict(TypedDict):
+    """Dictionary form of `ParquetInfo`, returned by `ParquetInfo.to_dict()`."""
+
+    file_path: str
+    file_size: int
+    num_rows: int
+    num_columns: int
+    num_row_groups: int
+    compression: Optional[str]
+    created_by: Optional[str]
+    table_kind: Literal["csf", "descriptor", "other"]
+    descriptor_size: Optional[int]
+    schema: list[ParquetColumnInfo]
+    row_groups: list[ParquetRowGroupInfo]
+    csf_metadata: Optional[CsfTableMetadata]
+    key_value_metadata: dict[str, str]
+
+
+class CsfExportStats(TypedDict):
+    """Statistics returned when writing a Parquet CSF table back to text.
+
+    `success` is always True: failures raise CSFIOError, CSFHeaderError or
+    CSFSchemaError instead of returning a result.
+    """
+
+    success: bool
+    input_file: str
+    output_file: str
+    header_file: str
+    csf_count: int
+    block_count: int
+
+
+class GenerationStats(TypedDict):
+    """Statistics returned from native CSF generation."""
+
+    success: bool
+    output_file: str
+    header_file: str
+    configuration_count: int
+    csf_count: int
+    block_count: int
+    block_csf_counts: list[int]
+    peel_subshells: list[str]
+
+
+class CsfValidationError(TypedDict):
+    """A CSF that failed coupling validation."""
+
+    index: int
+    block: int
+    errors: list[str]
+
+
+class ValidationReport(TypedDict):
+    """Result of validating a CSF file."""
+
+    input_file: str
+    csf_count: int
+    valid_count: int
+    invalid_count: int
+    is_valid: bool
+    invalid: list[CsfValidationError]
 
 
 class DescriptorGenerationStats(TypedDict):
@@ -111,6 +264,11 @@ class DescriptorGenerationStats(TypedDict):
     descriptor_count: NotRequired[int]
     orbital_count: NotRequired[int]
     descriptor_size: NotRequired[int]
+    failed_count: NotRequired[int]
+    rejects_file: NotRequired[str]
+    num_workers: NotRequired[int]
+    elapsed_seconds: NotRequired[float]
+    csfs_per_second: NotRequired[float]
     error: NotRequired[str]
 
 
@@ -125,25 +283,58 @@ def convert_csfs(
     max_line_len: Optional[int] = 256,
     chunk_size: Optional[int] = 3000000,
     num_workers: Optional[int] = None,
-) -> ConversionStats:
+    parallel_read: bool = False,
+    raise_on_error: bool = False,
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
+) -> Union[ConversionResult, ConversionStats]:
     """
     Convert CSF text file to Parquet format using parallel processing.
 
-    This function is optimized for large-scale data processing. It uses a streaming
-    approach with rayon-based parallel processing:
-    - Stream: Read file in batches to avoid loading large files into memory
-    - Parallel: Process each batch with rayon's work-stealing (all cores used by default)
-    - Order: Maintain CSF order in output
+    This function is optimized for large-scale data processing. Reading,
+    processing and writing overlap in a pipeline:
+    - Stream: A reader thread reads the file in batches of at most 8192 CSFs
+    - Parallel: Worker threads process batches (all cores used by default)
+    - Order: A writer thread writes the batches in CSF order
+    Only a few batches per worker are in memory at once.
 
     Args:
-        input_path: Path to input CSF file
+        input_path: Path to input CSF file; gzip, zstd and xz files (e.g.
+            ``.csf.gz``) are detected from their first bytes and decompressed
+            while reading
         output_path: Path to output Parquet file
         max_line_len: Maximum line length (default: 256)
-        chunk_size: Number of lines per read batch (default: 3000000)
+        chunk_size: Number of lines per read batch, capped at 8192 CSFs (default: 3000000)
         num_workers: Optional number of worker threads (default: CPU core count)
+        parallel_read: Split the file into 8 MiB byte ranges and let every worker
+            read whole ranges, instead of reading through one thread (default:
+            False). Scales on NVMe drives and parallel filesystems; chunk_size
+            is unused in this mode. Compressed input is always decompressed by
+            one reader thread.
+        raise_on_error: Raise a `CSFError` subclass on failure instead of returning
+            ``{"success": False, "error": ...}``. Will become the default in a
+            future release; the legacy behaviour emits a FutureWarning.
+        compression: Parquet codec (default: None keeps the writer's default,
+            uncompressed)
+        compression_level: Level for gzip (0-10), brotli (0-11) and zstd (1-22)
+            (default: the codec's default)
+        row_group_rows: Most rows per row group (default: Parquet's 1M rows)
+        dictionary: Dictionary-encode the columns (default: True)
+        statistics: Write min/max column statistics (default: True)
+        data_page_size: Target data page size in bytes (default: Parquet's 1 MiB)
 
     Returns:
-        Dictionary containing conversion statistics and status
+        `ConversionResult` with the conversion statistics. It also supports
+        ``stats["csf_count"]`` and ``stats.to_dict()`` for dictionary-style code.
+        The partial Parquet file and header file are removed on failure.
+
+    Raises:
+        CSFIOError: If a file cannot be read or written (with raise_on_error=True)
+        CSFFormatError: If a CSF is malformed (with raise_on_error=True)
 
     Examples:
         >>> # Use all CPU cores (default)
@@ -151,6 +342,19 @@ def convert_csfs(
         >>>
         >>> # Limit to 8 workers for shared servers
         >>> stats = convert_csfs("input.csf", "output.parquet", num_workers=8)
+        >>>
+        >>> # Read a file on a parallel filesystem with every worker
+        >>> stats = convert_csfs("input.csf", "output.parquet", parallel_read=True)
+        >>>
+        >>> # Raise on failure instead of checking stats["success"]
+        >>> stats = convert_csfs("input.csf", "output.parquet", raise_on_error=True)
+        >>> stats.csf_count
+        >>>
+        >>> # Smaller files for archiving, fewer rows per row group for filtering
+        >>> stats = convert_csfs(
+        ...     "input.csf", "output.parquet", compression="zstd", compression_level=9,
+        ...     row_group_rows=100_000,
+        ... )
 
     Performance Considerations:
         - For single-task environments: omit num_workers (uses all cores)
@@ -163,27 +367,273 @@ def convert_csfs(
         max_line_len=max_line_len,
         chunk_size=chunk_size,
         num_workers=num_workers,
+        parallel_read=parallel_read,
+        raise_on_error=raise_on_error,
+        compression=compression,
+        compression_level=compression_level,
+        row_group_rows=row_group_rows,
+        dictionary=dictionary,
+        statistics=statistics,
+        data_page_size=data_page_size,
+    )
+
+
+def parquet_to_csfs(
+    input_parquet: Union[str, Path],
+    output_path: Union[str, Path],
+    header_path: Optional[Union[str, Path]] = None,
+) -> CsfExportStats:
+    """
+    Write a converted Parquet CSF table back to a GRASP-readable CSF file.
+
+    The 5 header lines come from the header `convert_csfs` stores in the
+    Parquet metadata (or its `_header.toml` for older files), followed by the
+    line1/line2/line3 triples in row order. A ` *` separator is inserted
+    whenever the `block` column changes, so filtered selections keep their
+    J/parity block structure.
+
+    Converting a file and writing it back is byte-exact as long as no line was
+    truncated during conversion.
+
+    Args:
+        input_parquet: Path to Parquet file produced by `convert_csfs`
+            (may be filtered with Polars beforehand)
+        output_path: Path to output CSF file
+        header_path: Path to header TOML file (default: read from the Parquet
+            metadata, else a `_header.toml` next to the file)
+
+    Returns:
+        Dictionary with csf_count, block_count and header_file, the file the
+        header was read from (the Parquet file itself when taken from its metadata)
+
+    Raises:
+        CSFIOError: If a file cannot be read or written
+        CSFHeaderError: If no header is found or it cannot be parsed
+        CSFSchemaError: If the line1/line2/line3 columns are missing or not strings
+
+    Examples:
+        >>> import polars as pl
+        >>> df = pl.read_parquet("output.parquet").filter(pl.col("block") == 0)
+        >>> df.write_parquet("selected.parquet")
+        >>> parquet_to_csfs("selected.parquet", "selected.c", header_path="input_header.toml")
+        >>> # Files written by convert_csfs carry their header
+        >>> parquet_to_csfs("output.parquet", "rebuilt.c")
+    """
+    return _parquet_to_csfs(
+        input_parquet=str(input_parquet),
+        output_path=str(output_path),
+        header_path=None if header_path is None else str(header_path),
     )
 
 
-def get_parquet_info(input_path: Union[str, Path]) -> dict:
+def get_parquet_info(input_path: Union[str, Path]) -> ParquetInfo:
     """
-    Get basic information and metadata from a Parquet file.
+    Describe a Parquet file from its footer, without reading the data.
 
     Args:
         input_path: Path to Parquet file
 
     Returns:
-        Dictionary containing file information:
-        - file_path: File path
-        - file_size: File size in bytes
-        - num_rows: Number of rows in the file
-        - num_columns: Number of columns
-        - compression: Compression method used
+        `ParquetInfo` with the file information as attributes. Items can
+        also be read as ``info["num_rows"]``, and ``info.to_dict()``
+        returns the plain dictionary:
+        - file_path, file_size, num_rows, num_columns, num_row_groups
+        - compression: Codec of the column chunks, e.g. "zstd"
+          (comma-separated when columns differ)
+        - created_by: Library that wrote the file
+        - table_kind: "csf" (line1..line3 columns), "descriptor"
+          (col_0..col_N columns) or "other"
+        - descriptor_size: Number of descriptor columns of a descriptor table
+        - schema: `ColumnInfo` per column with Arrow type, codec, encodings
+          and sizes
+        - row_groups: `RowGroupInfo` per row group with row count, byte sizes
+          and `ColumnChunkInfo` column chunks with codec, encodings, sizes,
+          null count and min/max statistics
+        - csf_metadata: Header lines, core/peel subshells, conversion statistics
+          and parameters stored by rCSFs, or None
+        - key_value_metadata: Every key-value metadata entry
+
+    Examples:
+        >>> info = get_parquet_info("output.parquet")
+        >>> info.table_kind, info.compression, info.num_row_groups
+        ('csf', 'zstd', 3)
+        >>> info.csf_metadata["peel_subshells"]
+        ['5s', '4d-', '4d', '5p-', '5p', '6s']
     """
     return _get_parquet_info(input_path=str(input_path))
 
 
+def generate_csfs(
+    output_path: Union[str, Path],
+    references: list[str],
+    active_shells: list[str],
+    excitation_rank: int = 2,
+    core_shells: Optional[list[str]] = None,
+    double_j_min: Optional[int] = None,
+    double_j_max: Optional[int] = None,
+    parity: Optional[str] = None,
+    chunk_size: Optional[int] = None,
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
+) -> GenerationStats:
+    """
+    Generate jj-coupled CSFs from reference configurations and write them to Parquet.
+
+    Every reference is expanded by moving up to `excitation_rank` electrons
+    between the active shells; reference shells that are not active keep their
+    occupation. Each configuration is split into relativistic subshells and all
+    couplings with a requested total J are enumerated.
+
+    The output has the same columns as `convert_csfs` (idx, line1, line2, line3,
+    block, ...) and `[output_stem]_header.toml` is written next to it, so it can
+    be passed to `generate_descriptors_from_parquet` or `parquet_to_csfs`.
+    Both files are removed again if generation fails.
+
+    Args:
+        output_path: Path to output Parquet file
+        references: Reference configurations, e.g. ["3s(2) 3p(1)"]
+        active_shells: Shells open for excitation, e.g. ["3s", "3p", "3d"]
+        excitation_rank: 1 = S, 2 = SD, 3 = SDT (default: 2)
+        core_shells: Closed core shells listed in the header, e.g. ["1s", "2s", "2p"]
+        double_j_min: Lowest total 2J to keep (default: all)
+        double_j_max: Highest total 2J to keep (default: all)
+        parity: "+" or "-" to keep a single parity (default: both)
+        chunk_size: Number of CSFs per record batch (default: 1000000)
+        compression: Parquet codec (default: None keeps the writer's default,
+            uncompressed)
+        compression_level: Level for gzip (0-10), brotli (0-11) and zstd (1-22)
+            (default: the codec's default)
+        row_group_rows: Most rows per row group (default: Parquet's 1M rows)
+        dictionary: Dictionary-encode the columns (default: True)
+        statistics: Write min/max column statistics (default: True)
+        data_page_size: Target data page size in bytes (default: Parquet's 1 MiB)
+
+    Returns:
+        Dictionary with configuration_count, csf_count and block sizes
+
+    Examples:
+        >>> stats = generate_csfs(
+        ...     "mg_like.parquet",
+        ...     references=["3s(2)"],
+        ...     active_shells=["3s", "3p", "3d"],
+        ...     excitation_rank=2,
+        ...     core_shells=["1s", "2s", "2p"],
+        ...     parity="+",
+        ... )
+    """
+    return _generate_csfs(
+        output_path=str(output_path),
+        references=references,
+        active_shells=active_shells,
+        excitation_rank=excitation_rank,
+        core_shells=core_shells,
+        double_j_min=double_j_min,
+        double_j_max=double_j_max,
+        parity=parity,
+        chunk_size=chunk_size,
+        compression=compression,
+        compression_level=compression_level,
+        row_group_rows=row_group_rows,
+        dictionary=dictionary,
+        statistics=statistics,
+        data_page_size=data_page_size,
+    )
+
+
+def analyze_excitations(
+    input_path: Union[str, Path],
+    output_path: Union[str, Path],
+    references: list[str],
+    max_rank: Optional[int] = None,
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
+) -> ExcitationStats:
+    """
+    Compute each CSF's excitation rank relative to reference configurations.
+
+    The relativistic subshells of each CSF are folded onto nl shells and
+    compared with every reference; the closest one (first on ties) is used.
+    Four columns are appended to the CSF table:
+
+    - excitation_rank: number of electrons moved
+    - reference_index: index of the closest reference
+    - excited_from: shells that lost electrons, e.g. "3s(2)"
+    - excited_to: shells that gained electrons, e.g. "3p(1) 3d(1)"
+
+    CSFs whose electron count matches no reference get nulls.
+
+    Args:
+        input_path: Converted Parquet file (.parquet) or GRASP CSF file
+        output_path: Path to output Parquet file
+        references: Reference configurations, e.g. ["3s(2) 3p(1)"]
+        max_rank: Keep only CSFs with at most this rank (default: keep all)
+        compression: Parquet codec (default: None keeps the writer's default,
+            uncompressed)
+        compression_level: Level for gzip (0-10), brotli (0-11) and zstd (1-22)
+            (default: the codec's default)
+        row_group_rows: Most rows per row group (default: Parquet's 1M rows)
+        dictionary: Dictionary-encode the columns (default: True)
+        statistics: Write min/max column statistics (default: True)
+        data_page_size: Target data page size in bytes (default: Parquet's 1 MiB)
+
+    Returns:
+        Dictionary with csf_count, kept_count, rank_counts (CSFs per rank),
+        unmatched_count (electron count matches no reference) and
+        unparsed_count (line1 could not be parsed). The partial output and
+        header file are removed on failure.
+
+    Examples:
+        >>> stats = analyze_excitations(
+        ...     "output.parquet", "singles.parquet",
+        ...     references=["3s(2) 3p(1)"], max_rank=1,
+        ... )
+        >>> stats["rank_counts"]
+    """
+    return _analyze_excitations(
+        input_path=str(input_path),
+        output_path=str(output_path),
+        references=references,
+        max_rank=max_rank,
+        compression=compression,
+        compression_level=compression_level,
+        row_group_rows=row_group_rows,
+        dictionary=dictionary,
+        statistics=statistics,
+        data_page_size=data_page_size,
+    )
+
+
+def validate_csfs(input_path: Union[str, Path]) -> ValidationReport:
+    """
+    Check every CSF in a GRASP CSF file against the jj-coupling rules.
+
+    Each CSF is checked for occupations above 2j+1, subshell J values (and
+    seniorities) not allowed for the occupancy, coupled J values on line3 that
+    violate the triangle rule, and a final J that does not close the chain.
+
+    Args:
+        input_path: Path to input CSF file, plain or gzip/zstd/xz compressed
+
+    Returns:
+        Dictionary with csf_count, valid_count, invalid_count, is_valid and the
+        list of invalid CSFs (index, block and error messages)
+
+    Examples:
+        >>> report = validate_csfs("input.csf")
+        >>> for entry in report["invalid"]:
+        ...     print(entry["index"], entry["errors"])
+    """
+    return _validate_csfs(input_path=str(input_path))
+
+
 #///////////////////////////////////////////////////////////////////////////////
 # Batch Descriptor Generation Functions
 #///////////////////////////////////////////////////////////////////////////////
@@ -191,10 +641,11 @@ def get_parquet_info(input_path: Union[str, Path]) -> dict:
 
 def read_peel_subshells(header_path: Union[str, Path]) -> list[str]:
     """
-    Extract peel subshells from a header TOML file.
+    Extract peel subshells from a header TOML file or a converted Parquet file.
 
     Args:
-        header_path: Path to the header TOML file
+        header_path: Path to the header TOML file, or to a `.parquet` file
+            written by rCSFs (read from its metadata)
 
     Returns:
         List of subshell names (e.g., ['5s', '4d-', '4d', '5p-', '5p', '6s'])
@@ -203,6 +654,8 @@ def read_peel_subshells(header_path: Union[str, Path]) -> list[str]:
         >>> peel_subshells = read_peel_subshells("data_header.toml")
         >>> print(peel_subshells)
         ['5s', '4d-', '4d', '5p-', '5p', '6s']
+        >>> read_peel_subshells("data.parquet")
+        ['5s', '4d-', '4d', '5p-', '5p', '6s']
     """
     return _read_peel_subshells(str(header_path))
 
@@ -210,8 +663,17 @@ def read_peel_subshells(header_path: Union[str, Path]) -> list[str]:
 def generate_descriptors_from_parquet(
     input_parquet: Union[str, Path],
     output_parquet: Union[str, Path],
-    peel_subshells: list[str],
+    peel_subshells: Optional[list[str]] = None,
+    header_path: Optional[Union[str, Path]] = None,
     num_workers: Optional[int] = None,
+    passthrough_columns: Optional[list[str]] = None,
+    error_policy: Literal["fail", "skip", "zeros", "nulls"] = "zeros",
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
 ) -> DescriptorGenerationStats:
     """
     Generate CSF descriptors from a parquet file using parallel processing.
@@ -221,16 +683,41 @@ def generate_descriptors_from_parquet(
     with streaming batch processing for low memory usage.
 
     Output Format:
-        Parquet with multiple Int32 columns `col_0, col_1, ..., col_N` and ZSTD compression (level 3).
+        Parquet with multiple Int32 columns `col_0, col_1, ..., col_N` and ZSTD compression
+        (level 3) unless `compression` picks another codec.
         Each column corresponds to one position in the descriptor array.
         This multi-column format is much faster than List column format for large datasets.
         Example: For 3 orbitals (descriptor_size=9), columns are: col_0, col_1, ..., col_8
+        The descriptor columns are preceded by `idx` and any passthrough columns,
+        in input order.
 
     Args:
         input_parquet: Path to input parquet file (must have line1, line2, line3, idx columns)
         output_parquet: Path to output Parquet file for descriptors
         peel_subshells: List of subshell names (e.g., ['5s', '4d-', '4d', '5p-', '5p', '6s'])
-        num_workers: Number of worker threads (default: CPU core count)
+            (default: read from the header stored in the input's Parquet metadata)
+        header_path: Header TOML file to take the peel subshells from when
+            peel_subshells is None (default: the Parquet metadata, else a
+            `_header.toml` next to the input)
+        num_workers: Number of parsing threads; caps CPU use on shared nodes
+            (default: CPU core count)
+        passthrough_columns: Input columns copied unchanged next to `idx`,
+            e.g. ["block"] (default: only `idx`)
+        error_policy: What to write for CSFs that fail to parse:
+            "fail" raises on the first failure, "skip" leaves the row out,
+            "zeros" writes an all-zero descriptor (default), "nulls" writes nulls.
+            Every failure is recorded (idx, line1..line3, error) in
+            `[output_stem]_rejects.parquet`, including the one that stops a
+            "fail" run. A failed run removes the descriptor file and keeps
+            the rejects file of the previous run otherwise.
+        compression: Parquet codec of the descriptor and rejects files (default: None keeps the writer's default,
+            ZSTD level 3)
+        compression_level: Level for gzip (0-10), brotli (0-11) and zstd (1-22)
+            (default: the codec's default)
+        row_group_rows: Most rows per row group (default: Parquet's 1M rows)
+        dictionary: Dictionary-encode the columns (default: True)
+        statistics: Write min/max column statistics (default: True)
+        data_page_size: Target data page size in bytes (default: Parquet's 1 MiB)
 
     Returns:
         Dictionary containing generation statistics:
@@ -241,17 +728,17 @@ def generate_descriptors_from_parquet(
         - descriptor_count: Number of descriptors generated
         - orbital_count: Number of orbitals
         - descriptor_size: Size of each descriptor (3 * orbital_count)
+        - failed_count: Number of CSFs that failed to parse
+        - rejects_file: Path of the rejects file (only when failed_count > 0)
+        - num_workers: Number of parsing threads used
+        - elapsed_seconds: Wall-clock time of the run
+        - csfs_per_second: Throughput (csf_count / elapsed_seconds)
 
     Examples:
-        >>> # Basic usage with peel_subshells from header
-        >>> from rcsfs import read_peel_subshells, generate_descriptors_from_parquet
+        >>> # Basic usage, peel subshells read from the Parquet metadata
+        >>> from rcsfs import generate_descriptors_from_parquet
         >>>
-        >>> peel_subshells = read_peel_subshells("data_header.toml")
-        >>> stats = generate_descriptors_from_parquet(
-        ...     "csfs_data.parquet",
-        ...     "descriptors.parquet",
-        ...     peel_subshells=peel_subshells
-        ... )
+        >>> stats = generate_descriptors_from_parquet("csfs_data.parquet", "descriptors.parquet")
 
         >>> # Read with polars
         >>> import polars as pl
@@ -289,7 +776,16 @@ def generate_descriptors_from_parquet(
         input_parquet=str(input_parquet),
         output_file=str(output_parquet),
         peel_subshells=peel_subshells,
+        header_path=None if header_path is None else str(header_path),
         num_workers=num_workers,
+        passthrough_columns=passthrough_columns,
+        error_policy=error_policy,
+        compression=compression,
+        compression_level=compression_level,
+        row_group_rows=row_group_rows,
+        dictionary=dictionary,
+        statistics=statistics,
+        data_page_size=data_page_size,
     )
 
 
@@ -303,11 +799,21 @@ class CSFProcessor:
     CSF file processor class providing an object-oriented interface.
 
     This class allows for easy configuration and repeated conversion operations
-    with consistent settings.
+    with consistent settings. Each processor owns its worker thread pool, so
+    processors with different worker counts can be used in the same process.
     """
 
     def __init__(
-        self, max_line_len: Optional[int] = 256, chunk_size: Optional[int] = 3000000
+        self,
+        max_line_len: Optional[int] = 256,
+        chunk_size: Optional[int] = 3000000,
+        num_workers: Optional[int] = None,
+        compression: Optional[ParquetCompression] = None,
+        compression_level: Optional[int] = None,
+        row_group_rows: Optional[int] = None,
+        dictionary: bool = True,
+        statistics: bool = True,
+        data_page_size: Optional[int] = None,
     ):
         """
         Create a new CSF processor instance.
@@ -315,9 +821,21 @@ class CSFProcessor:
         Args:
             max_line_len: Maximum line length (default: 256)
             chunk_size: Batch processing size (default: 3000000)
+            num_workers: Worker threads of this processor (default: CPU core count)
+            compression, compression_level, row_group_rows, dictionary,
+            statistics, data_page_size: Parquet layout of every converted file
+                (see `convert_csfs`)
         """
         self._processor = _CSFProcessor(
-            max_line_len=max_line_len, chunk_size=chunk_size
+            max_line_len=max_line_len,
+            chunk_size=chunk_size,
+            num_workers=num_workers,
+            compression=compression,
+            compression_level=compression_level,
+            row_group_rows=row_group_rows,
+            dictionary=dictionary,
+            statistics=statistics,
+            data_page_size=data_page_size,
         )
 
     @property
@@ -344,6 +862,18 @@ class CSFProcessor:
             raise ValueError("chunk_size must be greater than 0")
         self._processor.set_chunk_size(value)
 
+    @property
+    def num_workers(self) -> int:
+        """Get the number of worker threads."""
+        return self._processor.get_config()["num_workers"]
+
+    @num_workers.setter
+    def num_workers(self, value: int) -> None:
+        """Set the number of worker threads."""
+        if value <= 0:
+            raise ValueError("num_workers must be greater than 0")
+        self._processor.num_workers = value
+
     def get_config(self) -> dict:
         """
         Get current processor configuration.
@@ -358,27 +888,33 @@ class CSFProcessor:
         input_path: Union[str, Path],
         output_path: Union[str, Path],
         num_workers: Optional[int] = None,
-    ) -> ConversionStats:
+        parallel_read: bool = False,
+        raise_on_error: bool = False,
+    ) -> Union[ConversionResult, ConversionStats]:
         """
         Convert CSF file using parallel processing.
 
         Args:
             input_path: Path to input CSF file
             output_path: Path to output Parquet file
-            num_workers: Optional number of worker threads (default: CPU core count)
+            num_workers: Worker threads for this call only (default: the processor's pool)
+            parallel_read: Read byte ranges of the file concurrently (see `convert_csfs`)
+            raise_on_error: Raise a `CSFError` subclass on failure (see `convert_csfs`)
 
         Returns:
-            Conversion statistics and status
+            `ConversionResult` with the conversion statistics
         """
         return self._processor.convert(
             input_path=str(input_path),
             output_path=str(output_path),
             num_workers=num_workers,
+            parallel_read=parallel_read,
+            raise_on_error=raise_on_error,
         )
 
-    def get_metadata(self, input_path: Union[str, Path]) -> dict:
+    def get_metadata(self, input_path: Union[str, Path]) -> ParquetInfo:
         """
-        Get Parquet file information.
+        Get Parquet file information, same as `get_parquet_info`.
 
         Args:
             input_path: Path to Parquet file
@@ -399,7 +935,32 @@ __all__ = [
     # CSF file conversion
     "convert_csfs",
     "get_parquet_info",
+    "parquet_to_csfs",
     "CSFProcessor",
+    "ConversionResult",
+    "ParquetInfo",
+    "RowGroupInfo",
+    "ColumnChunkInfo",
+    "ColumnInfo",
+    # Exceptions
+    "CSFError",
+    "CSFIOError",
+    "CSFHeaderError",
+    "CSFFormatError",
+    "CSFSchemaError",
+    "CSFParquetError",
+    # Typed CSF model
+    "Csf",
+    "CsfHeader",
+    "Orbital",
+    "sort_orbitals",
+    # CSF generation
+    "generate_csfs",
+    # Excitation analysis
+    "analyze_excitations",
+    # Coupling validation
+    "validate_csf",
+    "validate_csfs",
     # CSF descriptor generation
     "CSFDescriptorGenerator",
     # Batch descriptor generation
@@ -407,5 +968,16 @@ __all__ = [
     "read_peel_subshells",
     # Type definitions
     "ConversionStats",
+    "CsfExportStats",
+    "CsfValidationError",
+    "ExcitationStats",
+    "GenerationStats",
+    "ValidationReport",
     "DescriptorGenerationStats",
+    "ParquetCompression",
+    "ParquetInfoDict",
+    "ParquetColumnInfo",
+    "ParquetRowGroupInfo",
+    "ParquetColumnChunkInfo",
+    "CsfTableMetadata",
 ]
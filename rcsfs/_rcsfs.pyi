@@ -4,12 +4,39 @@ Type stubs for the _rcsfs Rust extension module.
 This file provides type hints for the compiled Rust extension module.
 """
 
-from typing import Optional
+from typing import Any, Iterator, Literal, Optional, Union, overload
 
 from typing_extensions import NotRequired, TypedDict
 
 __version__: str
 
+ParquetCompression = Literal["uncompressed", "snappy", "gzip", "brotli", "lz4", "zstd"]
+
+# ///////////////////////////////////////////////////////////////////////////////
+# Exceptions
+# ///////////////////////////////////////////////////////////////////////////////
+
+class CSFError(Exception):
+    """Base class of all rCSFs errors."""
+
+class CSFIOError(CSFError):
+    """A file could not be opened, read or written."""
+
+class CSFHeaderError(CSFError):
+    """The CSF header or header TOML file is missing or malformed."""
+
+class CSFFormatError(CSFError):
+    """A CSF could not be parsed; `idx` and `line` locate it when known."""
+
+    idx: Optional[int]
+    line: Optional[int]
+
+class CSFSchemaError(CSFError):
+    """A Parquet table lacks a required column or has the wrong column type."""
+
+class CSFParquetError(CSFError):
+    """Reading or writing Parquet/Arrow data failed."""
+
 # ///////////////////////////////////////////////////////////////////////////////
 # Type Definitions for Return Values
 # ///////////////////////////////////////////////////////////////////////////////
@@ -27,21 +54,386 @@ class ConversionStats(TypedDict):
     total_lines: NotRequired[int]
     csf_count: NotRequired[int]
     truncated_count: NotRequired[int]
+    block_count: NotRequired[int]
+    block_csf_counts: NotRequired[list[int]]
+    electron_count: NotRequired[Optional[int]]
+    electron_count_mismatch_count: NotRequired[int]
+    parity_mismatch_count: NotRequired[int]
+    unparsed_count: NotRequired[int]
     num_workers: NotRequired[int]
 
+class ConversionResult:
+    """Statistics of a successful CSF to Parquet conversion."""
+
+    input_file: str
+    output_file: str
+    header_file: Optional[str]
+    max_line_len: int
+    chunk_size: int
+    num_workers: int
+    csf_count: int
+    total_lines: int
+    truncated_count: int
+    block_count: int
+    block_csf_counts: list[int]
+    electron_count: Optional[int]
+    electron_count_mismatch_count: int
+    parity_mismatch_count: int
+    unparsed_count: int
+    @property
+    def success(self) -> bool: ...
+    def to_dict(self) -> ConversionStats: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def get(self, key: str, default: Any = None) -> Any: ...
+
 # ///////////////////////////////////////////////////////////////////////////////
 # CSF File Conversion Functions
 # ///////////////////////////////////////////////////////////////////////////////
 
+@overload
 def convert_csfs(
     input_path: str,
     output_path: str,
     max_line_len: Optional[int] = 256,
     chunk_size: Optional[int] = 3000000,
     num_workers: Optional[int] = None,
-) -> ConversionStats: ...
+    parallel_read: bool = False,
+    *,
+    raise_on_error: Literal[True],
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
+) -> ConversionResult: ...
+@overload
+def convert_csfs(
+    input_path: str,
+    output_path: str,
+    max_line_len: Optional[int] = 256,
+    chunk_size: Optional[int] = 3000000,
+    num_workers: Optional[int] = None,
+    parallel_read: bool = False,
+    raise_on_error: bool = False,
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
+) -> Union[ConversionResult, ConversionStats]: ...
+
+class ParquetColumnInfo(TypedDict):
+    """Column of a Parquet file, with its storage summed over all row groups."""
+
+    name: str
+    data_type: str
+    nullable: bool
+    codec: Optional[str]
+    encodings: list[str]
+    compressed_size: int
+    uncompressed_size: int
+
+class ParquetColumnChunkInfo(TypedDict):
+    """One column chunk of a row group, with its min/max statistics."""
 
-def get_parquet_info(input_path: str) -> dict: ...
+    name: str
+    codec: str
+    encodings: list[str]
+    compressed_size: int
+    uncompressed_size: int
+    null_count: Optional[int]
+    min: Any
+    max: Any
+
+class ParquetRowGroupInfo(TypedDict):
+    """One row group of a Parquet file."""
+
+    num_rows: int
+    total_byte_size: int
+    compressed_size: int
+    columns: list[ParquetColumnChunkInfo]
+
+class CsfTableMetadata(TypedDict):
+    """CSF header and provenance stored in the metadata of tables written by rCSFs."""
+
+    rcsfs_version: Optional[str]
+    header_lines: list[str]
+    core_subshells: list[str]
+    peel_subshells: list[str]
+    conversion_stats: dict[str, Any]
+    conversion: Optional[dict[str, Any]]
+
+class ParquetInfoDict(TypedDict):
+    """Dictionary form of `ParquetInfo`, returned by `ParquetInfo.to_dict()`."""
+
+    file_path: str
+    file_size: int
+    num_rows: int
+    num_columns: int
+    num_row_groups: int
+    compression: Optional[str]
+    created_by: Optional[str]
+    table_kind: Literal["csf", "descriptor", "other"]
+    descriptor_size: Optional[int]
+    schema: list[ParquetColumnInfo]
+    row_groups: list[ParquetRowGroupInfo]
+    csf_metadata: Optional[CsfTableMetadata]
+    key_value_metadata: dict[str, str]
+
+class ColumnInfo:
+    """Column of a Parquet file, with its storage summed over all row groups."""
+
+    name: str
+    data_type: str
+    nullable: bool
+    codec: Optional[str]
+    encodings: list[str]
+    compressed_size: int
+    uncompressed_size: int
+    def to_dict(self) -> ParquetColumnInfo: ...
+    def __getitem__(self, key: str) -> Any: ...
+
+class ColumnChunkInfo:
+    """One column chunk of a row group, with its min/max statistics."""
+
+    name: str
+    codec: str
+    encodings: list[str]
+    compressed_size: int
+    uncompressed_size: int
+    null_count: Optional[int]
+    min: Any
+    max: Any
+    def to_dict(self) -> ParquetColumnChunkInfo: ...
+    def __getitem__(self, key: str) -> Any: ...
+
+class RowGroupInfo:
+    """One row group of a Parquet file."""
+
+    num_rows: int
+    total_byte_size: int
+    compressed_size: int
+    columns: list[ColumnChunkInfo]
+    def to_dict(self) -> ParquetRowGroupInfo: ...
+    def __getitem__(self, key: str) -> Any: ...
+
+class ParquetInfo:
+    """Description of a Parquet file returned by `get_parquet_info`."""
+
+    file_path: str
+    file_size: int
+    num_rows: int
+    num_columns: int
+    num_row_groups: int
+    compression: Optional[str]
+    created_by: Optional[str]
+    table_kind: Literal["csf", "descriptor", "other"]
+    descriptor_size: Optional[int]
+    schema: list[ColumnInfo]
+    row_groups: list[RowGroupInfo]
+    csf_metadata: Optional[CsfTableMetadata]
+    key_value_metadata: dict[str, str]
+    def to_dict(self) -> ParquetInfoDict: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def get(self, key: str, default: Any = None) -> Any: ...
+    def __contains__(self, key: object) -> bool: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[str]: ...
+    def keys(self) -> list[str]: ...
+    def items(self) -> list[tuple[str, Any]]: ...
+
+def get_parquet_info(input_path: str) -> ParquetInfo: ...
+
+class CsfExportStats(TypedDict):
+    """Statistics returned when writing a Parquet CSF table back to text.
+
+    `success` is always True: failures raise CSFIOError, CSFHeaderError or
+    CSFSchemaError instead of returning a result.
+    """
+
+    success: bool
+    input_file: str
+    output_file: str
+    header_file: str
+    csf_count: int
+    block_count: int
+
+def parquet_to_csfs(
+    input_parquet: str,
+    output_path: str,
+    header_path: Optional[str] = None,
+) -> CsfExportStats: ...
+
+# ///////////////////////////////////////////////////////////////////////////////
+# Typed CSF Model
+# ///////////////////////////////////////////////////////////////////////////////
+
+class CsfSubshell(TypedDict):
+    """One subshell entry of a parsed CSF (J values are doubled)."""
+
+    label: str
+    occupation: int
+    seniority: Optional[int]
+    intermediate_double_j: Optional[int]
+    coupled_double_j: Optional[int]
+
+class Csf:
+    """
+    A single CSF parsed from its three GRASP fixed-width lines.
+    """
+
+    @staticmethod
+    def parse(line1: str, line2: str, line3: str) -> Csf: ...
+    @property
+    def subshells(self) -> list[CsfSubshell]: ...
+    @property
+    def final_double_j(self) -> int: ...
+    @property
+    def parity(self) -> str: ...
+    @property
+    def electron_count(self) -> int: ...
+    @property
+    def computed_parity(self) -> str: ...
+    def check_parity(self) -> None:
+        """Raise CSFFormatError if the declared parity disagrees with the occupations."""
+        ...
+    def to_lines(self) -> list[str]: ...
+
+class CsfHeader:
+    """
+    Parsed 5-line header of a GRASP CSF list (core, peel and CSF section markers).
+    """
+
+    def __init__(self, lines: list[str]) -> None: ...
+    @staticmethod
+    def from_csf_file(path: str) -> CsfHeader: ...
+    @staticmethod
+    def from_header_toml(path: str) -> CsfHeader: ...
+    @staticmethod
+    def from_parquet(path: str) -> CsfHeader: ...
+    @property
+    def core_subshells(self) -> list[str]: ...
+    @property
+    def peel_subshells(self) -> list[str]: ...
+    @property
+    def closed_core_electrons(self) -> int: ...
+    @property
+    def format_marker(self) -> str: ...
+    def to_lines(self) -> list[str]: ...
+
+class Orbital:
+    """
+    Relativistic subshell parsed from a GRASP label such as "4d-" or "5f".
+
+    Orbitals compare and sort in GRASP order (n, then l, then j).
+    """
+
+    def __init__(self, label: str) -> None: ...
+    @property
+    def n(self) -> int: ...
+    @property
+    def l(self) -> int: ...
+    @property
+    def kappa(self) -> int: ...
+    @property
+    def two_j(self) -> int: ...
+    @property
+    def max_occupancy(self) -> int: ...
+    def nl_label(self) -> str: ...
+    def __lt__(self, other: Orbital) -> bool: ...
+    def __hash__(self) -> int: ...
+
+def sort_orbitals(labels: list[str]) -> list[str]: ...
+
+# ///////////////////////////////////////////////////////////////////////////////
+# CSF Generation
+# ///////////////////////////////////////////////////////////////////////////////
+
+class GenerationStats(TypedDict):
+    """Statistics returned from native CSF generation."""
+
+    success: bool
+    output_file: str
+    header_file: str
+    configuration_count: int
+    csf_count: int
+    block_count: int
+    block_csf_counts: list[int]
+    peel_subshells: list[str]
+
+def generate_csfs(
+    output_path: str,
+    references: list[str],
+    active_shells: list[str],
+    excitation_rank: int = 2,
+    core_shells: Optional[list[str]] = None,
+    double_j_min: Optional[int] = None,
+    double_j_max: Optional[int] = None,
+    parity: Optional[str] = None,
+    chunk_size: Optional[int] = None,
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
+) -> GenerationStats: ...
+
+# ///////////////////////////////////////////////////////////////////////////////
+# Excitation Analysis
+# ///////////////////////////////////////////////////////////////////////////////
+
+class ExcitationStats(TypedDict):
+    """Statistics returned from excitation-rank analysis."""
+
+    success: bool
+    input_file: str
+    output_file: str
+    header_file: NotRequired[str]
+    csf_count: int
+    kept_count: int
+    rank_counts: list[int]
+    unmatched_count: int
+    unparsed_count: int
+
+def analyze_excitations(
+    input_path: str,
+    output_path: str,
+    references: list[str],
+    max_rank: Optional[int] = None,
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
+) -> ExcitationStats: ...
+
+# ///////////////////////////////////////////////////////////////////////////////
+# Coupling Validation
+# ///////////////////////////////////////////////////////////////////////////////
+
+class CsfValidationError(TypedDict):
+    """A CSF that failed coupling validation."""
+
+    index: int
+    block: int
+    errors: list[str]
+
+class ValidationReport(TypedDict):
+    """Result of validating a CSF file."""
+
+    input_file: str
+    csf_count: int
+    valid_count: int
+    invalid_count: int
+    is_valid: bool
+    invalid: list[CsfValidationError]
+
+def validate_csf(line1: str, line2: str, line3: str) -> list[str]: ...
+def validate_csfs(input_path: str) -> ValidationReport: ...
 
 # ///////////////////////////////////////////////////////////////////////////////
 # CSF Descriptor Generation
@@ -58,9 +450,28 @@ class CSFDescriptorGenerator:
     def __init__(self, peel_subshells: list[str]) -> None: ...
     def orbital_count(self) -> int: ...
     def peel_subshells(self) -> list[str]: ...
-    def parse_csf(self, line1: str, line2: str, line3: str) -> list[int]: ...
+    def parse_csf(
+        self, line1: str, line2: str, line3: str, validate: bool = False
+    ) -> list[int]:
+        """
+        Parse a single CSF into a descriptor array.
+
+        Raises CSFFormatError if the final J is unreadable or a subshell is
+        not in `peel_subshells` (e.g. "4s is not a peel subshell"). Earlier
+        versions printed a warning to stderr and skipped that subshell.
+        `parse_csf_from_list` and `batch_parse_csfs` behave the same.
+        """
+        ...
     def parse_csf_from_list(self, csf_lines: list[str]) -> list[int]: ...
-    def batch_parse_csfs(self, csf_list: list[list[str]]) -> list[list[int]]: ...
+    def batch_parse_csfs(
+        self, csf_list: list[list[str]], validate: bool = False
+    ) -> list[list[int]]: ...
+    def decode_descriptor(self, descriptor: list[int]) -> list[str]:
+        """Rebuild the three CSF lines; raises CSFFormatError for an invalid coupling."""
+        ...
+    def batch_decode_descriptors(self, descriptors: list[list[int]]) -> list[list[str]]:
+        """Decode many descriptors; the CSFFormatError of the first invalid one carries its idx."""
+        ...
     def get_config(self) -> dict: ...
 
 # ///////////////////////////////////////////////////////////////////////////////
@@ -77,13 +488,27 @@ class DescriptorGenerationStats(TypedDict):
     descriptor_count: NotRequired[int]
     orbital_count: NotRequired[int]
     descriptor_size: NotRequired[int]
+    failed_count: NotRequired[int]
+    rejects_file: NotRequired[str]
+    num_workers: NotRequired[int]
+    elapsed_seconds: NotRequired[float]
+    csfs_per_second: NotRequired[float]
     error: NotRequired[str]
 
 def py_generate_descriptors_from_parquet(
     input_parquet: str,
     output_file: str,
-    peel_subshells: list[str],
+    peel_subshells: Optional[list[str]] = None,
+    header_path: Optional[str] = None,
     num_workers: Optional[int] = None,
+    passthrough_columns: Optional[list[str]] = None,
+    error_policy: Literal["fail", "skip", "zeros", "nulls"] = "zeros",
+    compression: Optional[ParquetCompression] = None,
+    compression_level: Optional[int] = None,
+    row_group_rows: Optional[int] = None,
+    dictionary: bool = True,
+    statistics: bool = True,
+    data_page_size: Optional[int] = None,
 ) -> DescriptorGenerationStats: ...
 
 def py_read_peel_subshells(header_path: str) -> list[str]: ...
@@ -100,12 +525,27 @@ class CSFProcessor:
     """
 
     def __init__(
-        self, max_line_len: Optional[int] = 256, chunk_size: Optional[int] = 3000000
+        self,
+        max_line_len: Optional[int] = 256,
+        chunk_size: Optional[int] = 3000000,
+        num_workers: Optional[int] = None,
+        compression: Optional[ParquetCompression] = None,
+        compression_level: Optional[int] = None,
+        row_group_rows: Optional[int] = None,
+        dictionary: bool = True,
+        statistics: bool = True,
+        data_page_size: Optional[int] = None,
     ) -> None: ...
     def set_max_line_len(self, value: int) -> None: ...
     def set_chunk_size(self, value: int) -> None: ...
+    num_workers: int
     def get_config(self) -> dict: ...
     def convert(
-        self, input_path: str, output_path: str, num_workers: Optional[int] = None
-    ) -> ConversionStats: ...
-    def get_metadata(self, input_path: str) -> dict: ...
+        self,
+        input_path: str,
+        output_path: str,
+        num_workers: Optional[int] = None,
+        parallel_read: bool = False,
+        raise_on_error: bool = False,
+    ) -> Union[ConversionResult, ConversionStats]: ...
+    def get_metadata(self, input_path: str) -> ParquetInfo: ...
@@ -0,0 +1,188 @@
+//! Angular Momentum Coupling Rules
+//!
+//! Helpers for the jj-coupling checks shared by the descriptor decoder and the
+//! CSF validator. All angular momenta are doubled (2J) so half-integer values
+//! stay integral.
+
+/// Check the triangle rule |a - b| <= c <= a + b for doubled angular momenta
+///
+/// The sum a + b + c must also be even, otherwise integer and half-integer
+/// values are being mixed.
+pub fn triangle(a: i32, b: i32, c: i32) -> bool {
+    a >= 0 && b >= 0 && c >= 0 && (a - b).abs() <= c && c <= a + b && (a + b + c) % 2 == 0
+}
+
+/// Count the N-electron states of a j-subshell for every total 2M
+///
+/// Entry `i` holds the number of states with 2M = 2i - max, where
+/// `max = counts.len() - 1` bounds the doubled projection.
+fn projection_counts(two_j: u32, occupation: u32) -> Vec<u64> {
+    let two_j = two_j as i32;
+    let n = occupation as usize;
+    let m_values: Vec<i32> = (-two_j..=two_j).step_by(2).collect();
+    if n > m_values.len() {
+        return Vec::new();
+    }
+
+    // dp[k][sum + offset] = number of ways to choose k distinct m values with that sum
+    let offset = two_j * n as i32;
+    let width = (2 * offset + 1) as usize;
+    let mut dp = vec![vec![0u64; width]; n + 1];
+    dp[0][offset as usize] = 1;
+    for &m in &m_values {
+        for k in (1..=n).rev() {
+            for s in 0..width {
+                let prev = s as i32 - m;
+                if prev >= 0 && (prev as usize) < width {
+                    dp[k][s] += dp[k - 1][prev as usize];
+                }
+            }
+        }
+    }
+
+    // Every reachable sum has the parity of the offset, so keep every other entry
+    dp.swap_remove(n).into_iter().step_by(2).collect()
+}
+
+/// Number of independent states with total 2J = `double_j` for `occupation`
+/// electrons in a subshell with 2j = `two_j`
+///
+/// Zero means the J value is not allowed for that occupancy.
+///
+/// # Examples
+/// ```text
+/// subshell_j_multiplicity(3, 2, 4) => 1   // p3/2^2 J=2
+/// subshell_j_multiplicity(3, 2, 2) => 0   // p3/2^2 J=1 is Pauli-forbidden
+/// subshell_j_multiplicity(7, 4, 8) => 2   // f7/2^4 J=4 occurs twice
+/// ```
+pub fn subshell_j_multiplicity(two_j: u32, occupation: u32, double_j: i32) -> u32 {
+    let counts = projection_counts(two_j, occupation);
+    let max = counts.len() as i32 - 1;
+    if max < 0 || double_j < 0 || double_j > max || (double_j + max) % 2 != 0 {
+        return 0;
+    }
+    // States with projection 2M = 2J minus those with 2M = 2J + 2
+    let at = |two_m: i32| counts.get(((two_m + max) / 2) as usize).copied().unwrap_or(0);
+    (at(double_j) - at(double_j + 2)) as u32
+}
+
+/// Seniorities under which total 2J = `double_j` occurs for `occupation`
+/// electrons in a subshell with 2j = `two_j`
+///
+/// GRASP writes the seniority as `v;J` on line2 only when this list has more
+/// than one entry.
+pub fn subshell_seniorities(two_j: u32, occupation: u32, double_j: i32) -> Vec<u32> {
+    let capacity = two_j + 1;
+    if occupation > capacity {
+        return Vec::new();
+    }
+    // Seniority is bounded by the number of electrons or holes, whichever is smaller
+    let max_v = occupation.min(capacity - occupation);
+    (max_v % 2..=max_v)
+        .step_by(2)
+        .filter(|&v| {
+            let with_v = subshell_j_multiplicity(two_j, v, double_j);
+            let below = if v >= 2 {
+                subshell_j_multiplicity(two_j, v - 2, double_j)
+            } else {
+                0
+            };
+            with_v > below
+        })
+        .collect()
+}
+
+/// All states of `occupation` electrons in a subshell with 2j = `two_j`, in the
+/// form GRASP writes them on line2
+///
+/// Each entry is `(seniority, 2J)`, ordered by J and then seniority. The
+/// seniority is only set when the same J occurs under several seniorities,
+/// matching the `v;J` notation.
+///
+/// # Returns
+/// * `Ok(states)` - Every state of the subshell
+/// * `Err(String)` - If some J cannot be told apart even with seniority
+pub fn subshell_states(two_j: u32, occupation: u32) -> Result<Vec<(Option<u32>, i32)>, String> {
+    let capacity = two_j + 1;
+    if occupation > capacity {
+        return Err(format!(
+            "{} electrons do not fit into a subshell with 2j = {}",
+            occupation, two_j
+        ));
+    }
+    let q = occupation.min(capacity - occupation);
+    let max_double_j = (q * (two_j + 1 - q)) as i32;
+
+    let mut states = Vec::new();
+    for double_j in (0..=max_double_j).filter(|j| (j + max_double_j) % 2 == 0) {
+        let multiplicity = subshell_j_multiplicity(two_j, occupation, double_j) as usize;
+        if multiplicity == 0 {
+            continue;
+        }
+        let seniorities = subshell_seniorities(two_j, occupation, double_j);
+        if multiplicity > seniorities.len().max(1) {
+            return Err(format!(
+                "J = {}/2 occurs {} times for {} electrons in 2j = {} and cannot be labelled by seniority",
+                double_j, multiplicity, occupation, two_j
+            ));
+        }
+        if seniorities.len() > 1 {
+            states.extend(seniorities.into_iter().map(|v| (Some(v), double_j)));
+        } else {
+            states.push((None, double_j));
+        }
+    }
+    Ok(states)
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangle() {
+        assert!(triangle(9, 16, 7));
+        assert!(triangle(7, 3, 8));
+        assert!(!triangle(2, 2, 6));
+        assert!(!triangle(3, 3, 3));
+    }
+
+    #[test]
+    fn test_p_three_half_states() {
+        // p3/2^2: J = 0, 2
+        assert_eq!(subshell_j_multiplicity(3, 2, 0), 1);
+        assert_eq!(subshell_j_multiplicity(3, 2, 2), 0);
+        assert_eq!(subshell_j_multiplicity(3, 2, 4), 1);
+        // Closed shell only has J = 0
+        assert_eq!(subshell_j_multiplicity(3, 4, 0), 1);
+        assert_eq!(subshell_j_multiplicity(3, 4, 2), 0);
+        // Overfilled shell has no states
+        assert_eq!(subshell_j_multiplicity(3, 5, 3), 0);
+    }
+
+    #[test]
+    fn test_f_seven_half_four_electrons() {
+        // f7/2^4: J = 0, 2, 2, 4, 4, 5, 6, 8
+        let expected = [(0, 1), (2, 0), (4, 2), (6, 0), (8, 2), (10, 1), (12, 1), (16, 1)];
+        for (double_j, count) in expected {
+            assert_eq!(subshell_j_multiplicity(7, 4, double_j), count, "2J = {}", double_j);
+        }
+        assert_eq!(subshell_seniorities(7, 4, 8), vec![2, 4]);
+        assert_eq!(subshell_seniorities(7, 4, 12), vec![2]);
+    }
+
+    #[test]
+    fn test_subshell_states() {
+        assert_eq!(subshell_states(3, 2).unwrap(), vec![(None, 0), (None, 4)]);
+        assert_eq!(subshell_states(3, 4).unwrap(), vec![(None, 0)]);
+        assert_eq!(subshell_states(1, 1).unwrap(), vec![(None, 1)]);
+        let f4 = subshell_states(7, 4).unwrap();
+        assert_eq!(f4.len(), 8);
+        assert!(f4.contains(&(Some(2), 8)) && f4.contains(&(Some(4), 8)));
+        assert!(subshell_states(3, 5).is_err());
+    }
+}
@@ -0,0 +1,554 @@
+//! Typed CSF Data Model
+//!
+//! This module provides a structured representation of a single Configuration
+//! State Function (CSF) as written by GRASP. A CSF occupies three fixed-width
+//! lines in a `.csf` file:
+//!
+//! ```text
+//! line1: "  5s ( 2)  4d-( 4)  4d ( 6)  4f-( 3)  4f ( 4)  5d-( 1)"
+//! line2: "                             9/2        8      3/2"
+//! line3: "                                     7/2      4-"
+//! ```
+//!
+//! * line1 lists the subshells in 9-character blocks: a 5-character label and
+//!   the occupation in parentheses.
+//! * line2 holds the J of each open subshell, right-aligned in the subshell's
+//!   block. Subshells with several states of the same J write `v;J`.
+//! * line3 holds the coupled J after adding each subshell, shifted 4 columns
+//!   to the right, and ends with the total J followed by the parity sign.
+//!
+//! All angular momenta are stored doubled (2J) so half-integer values stay integral.
+
+use crate::csfs_descriptor::j_to_double_j;
+use crate::orbital::Orbital;
+use std::fmt;
+
+/// Width of one subshell block on line1 and line2
+const BLOCK_WIDTH: usize = 9;
+
+/// Width of the subshell label at the start of each line1 block
+const LABEL_WIDTH: usize = 5;
+
+/// Offset of the first coupling block on line3 relative to line1
+const COUPLING_OFFSET: usize = 4;
+
+/// Format a doubled J value the way GRASP prints it ("3/2", "2", ...)
+///
+/// # Examples
+/// ```text
+/// double_j_to_string(3) => "3/2"
+/// double_j_to_string(4) => "2"
+/// ```
+pub fn double_j_to_string(double_j: i32) -> String {
+    if double_j % 2 == 0 {
+        (double_j / 2).to_string()
+    } else {
+        format!("{}/2", double_j)
+    }
+}
+
+/// Format a subshell label into its 5-character GRASP field ("  4d-", "  5s ")
+pub(crate) fn format_label(label: &str) -> String {
+    if label.ends_with('-') {
+        format!("{:>5}", label)
+    } else {
+        format!("{:>4} ", label)
+    }
+}
+
+/// Parity of a CSF, written as `+` or `-` after the total J on line3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+impl Parity {
+    /// Parse a parity sign character (`+` or `-`)
+    pub fn from_sign(sign: char) -> Option<Self> {
+        match sign {
+            '+' => Some(Parity::Even),
+            '-' => Some(Parity::Odd),
+            _ => None,
+        }
+    }
+
+    /// The sign character GRASP uses for this parity
+    pub fn sign(self) -> char {
+        match self {
+            Parity::Even => '+',
+            Parity::Odd => '-',
+        }
+    }
+
+    /// Parity of a configuration with `odd_electrons` electrons in odd-l subshells
+    pub fn from_odd_electrons(odd_electrons: u32) -> Self {
+        if odd_electrons % 2 == 1 {
+            Parity::Odd
+        } else {
+            Parity::Even
+        }
+    }
+}
+
+impl fmt::Display for Parity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.sign())
+    }
+}
+
+/// One subshell entry of a CSF
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsfSubshell {
+    /// Subshell label as written on line1 (e.g. "4d-", "5s")
+    pub label: String,
+    /// Number of electrons in the subshell
+    pub occupation: u32,
+    /// Seniority-like qualifier written before `;` on line2, if any
+    pub seniority: Option<u32>,
+    /// Intermediate J of the subshell from line2 (2J), `None` when blank
+    pub intermediate_double_j: Option<i32>,
+    /// Coupled J after adding this subshell from line3 (2J), `None` when blank
+    pub coupled_double_j: Option<i32>,
+}
+
+impl CsfSubshell {
+    /// Parse the subshell label into its quantum numbers
+    pub fn orbital(&self) -> Result<Orbital, String> {
+        self.label.parse()
+    }
+}
+
+/// A single CSF parsed from its three GRASP lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Csf {
+    /// Subshells in line1 order
+    pub subshells: Vec<CsfSubshell>,
+    /// Total J of the CSF (2J)
+    pub final_double_j: i32,
+    /// Parity of the CSF
+    pub parity: Parity,
+}
+
+/// Return the fixed-width field starting at column `start`, clipped to the line end
+fn field(line: &str, start: usize, width: usize) -> &str {
+    if start >= line.len() {
+        return "";
+    }
+    &line[start..(start + width).min(line.len())]
+}
+
+/// Split a line1 block ("  4d-( 4)") into its label and occupation
+fn parse_subshell_block(block: &str) -> Result<(&str, u32), String> {
+    let (label, occupation) = block.split_at(LABEL_WIDTH);
+    if !occupation.starts_with('(') || !occupation.ends_with(')') {
+        return Err(format!("Malformed subshell block: {:?}", block));
+    }
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(format!("Missing subshell label in block: {:?}", block));
+    }
+    let occupation = occupation[1..occupation.len() - 1]
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid occupation in block: {:?}", block))?;
+    Ok((label, occupation))
+}
+
+/// Check line1 and return it without trailing whitespace
+fn checked_line1(line1: &str) -> Result<&str, String> {
+    let line1 = line1.trim_end();
+    if line1.is_empty() {
+        return Err("line1 is empty".to_string());
+    }
+    if !line1.is_ascii() {
+        return Err("CSF lines must be ASCII".to_string());
+    }
+    if !line1.len().is_multiple_of(BLOCK_WIDTH) {
+        return Err(format!(
+            "line1 length {} is not a multiple of {}",
+            line1.len(),
+            BLOCK_WIDTH
+        ));
+    }
+    Ok(line1)
+}
+
+/// Subshells and occupations of line1, without parsing the J lines
+pub fn parse_configuration_line(line1: &str) -> Result<Vec<(Orbital, u32)>, String> {
+    let line1 = checked_line1(line1)?;
+    (0..line1.len())
+        .step_by(BLOCK_WIDTH)
+        .map(|start| {
+            let (label, occupation) = parse_subshell_block(&line1[start..start + BLOCK_WIDTH])?;
+            Ok((label.parse()?, occupation))
+        })
+        .collect()
+}
+
+/// Electron count and parity of a configuration, read from line1 only
+///
+/// Cheaper than `Csf::parse` because line2 and line3 are not touched. The
+/// count covers the listed subshells only; closed core electrons are not part
+/// of a CSF.
+pub fn configuration_summary(line1: &str) -> Result<(u32, Parity), String> {
+    let mut electrons = 0;
+    let mut odd_electrons = 0;
+    for (orbital, occupation) in parse_configuration_line(line1)? {
+        electrons += occupation;
+        if orbital.is_odd() {
+            odd_electrons += occupation;
+        }
+    }
+    Ok((electrons, Parity::from_odd_electrons(odd_electrons)))
+}
+
+/// Parity sign written at the end of line3, if any
+pub fn declared_parity(line3: &str) -> Option<Parity> {
+    line3.trim_end().chars().last().and_then(Parity::from_sign)
+}
+
+/// Parse a J field that may carry a `v;` qualifier (e.g. "   4;   4")
+fn parse_j_field(text: &str) -> Result<(Option<u32>, i32), String> {
+    match text.split_once(';') {
+        Some((seniority, j)) => {
+            let seniority = seniority
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid seniority: {}", text.trim()))?;
+            Ok((Some(seniority), j_to_double_j(j)?))
+        }
+        None => Ok((None, j_to_double_j(text)?)),
+    }
+}
+
+impl Csf {
+    /// Parse a CSF from its three lines
+    ///
+    /// # Arguments
+    /// * `line1` - Subshell labels and occupations
+    /// * `line2` - Intermediate J values of the open subshells
+    /// * `line3` - Coupled J values, total J and parity
+    ///
+    /// # Returns
+    /// * `Ok(Csf)` - The parsed CSF
+    /// * `Err(String)` - Error message if any field is malformed
+    pub fn parse(line1: &str, line2: &str, line3: &str) -> Result<Self, String> {
+        let line1 = checked_line1(line1)?;
+        let line2 = line2.trim_end();
+        let line3 = line3.trim_end();
+
+        if !line2.is_ascii() || !line3.is_ascii() {
+            return Err("CSF lines must be ASCII".to_string());
+        }
+        let subshell_count = line1.len() / BLOCK_WIDTH;
+
+        // Total J and parity sit at the very end of line3 ("... 7/2      4-")
+        let parity_char = line3.chars().last().ok_or("line3 is empty")?;
+        let parity = Parity::from_sign(parity_char)
+            .ok_or_else(|| format!("line3 does not end with a parity sign: {:?}", line3))?;
+        let body = &line3[..line3.len() - 1];
+        let final_start = body.trim_end().rfind(' ').map_or(0, |p| p + 1);
+        let final_double_j = j_to_double_j(&body[final_start..])?;
+        let couplings = &body[..final_start];
+
+        if line2.len() > subshell_count * BLOCK_WIDTH {
+            return Err("line2 has values beyond the last subshell".to_string());
+        }
+
+        let mut subshells = Vec::with_capacity(subshell_count);
+        for i in 0..subshell_count {
+            let (label, occupation) =
+                parse_subshell_block(&line1[i * BLOCK_WIDTH..(i + 1) * BLOCK_WIDTH])?;
+
+            let intermediate = field(line2, i * BLOCK_WIDTH, BLOCK_WIDTH);
+            let (seniority, intermediate_double_j) = if intermediate.trim().is_empty() {
+                (None, None)
+            } else {
+                let (seniority, j) = parse_j_field(intermediate)?;
+                (seniority, Some(j))
+            };
+
+            let coupled = field(couplings, COUPLING_OFFSET + i * BLOCK_WIDTH, BLOCK_WIDTH);
+            let coupled_double_j = if coupled.trim().is_empty() {
+                None
+            } else {
+                Some(j_to_double_j(coupled)?)
+            };
+
+            subshells.push(CsfSubshell {
+                label: label.to_string(),
+                occupation,
+                seniority,
+                intermediate_double_j,
+                coupled_double_j,
+            });
+        }
+
+        Ok(Csf {
+            subshells,
+            final_double_j,
+            parity,
+        })
+    }
+
+    /// Number of electrons in the listed subshells (the closed core is not part of a CSF)
+    pub fn electron_count(&self) -> u32 {
+        self.subshells.iter().map(|s| s.occupation).sum()
+    }
+
+    /// Parity implied by the occupations, (-1)^(sum of occupation * l)
+    pub fn computed_parity(&self) -> Result<Parity, String> {
+        let mut odd_electrons = 0;
+        for subshell in &self.subshells {
+            if subshell.orbital()?.is_odd() {
+                odd_electrons += subshell.occupation;
+            }
+        }
+        Ok(Parity::from_odd_electrons(odd_electrons))
+    }
+
+    /// Check the declared parity sign against the parity implied by the occupations
+    pub fn check_parity(&self) -> Result<(), String> {
+        let computed = self.computed_parity()?;
+        if computed != self.parity {
+            return Err(format!(
+                "Declared parity {} does not match parity {} of the occupations",
+                self.parity, computed
+            ));
+        }
+        Ok(())
+    }
+
+    /// Format the CSF back into its three GRASP fixed-width lines
+    ///
+    /// Lines carry no trailing whitespace, matching `rcsfgenerate` output.
+    pub fn to_lines(&self) -> [String; 3] {
+        let count = self.subshells.len();
+        let mut line1 = String::with_capacity(count * BLOCK_WIDTH);
+        let mut line2 = String::with_capacity(count * BLOCK_WIDTH);
+        let mut line3 = String::with_capacity(count * BLOCK_WIDTH + COUPLING_OFFSET + 2);
+        line3.push_str(&" ".repeat(COUPLING_OFFSET));
+
+        for (i, subshell) in self.subshells.iter().enumerate() {
+            line1.push_str(&format!(
+                "{}({:>2})",
+                format_label(&subshell.label),
+                subshell.occupation
+            ));
+
+            match (subshell.seniority, subshell.intermediate_double_j) {
+                (Some(v), Some(j)) => {
+                    line2.push_str(&format!("{:>4};{:>4}", v, double_j_to_string(j)))
+                }
+                (None, Some(j)) => line2.push_str(&format!("{:>9}", double_j_to_string(j))),
+                _ => line2.push_str(&" ".repeat(BLOCK_WIDTH)),
+            }
+
+            if i + 1 < count {
+                match subshell.coupled_double_j {
+                    Some(j) => line3.push_str(&format!("{:>8} ", double_j_to_string(j))),
+                    None => line3.push_str(&" ".repeat(BLOCK_WIDTH)),
+                }
+            }
+        }
+        line3.push_str(&format!(
+            "{:>6}{}",
+            double_j_to_string(self.final_double_j),
+            self.parity
+        ));
+
+        let line2 = line2.trim_end().to_string();
+        [line1, line2, line3]
+    }
+}
+
+impl fmt::Display for Csf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [line1, line2, line3] = self.to_lines();
+        write!(f, "{}\n{}\n{}", line1, line2, line3)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Python-exposed typed CSF
+#[cfg(feature = "python")]
+#[pyclass(name = "Csf")]
+pub struct PyCsf {
+    inner: Csf,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyCsf {
+    /// Parse a CSF from its three lines
+    #[staticmethod]
+    fn parse(line1: &str, line2: &str, line3: &str) -> PyResult<Self> {
+        Csf::parse(line1, line2, line3)
+            .map(|inner| Self { inner })
+            .map_err(|e| crate::error::format_error(e, None, None))
+    }
+
+    /// Subshells as a list of dictionaries
+    ///
+    /// Keys: label, occupation, seniority, intermediate_double_j, coupled_double_j
+    #[getter]
+    fn subshells(&self, py: Python) -> PyResult<Vec<pyo3::Py<pyo3::PyAny>>> {
+        use pyo3::types::{PyDict, PyDictMethods};
+
+        self.inner
+            .subshells
+            .iter()
+            .map(|s| {
+                let dict = PyDict::new(py);
+                dict.set_item("label", &s.label)?;
+                dict.set_item("occupation", s.occupation)?;
+                dict.set_item("seniority", s.seniority)?;
+                dict.set_item("intermediate_double_j", s.intermediate_double_j)?;
+                dict.set_item("coupled_double_j", s.coupled_double_j)?;
+                Ok(dict.into())
+            })
+            .collect()
+    }
+
+    /// Total J of the CSF (2J)
+    #[getter]
+    fn final_double_j(&self) -> i32 {
+        self.inner.final_double_j
+    }
+
+    /// Parity sign ("+" or "-")
+    #[getter]
+    fn parity(&self) -> String {
+        self.inner.parity.to_string()
+    }
+
+    /// Number of electrons in the listed subshells
+    #[getter]
+    fn electron_count(&self) -> u32 {
+        self.inner.electron_count()
+    }
+
+    /// Parity sign implied by the occupations ("+" or "-")
+    #[getter]
+    fn computed_parity(&self) -> PyResult<String> {
+        self.inner
+            .computed_parity()
+            .map(|p| p.to_string())
+            .map_err(|e| crate::error::format_error(e, None, None))
+    }
+
+    /// Raise CSFFormatError if the declared parity disagrees with the occupations
+    fn check_parity(&self) -> PyResult<()> {
+        self.inner
+            .check_parity()
+            .map_err(|e| crate::error::format_error(e, None, None))
+    }
+
+    /// Format back into the three GRASP lines
+    fn to_lines(&self) -> Vec<String> {
+        self.inner.to_lines().to_vec()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+/// Register the Python classes of this module
+#[cfg(feature = "python")]
+pub fn register_csf_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyCsf>()?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE1: &str = "  5s ( 2)  4d-( 4)  4d ( 6)  5p-( 2)  5p ( 4)  6s ( 2)  4f-( 3)  4f ( 4)  5d-( 1)";
+    const LINE2: &str = "                                                            9/2        8      3/2";
+    const LINE3: &str = "                                                                        7/2      4-";
+
+    #[test]
+    fn test_double_j_to_string() {
+        assert_eq!(double_j_to_string(3), "3/2");
+        assert_eq!(double_j_to_string(8), "4");
+        assert_eq!(double_j_to_string(0), "0");
+    }
+
+    #[test]
+    fn test_parse_csf() {
+        let csf = Csf::parse(LINE1, LINE2, LINE3).unwrap();
+        assert_eq!(csf.subshells.len(), 9);
+        assert_eq!(csf.final_double_j, 8);
+        assert_eq!(csf.parity, Parity::Odd);
+
+        let f_minus = &csf.subshells[6];
+        assert_eq!(f_minus.label, "4f-");
+        assert_eq!(f_minus.occupation, 3);
+        assert_eq!(f_minus.intermediate_double_j, Some(9));
+        assert_eq!(f_minus.coupled_double_j, None);
+
+        let f = &csf.subshells[7];
+        assert_eq!(f.intermediate_double_j, Some(16));
+        assert_eq!(f.coupled_double_j, Some(7));
+
+        assert_eq!(csf.subshells[0].intermediate_double_j, None);
+    }
+
+    #[test]
+    fn test_parse_seniority() {
+        let line2 = "                                                            3/2   4;   4      3/2";
+        let csf = Csf::parse(LINE1, line2, LINE3).unwrap();
+        assert_eq!(csf.subshells[7].seniority, Some(4));
+        assert_eq!(csf.subshells[7].intermediate_double_j, Some(8));
+    }
+
+    #[test]
+    fn test_electrons_and_parity() {
+        let csf = Csf::parse(LINE1, LINE2, LINE3).unwrap();
+        assert_eq!(csf.electron_count(), 28);
+        // 5p-(2) 5p(4) 4f-(3) 4f(4): 13 electrons in odd-l subshells
+        assert_eq!(csf.computed_parity().unwrap(), Parity::Odd);
+        assert!(csf.check_parity().is_ok());
+        assert_eq!(configuration_summary(LINE1).unwrap(), (28, Parity::Odd));
+        assert_eq!(declared_parity(LINE3), Some(Parity::Odd));
+
+        let wrong = LINE3.replace("4-", "4+");
+        let csf = Csf::parse(LINE1, LINE2, &wrong).unwrap();
+        assert!(csf.check_parity().is_err());
+    }
+
+    #[test]
+    fn test_subshell_orbital() {
+        let csf = Csf::parse(LINE1, LINE2, LINE3).unwrap();
+        let orbital = csf.subshells[1].orbital().unwrap();
+        assert_eq!(orbital.max_occupancy(), 4);
+        assert!(orbital.check_occupation(csf.subshells[1].occupation).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let csf = Csf::parse(LINE1, LINE2, LINE3).unwrap();
+        assert_eq!(csf.to_lines(), [LINE1, LINE2, LINE3]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Csf::parse("", LINE2, LINE3).is_err());
+        assert!(Csf::parse("  5s ( 2", "", "  0+").is_err());
+        assert!(Csf::parse("  5s ( x)", "", "  0+").is_err());
+        assert!(Csf::parse(LINE1, LINE2, "      4").is_err());
+    }
+}
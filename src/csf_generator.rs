@@ -0,0 +1,866 @@
+//! Native CSF Generator
+//!
+//! Enumerates jj-coupled CSFs from reference configurations and excitation
+//! rules, the job `rcsfgenerate` does in GRASP, and streams them straight into
+//! the Parquet table written by the converter (`idx`, `line1`..`line3`,
+//! `block`, ...) together with its `_header.toml`. The output can be fed to the
+//! descriptor generator or written back to a `.csf` file with `parquet_to_csfs`.
+//!
+//! Generation works in three steps:
+//!
+//! 1. Non-relativistic configurations: every reference is expanded by moving
+//!    up to `excitation_rank` electrons between the active shells. Reference
+//!    shells that are not active keep their occupation.
+//! 2. Relativistic configurations: each nl shell is split into its `nl-` and
+//!    `nl` subshells in every possible way.
+//! 3. Couplings: subshell J values are coupled left to right, every chain
+//!    ending on a requested total J becomes one CSF.
+//!
+//! CSFs are grouped into J/parity blocks by increasing J, even parity first.
+
+use crate::coupling::{subshell_states, triangle};
+use crate::csf::{Csf, CsfSubshell, Parity};
+use crate::csf_header::CsfHeader;
+use crate::csfs_conversion::{
+    append_header_metadata, build_csf_batch, csf_table_schema, header_file_path, write_header_file, ConsistencyCheck,
+    ConversionStats, HeaderData, HeaderInfo, PartialOutput, RawCsf,
+};
+use crate::error::CsfError;
+use crate::orbital::{l_symbol, Orbital};
+use crate::writer_options::{compression_name, WriterOptions};
+use parquet::arrow::ArrowWriter;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::Path;
+
+/// A non-relativistic shell (n, l)
+pub(crate) type Shell = (u32, u32);
+
+/// Subshell states `(seniority, 2J)` as returned by `subshell_states`
+type SubshellStates = Vec<(Option<u32>, i32)>;
+
+/// Parse a non-relativistic shell label such as "3p"
+fn parse_shell(label: &str) -> Result<Shell, String> {
+    let label = label.trim();
+    if label.ends_with('-') {
+        return Err(format!(
+            "Expected a non-relativistic shell such as \"3p\", found {:?}",
+            label
+        ));
+    }
+    label.parse::<Orbital>().map(|o| o.nl())
+}
+
+/// Maximum number of electrons in a non-relativistic shell, 2(2l+1)
+fn shell_capacity((_, l): Shell) -> u32 {
+    2 * (2 * l + 1)
+}
+
+/// Label of a non-relativistic shell, e.g. "3p"
+pub(crate) fn shell_label((n, l): Shell) -> String {
+    format!("{}{}", n, l_symbol(l).unwrap_or('?'))
+}
+
+/// The relativistic subshells of a shell in GRASP order (`nl-` before `nl`)
+fn relativistic_subshells((n, l): Shell) -> Vec<Orbital> {
+    let mut subshells = Vec::with_capacity(2);
+    if l > 0 {
+        subshells.extend(Orbital::from_nlj(n, l, true));
+    }
+    subshells.extend(Orbital::from_nlj(n, l, false));
+    subshells
+}
+
+/// Parse a non-relativistic configuration such as "3s(2) 3p(1)" or "3s(2)3p(1)"
+///
+/// # Returns
+/// * `Ok(Vec<(Shell, u32)>)` - Shells and occupations in the order written
+/// * `Err(String)` - If a shell is malformed, repeated or over-occupied
+pub(crate) fn parse_configuration(text: &str) -> Result<Vec<(Shell, u32)>, String> {
+    let mut shells: Vec<(Shell, u32)> = Vec::new();
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        let open = rest
+            .find('(')
+            .ok_or_else(|| format!("Missing '(' in configuration {:?}", text))?;
+        let close = rest[open..]
+            .find(')')
+            .map(|p| p + open)
+            .ok_or_else(|| format!("Missing ')' in configuration {:?}", text))?;
+
+        let shell = parse_shell(&rest[..open])?;
+        let occupation: u32 = rest[open + 1..close]
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid occupation in configuration {:?}", text))?;
+        if occupation > shell_capacity(shell) {
+            return Err(format!(
+                "{} holds at most {} electrons, found {} in {:?}",
+                shell_label(shell),
+                shell_capacity(shell),
+                occupation,
+                text
+            ));
+        }
+        if shells.iter().any(|(s, _)| *s == shell) {
+            return Err(format!(
+                "{} appears twice in configuration {:?}",
+                shell_label(shell),
+                text
+            ));
+        }
+        shells.push((shell, occupation));
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',' || c == '.');
+    }
+
+    if shells.is_empty() {
+        return Err("Empty reference configuration".to_string());
+    }
+    Ok(shells)
+}
+
+/// Settings of a CSF generation run
+#[derive(Debug, Clone, Default)]
+pub struct CsfGeneratorConfig {
+    /// Closed core shells (e.g. "1s", "2p"), written to the header only
+    pub core_shells: Vec<String>,
+    /// Reference configurations over the peel shells, e.g. "3s(2) 3p(1)"
+    pub references: Vec<String>,
+    /// Shells electrons may be excited from and into
+    pub active_shells: Vec<String>,
+    /// Maximum number of electrons moved relative to a reference (1 = S, 2 = SD, 3 = SDT)
+    pub excitation_rank: u32,
+    /// Lowest and highest total 2J to keep, `None` for every J
+    pub double_j_range: Option<(i32, i32)>,
+    /// Parity to keep, `None` for both
+    pub parity: Option<Parity>,
+}
+
+/// One relativistic configuration: the occupation of every peel subshell
+struct RelativisticConfiguration {
+    occupations: Vec<u32>,
+    parity: Parity,
+    max_double_j: i32,
+}
+
+/// An occupied subshell of the configuration being coupled
+struct OccupiedSubshell {
+    label: String,
+    occupation: u32,
+    closed: bool,
+    states: SubshellStates,
+}
+
+/// CSF generator for a fixed set of relativistic configurations
+pub struct CsfGenerator {
+    header: CsfHeader,
+    configurations: Vec<RelativisticConfiguration>,
+    electron_count: u32,
+    double_j_range: Option<(i32, i32)>,
+    parity: Option<Parity>,
+    /// Subshell states by (2j, occupation)
+    states: HashMap<(u32, u32), SubshellStates>,
+}
+
+impl CsfGenerator {
+    /// Expand the references into relativistic configurations
+    ///
+    /// # Returns
+    /// * `Ok(CsfGenerator)` - Ready to enumerate CSFs
+    /// * `Err(String)` - If the configuration is inconsistent (unknown shell
+    ///   labels, different electron counts, peel shells in the core, ...)
+    pub fn new(config: &CsfGeneratorConfig) -> Result<Self, String> {
+        if config.references.is_empty() {
+            return Err("At least one reference configuration is required".to_string());
+        }
+
+        let core: Vec<Shell> = config
+            .core_shells
+            .iter()
+            .map(|s| parse_shell(s))
+            .collect::<Result<_, _>>()?;
+        let references: Vec<Vec<(Shell, u32)>> = config
+            .references
+            .iter()
+            .map(|r| parse_configuration(r))
+            .collect::<Result<_, _>>()?;
+        let active: HashSet<Shell> = config
+            .active_shells
+            .iter()
+            .map(|s| parse_shell(s))
+            .collect::<Result<_, _>>()?;
+
+        // Peel shells: first appearance in the references, then the active list
+        let mut peel: Vec<Shell> = Vec::new();
+        let reference_shells = references.iter().flatten().map(|(s, _)| *s);
+        let active_shells = config.active_shells.iter().filter_map(|s| parse_shell(s).ok());
+        for shell in reference_shells.chain(active_shells) {
+            if core.contains(&shell) {
+                return Err(format!(
+                    "{} is both a core shell and a peel shell",
+                    shell_label(shell)
+                ));
+            }
+            if !peel.contains(&shell) {
+                peel.push(shell);
+            }
+        }
+
+        let electron_count: u32 = references[0].iter().map(|(_, q)| q).sum();
+        for (reference, text) in references.iter().zip(&config.references).skip(1) {
+            let count: u32 = reference.iter().map(|(_, q)| q).sum();
+            if count != electron_count {
+                return Err(format!(
+                    "Reference {:?} has {} electrons, expected {}",
+                    text, count, electron_count
+                ));
+            }
+        }
+
+        // Step 1: non-relativistic configurations, in first-seen order
+        let is_active: Vec<bool> = peel.iter().map(|s| active.contains(s)).collect();
+        let mut seen = HashSet::new();
+        let mut nonrelativistic = Vec::new();
+        for reference in &references {
+            let occupations: Vec<u32> = peel
+                .iter()
+                .map(|s| reference.iter().find(|(r, _)| r == s).map_or(0, |(_, q)| *q))
+                .collect();
+            let free: u32 = occupations
+                .iter()
+                .zip(&is_active)
+                .filter(|(_, active)| **active)
+                .map(|(q, _)| q)
+                .sum();
+            let mut current = occupations.clone();
+            distribute_electrons(
+                &peel,
+                &is_active,
+                &occupations,
+                config.excitation_rank,
+                0,
+                free,
+                0,
+                &mut current,
+                &mut |configuration| {
+                    if seen.insert(configuration.to_vec()) {
+                        nonrelativistic.push(configuration.to_vec());
+                    }
+                },
+            );
+        }
+
+        // Step 2: relativistic configurations
+        let subshells: Vec<Orbital> = peel.iter().flat_map(|&s| relativistic_subshells(s)).collect();
+        let mut generator = CsfGenerator {
+            header: CsfHeader {
+                core_subshells: core.iter().flat_map(|&s| relativistic_subshells(s)).collect(),
+                peel_subshells: subshells,
+                format_marker: "CSF(s):".to_string(),
+            },
+            configurations: Vec::new(),
+            electron_count,
+            double_j_range: config.double_j_range,
+            parity: config.parity,
+            states: HashMap::new(),
+        };
+
+        for configuration in &nonrelativistic {
+            let mut occupations = Vec::with_capacity(generator.header.peel_subshells.len());
+            generator.split_relativistic(&peel, configuration, 0, &mut occupations)?;
+        }
+
+        Ok(generator)
+    }
+
+    /// Split every shell of `configuration` from `index` on into `nl-`/`nl` occupations
+    fn split_relativistic(
+        &mut self,
+        peel: &[Shell],
+        configuration: &[u32],
+        index: usize,
+        occupations: &mut Vec<u32>,
+    ) -> Result<(), String> {
+        if index == peel.len() {
+            return self.add_configuration(occupations.clone());
+        }
+
+        let (_, l) = peel[index];
+        let q = configuration[index];
+        if l == 0 {
+            occupations.push(q);
+            self.split_relativistic(peel, configuration, index + 1, occupations)?;
+            occupations.pop();
+            return Ok(());
+        }
+
+        // Fill nl- first, as rcsfgenerate does
+        let (minus_capacity, plus_capacity) = (2 * l, 2 * l + 2);
+        let lowest = q.saturating_sub(plus_capacity);
+        for q_minus in (lowest..=q.min(minus_capacity)).rev() {
+            occupations.push(q_minus);
+            occupations.push(q - q_minus);
+            self.split_relativistic(peel, configuration, index + 1, occupations)?;
+            occupations.truncate(occupations.len() - 2);
+        }
+        Ok(())
+    }
+
+    /// Record one relativistic configuration and the states of its subshells
+    fn add_configuration(&mut self, occupations: Vec<u32>) -> Result<(), String> {
+        let mut odd_electrons = 0;
+        let mut max_double_j = 0;
+        for (orbital, &q) in self.header.peel_subshells.iter().zip(&occupations) {
+            if q == 0 {
+                continue;
+            }
+            if orbital.is_odd() {
+                odd_electrons += q;
+            }
+            let states = match self.states.entry((orbital.two_j(), q)) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    subshell_states(orbital.two_j(), q)
+                        .map_err(|e| format!("{}({}): {}", orbital, q, e))?,
+                ),
+            };
+            max_double_j += states.iter().map(|(_, j)| *j).max().unwrap_or(0);
+        }
+
+        self.configurations.push(RelativisticConfiguration {
+            occupations,
+            parity: Parity::from_odd_electrons(odd_electrons),
+            max_double_j,
+        });
+        Ok(())
+    }
+
+    /// Header of the generated CSF list
+    pub fn header(&self) -> &CsfHeader {
+        &self.header
+    }
+
+    /// Number of relativistic configurations
+    pub fn configuration_count(&self) -> usize {
+        self.configurations.len()
+    }
+
+    /// Number of peel electrons in every CSF
+    pub fn electron_count(&self) -> u32 {
+        self.electron_count
+    }
+
+    /// Enumerate every CSF block by block
+    ///
+    /// `emit` receives the block index and the CSF. Only blocks containing at
+    /// least one CSF get an index, so block indices are consecutive.
+    pub fn for_each_csf<E>(
+        &self,
+        mut emit: impl FnMut(u32, Csf) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let max_double_j = self.configurations.iter().map(|c| c.max_double_j).max().unwrap_or(0);
+        let (low, high) = self.double_j_range.unwrap_or((0, max_double_j));
+        let parities: Vec<Parity> = match self.parity {
+            Some(parity) => vec![parity],
+            None => vec![Parity::Even, Parity::Odd],
+        };
+
+        let mut block = 0u32;
+        let first = (self.electron_count % 2) as i32;
+        for double_j in (first..=high.min(max_double_j)).step_by(2).filter(|&j| j >= low) {
+            for &parity in &parities {
+                let mut emitted = false;
+                let mut emit_in_block = |csf: Csf| {
+                    emitted = true;
+                    emit(block, csf)
+                };
+                for configuration in &self.configurations {
+                    if configuration.parity != parity || configuration.max_double_j < double_j {
+                        continue;
+                    }
+                    let occupied = self.occupied_subshells(configuration);
+                    let mut suffix_max = vec![0; occupied.len() + 1];
+                    for i in (0..occupied.len()).rev() {
+                        let max = occupied[i].states.iter().map(|(_, j)| *j).max().unwrap_or(0);
+                        suffix_max[i] = suffix_max[i + 1] + max;
+                    }
+                    let mut current = Vec::with_capacity(occupied.len());
+                    couple(
+                        &occupied,
+                        &suffix_max,
+                        0,
+                        None,
+                        double_j,
+                        parity,
+                        &mut current,
+                        &mut emit_in_block,
+                    )?;
+                }
+                if emitted {
+                    block += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The occupied subshells of a configuration with their allowed states
+    fn occupied_subshells(&self, configuration: &RelativisticConfiguration) -> Vec<OccupiedSubshell> {
+        self.header
+            .peel_subshells
+            .iter()
+            .zip(&configuration.occupations)
+            .filter(|(_, q)| **q > 0)
+            .map(|(orbital, &q)| OccupiedSubshell {
+                label: orbital.to_string(),
+                occupation: q,
+                closed: q == orbital.max_occupancy(),
+                states: self.states[&(orbital.two_j(), q)].clone(),
+            })
+            .collect()
+    }
+
+    /// Enumerate every CSF and write it to a Parquet file
+    ///
+    /// The table has the same schema as `convert_csfs_to_parquet` output and
+    /// `[output_stem]_header.toml` is written next to it, so the result works
+    /// with the descriptor generator and `parquet_to_csfs` unchanged. Both files
+    /// are removed again if generation fails.
+    ///
+    /// # Arguments
+    /// * `output_path` - Path to output Parquet file
+    /// * `chunk_size` - Number of CSFs per record batch
+    /// * `writer_options` - Codec, row groups and encodings of the output file
+    pub fn write_parquet(
+        &self,
+        output_path: &Path,
+        chunk_size: usize,
+        writer_options: &WriterOptions,
+    ) -> crate::error::Result<ConversionStats> {
+        let props = writer_options.properties(parquet::basic::Compression::UNCOMPRESSED)?;
+        println!("开始生成 CSF");
+        println!("相对论组态数: {}", self.configurations.len());
+        println!("输出文件: {:?}", output_path);
+
+        let schema = csf_table_schema();
+        let mut partial = PartialOutput::default();
+        let output_file = File::create(output_path).map_err(|e| CsfError::io(output_path, e))?;
+        partial.track(output_path.to_path_buf());
+        partial.track(header_file_path(output_path, output_path));
+        println!("Parquet 写入器已创建，压缩: {}", compression_name(&props));
+        let mut writer = ArrowWriter::try_new(output_file, schema.clone(), Some(props))?;
+
+        let line_len = 9 * self.header.peel_subshells.len() + 16;
+        let chunk_size = chunk_size.max(1);
+        let mut consistency = ConsistencyCheck::new(self.header.closed_core_electrons());
+        let mut batch_records: Vec<RawCsf> = Vec::with_capacity(chunk_size);
+        let mut block_csf_counts: Vec<usize> = Vec::new();
+        let mut csf_count = 0;
+
+        self.for_each_csf(|block, csf| -> crate::error::Result<()> {
+            if block as usize == block_csf_counts.len() {
+                block_csf_counts.push(0);
+            }
+            block_csf_counts[block as usize] += 1;
+            batch_records.push(RawCsf {
+                block,
+                lines: csf.to_lines(),
+            });
+
+            if batch_records.len() == chunk_size {
+                let batch =
+                    build_csf_batch(&schema, csf_count, &batch_records, line_len, &mut consistency)?;
+                writer.write(&batch)?;
+                csf_count += batch_records.len();
+                batch_records.clear();
+                println!("已生成 {} 个 CSF", csf_count);
+            }
+            Ok(())
+        })?;
+
+        if !batch_records.is_empty() {
+            let batch = build_csf_batch(&schema, csf_count, &batch_records, line_len, &mut consistency)?;
+            writer.write(&batch)?;
+            csf_count += batch_records.len();
+        }
+
+        if csf_count == 0 {
+            println!("警告: 没有满足 J/宇称条件的 CSF");
+        }
+
+        let separators = block_csf_counts.len().saturating_sub(1);
+        let header_data = HeaderData {
+            header_info: HeaderInfo {
+                header_lines: self.header.to_lines().to_vec(),
+            },
+            conversion_stats: ConversionStats {
+                csf_count,
+                total_lines: 3 * csf_count + separators,
+                truncated_count: 0,
+                block_csf_counts,
+                electron_count: consistency.electron_count,
+                electron_count_mismatch_count: consistency.electron_count_mismatch_count,
+                parity_mismatch_count: consistency.parity_mismatch_count,
+                unparsed_count: consistency.unparsed_count,
+            },
+        };
+        append_header_metadata(&mut writer, &header_data, None)?;
+        writer.close()?;
+        let header_path = write_header_file(output_path, output_path, &header_data)?;
+        partial.keep();
+
+        println!("\n生成完成！");
+        println!("CSF 数量: {}", csf_count);
+        println!("Block 数量: {}", header_data.conversion_stats.block_csf_counts.len());
+        println!("Header 文件: {:?}", header_path);
+
+        Ok(header_data.conversion_stats)
+    }
+}
+
+/// Distribute `free` electrons over the active shells from `index` on
+///
+/// Inactive shells keep the reference occupation. `holes` counts the electrons
+/// removed from reference shells so far, which must stay within `rank`.
+#[allow(clippy::too_many_arguments)]
+fn distribute_electrons(
+    peel: &[Shell],
+    is_active: &[bool],
+    reference: &[u32],
+    rank: u32,
+    index: usize,
+    free: u32,
+    holes: u32,
+    current: &mut Vec<u32>,
+    emit: &mut dyn FnMut(&[u32]),
+) {
+    if index == peel.len() {
+        if free == 0 {
+            emit(current);
+        }
+        return;
+    }
+    if !is_active[index] {
+        distribute_electrons(peel, is_active, reference, rank, index + 1, free, holes, current, emit);
+        return;
+    }
+
+    let capacity = shell_capacity(peel[index]);
+    let lowest = reference[index].saturating_sub(rank - holes);
+    for q in (lowest..=capacity.min(free)).rev() {
+        current[index] = q;
+        let removed = reference[index].saturating_sub(q);
+        distribute_electrons(
+            peel,
+            is_active,
+            reference,
+            rank,
+            index + 1,
+            free - q,
+            holes + removed,
+            current,
+            emit,
+        );
+    }
+    current[index] = reference[index];
+}
+
+/// Couple the subshells from `pos` on, emitting every chain that ends on `target`
+#[allow(clippy::too_many_arguments)]
+fn couple<E>(
+    occupied: &[OccupiedSubshell],
+    suffix_max: &[i32],
+    pos: usize,
+    running: Option<i32>,
+    target: i32,
+    parity: Parity,
+    current: &mut Vec<CsfSubshell>,
+    emit: &mut dyn FnMut(Csf) -> Result<(), E>,
+) -> Result<(), E> {
+    if pos == occupied.len() {
+        return emit(Csf {
+            subshells: current.clone(),
+            final_double_j: target,
+            parity,
+        });
+    }
+
+    let x = running.unwrap_or(0);
+    if x - suffix_max[pos] > target || x + suffix_max[pos] < target {
+        return Ok(());
+    }
+
+    let subshell = &occupied[pos];
+    let is_last = pos + 1 == occupied.len();
+    for &(seniority, j) in &subshell.states {
+        let mut entry = CsfSubshell {
+            label: subshell.label.clone(),
+            occupation: subshell.occupation,
+            seniority,
+            intermediate_double_j: (!subshell.closed).then_some(j),
+            coupled_double_j: None,
+        };
+
+        if is_last {
+            if triangle(x, j, target) {
+                current.push(entry);
+                couple(occupied, suffix_max, pos + 1, running, target, parity, current, emit)?;
+                current.pop();
+            }
+        } else if subshell.closed || running.is_none() {
+            // Closed subshells keep the coupling, the first open one starts it
+            let next = if subshell.closed { running } else { Some(j) };
+            current.push(entry);
+            couple(occupied, suffix_max, pos + 1, next, target, parity, current, emit)?;
+            current.pop();
+        } else {
+            for coupled in ((x - j).abs()..=x + j).step_by(2) {
+                entry.coupled_double_j = Some(coupled);
+                current.push(entry.clone());
+                couple(occupied, suffix_max, pos + 1, Some(coupled), target, parity, current, emit)?;
+                current.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Generate jj-coupled CSFs and write them to a Parquet file
+///
+/// Args:
+///     output_path: Path to output Parquet file
+///     references: Reference configurations, e.g. ["3s(2) 3p(1)"]
+///     active_shells: Shells electrons may be excited from and into, e.g. ["3s", "3p", "3d"]
+///     excitation_rank: Maximum number of excited electrons (1 = S, 2 = SD, 3 = SDT)
+///     core_shells: Closed core shells written to the header (e.g. ["1s", "2s", "2p"])
+///     double_j_min: Lowest total 2J to keep (default: all)
+///     double_j_max: Highest total 2J to keep (default: all)
+///     parity: "+" or "-" to keep a single parity (default: both)
+///     chunk_size: Number of CSFs per record batch (default: 1000000)
+///     compression: Parquet codec (default: None, uncompressed)
+///     compression_level: Level for gzip, brotli and zstd (default: codec default)
+///     row_group_rows: Most rows per row group (default: Parquet's 1M rows)
+///     dictionary: Dictionary-encode the columns (default: True)
+///     statistics: Write column statistics (default: True)
+///     data_page_size: Target data page size in bytes (default: Parquet's 1 MiB)
+///
+/// Returns:
+///     Dictionary with generation statistics
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    output_path,
+    references,
+    active_shells,
+    excitation_rank=2,
+    core_shells=None,
+    double_j_min=None,
+    double_j_max=None,
+    parity=None,
+    chunk_size=None,
+    compression=None,
+    compression_level=None,
+    row_group_rows=None,
+    dictionary=true,
+    statistics=true,
+    data_page_size=None
+))]
+#[allow(clippy::too_many_arguments)]
+fn generate_csfs(
+    py: Python,
+    output_path: String,
+    references: Vec<String>,
+    active_shells: Vec<String>,
+    excitation_rank: u32,
+    core_shells: Option<Vec<String>>,
+    double_j_min: Option<i32>,
+    double_j_max: Option<i32>,
+    parity: Option<String>,
+    chunk_size: Option<usize>,
+    compression: Option<&str>,
+    compression_level: Option<u32>,
+    row_group_rows: Option<usize>,
+    dictionary: bool,
+    statistics: bool,
+    data_page_size: Option<usize>,
+) -> PyResult<Py<PyAny>> {
+    use pyo3::exceptions::PyValueError;
+    use pyo3::types::PyDict;
+
+    let parity = match parity.as_deref() {
+        None => None,
+        Some(sign) => Some(
+            sign.chars()
+                .next()
+                .filter(|_| sign.len() == 1)
+                .and_then(Parity::from_sign)
+                .ok_or_else(|| PyValueError::new_err(format!("parity must be '+' or '-', got {:?}", sign)))?,
+        ),
+    };
+    let double_j_range = match (double_j_min, double_j_max) {
+        (None, None) => None,
+        (low, high) => Some((low.unwrap_or(0), high.unwrap_or(i32::MAX))),
+    };
+
+    let config = CsfGeneratorConfig {
+        core_shells: core_shells.unwrap_or_default(),
+        references,
+        active_shells,
+        excitation_rank,
+        double_j_range,
+        parity,
+    };
+    if config.references.is_empty() {
+        return Err(PyValueError::new_err("At least one reference configuration is required"));
+    }
+    // Unparsable or inconsistent shells and configurations
+    let generator = CsfGenerator::new(&config).map_err(|e| crate::error::format_error(e, None, None))?;
+    let chunk_size = chunk_size.unwrap_or(1_000_000);
+    let writer_options = crate::writer_options::writer_options_from_kwargs(
+        compression,
+        compression_level,
+        row_group_rows,
+        dictionary,
+        statistics,
+        data_page_size,
+    )?;
+
+    let output = Path::new(&output_path);
+    let stats = py.detach(|| generator.write_parquet(output, chunk_size, &writer_options))?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("success", true)?;
+    dict.set_item("output_file", &output_path)?;
+    dict.set_item("configuration_count", generator.configuration_count())?;
+    dict.set_item("csf_count", stats.csf_count)?;
+    dict.set_item("block_count", stats.block_csf_counts.len())?;
+    dict.set_item("block_csf_counts", stats.block_csf_counts)?;
+    dict.set_item("peel_subshells", generator.header().peel_labels())?;
+    // The file `write_parquet` wrote; it fails if the header cannot be written
+    dict.set_item("header_file", header_file_path(output, output).to_string_lossy())?;
+    Ok(dict.into())
+}
+
+/// Register the Python functions of this module
+#[cfg(feature = "python")]
+pub fn register_generator_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(generate_csfs, module)?)?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(config: &CsfGeneratorConfig) -> Vec<(u32, Csf)> {
+        let generator = CsfGenerator::new(config).unwrap();
+        let mut csfs = Vec::new();
+        generator
+            .for_each_csf(|block, csf| -> Result<(), ()> {
+                csfs.push((block, csf));
+                Ok(())
+            })
+            .unwrap();
+        csfs
+    }
+
+    fn reference(text: &str) -> CsfGeneratorConfig {
+        CsfGeneratorConfig {
+            references: vec![text.to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn count_per_double_j(csfs: &[(u32, Csf)]) -> Vec<(i32, usize)> {
+        let mut counts: Vec<(i32, usize)> = Vec::new();
+        for (_, csf) in csfs {
+            match counts.iter_mut().find(|(j, _)| *j == csf.final_double_j) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((csf.final_double_j, 1)),
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_parse_configuration() {
+        assert_eq!(parse_configuration("3s(2) 3p(1)").unwrap(), vec![((3, 0), 2), ((3, 1), 1)]);
+        assert_eq!(parse_configuration("3s(2)3p(1)").unwrap().len(), 2);
+        assert!(parse_configuration("3p(7)").is_err());
+        assert!(parse_configuration("3p-(1)").is_err());
+        assert!(parse_configuration("3s(1) 3s(1)").is_err());
+        assert!(parse_configuration("").is_err());
+    }
+
+    #[test]
+    fn test_p_squared() {
+        // 3P0,1,2 1D2 1S0
+        let csfs = generate(&reference("2p(2)"));
+        assert_eq!(count_per_double_j(&csfs), vec![(0, 2), (2, 1), (4, 2)]);
+        assert!(csfs.iter().all(|(_, csf)| csf.parity == Parity::Even));
+        assert_eq!(csfs.last().unwrap().0, 2);
+    }
+
+    #[test]
+    fn test_level_counts() {
+        // d3 has 19 levels and f4 has 107
+        assert_eq!(generate(&reference("3d(3)")).len(), 19);
+        assert_eq!(generate(&reference("4f(4)")).len(), 107);
+    }
+
+    #[test]
+    fn test_single_and_double_excitations() {
+        let mut config = reference("2s(2)");
+        config.active_shells = vec!["2s".to_string(), "2p".to_string()];
+        config.excitation_rank = 2;
+        // 2s2 (1) + 2s2p (4) + 2p2 (5)
+        assert_eq!(generate(&config).len(), 10);
+
+        config.parity = Some(Parity::Even);
+        assert_eq!(generate(&config).len(), 6);
+
+        config.excitation_rank = 1;
+        config.parity = None;
+        assert_eq!(generate(&config).len(), 5);
+
+        config.double_j_range = Some((2, 2));
+        assert_eq!(generate(&config).len(), 2);
+    }
+
+    #[test]
+    fn test_inconsistent_configuration() {
+        let mut config = reference("2s(2)");
+        config.references.push("2s(1)".to_string());
+        assert!(CsfGenerator::new(&config).is_err());
+
+        let mut config = reference("2s(2)");
+        config.core_shells = vec!["2s".to_string()];
+        assert!(CsfGenerator::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_generated_csfs_are_valid() {
+        let mut config = reference("3s(2) 3p(2)");
+        config.active_shells = vec!["3s".into(), "3p".into(), "3d".into()];
+        config.excitation_rank = 2;
+        for (_, csf) in generate(&config) {
+            let [line1, line2, line3] = csf.to_lines();
+            let errors = crate::validation::validate_csf_lines(&line1, &line2, &line3);
+            assert!(errors.is_empty(), "{}\n{:?}", csf, errors);
+            assert_eq!(Csf::parse(&line1, &line2, &line3).unwrap(), csf);
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! CSF File Header
+//!
+//! Every GRASP CSF list starts with a fixed 5-line header:
+//!
+//! ```text
+//! Core subshells:
+//!   1s   2s   2p-  2p
+//! Peel subshells:
+//!   3s   3p-  3p   3d-  3d
+//! CSF(s):
+//! ```
+//!
+//! `CsfHeader` parses these lines into typed subshell lists. It can be read
+//! from the `.csf` text itself or from the `[stem]_header.toml` file written
+//! by the converter.
+
+use crate::csf::format_label;
+use crate::csf_reader::CsfInput;
+use crate::orbital::Orbital;
+use std::path::Path;
+
+/// Number of header lines at the beginning of a CSF file
+pub const CSF_HEADER_LINE_COUNT: usize = 5;
+
+/// Section marker expected on header line 1
+const CORE_MARKER: &str = "Core subshells:";
+
+/// Section marker expected on header line 3
+const PEEL_MARKER: &str = "Peel subshells:";
+
+/// Section marker expected on header line 5
+const CSF_MARKER: &str = "CSF(s):";
+
+/// Parsed 5-line header of a GRASP CSF list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsfHeader {
+    /// Closed core subshells (header line 2), may be empty
+    pub core_subshells: Vec<Orbital>,
+    /// Peel subshells (header line 4), in the order used by the CSF lines
+    pub peel_subshells: Vec<Orbital>,
+    /// Marker introducing the CSF section (header line 5), normally "CSF(s):"
+    pub format_marker: String,
+}
+
+/// Check that a header line carries the expected section marker
+fn expect_marker(line: &str, marker: &str, line_number: usize) -> Result<(), String> {
+    let found = line.trim();
+    let expected = marker.trim_end_matches(':');
+    if !found.trim_end_matches(':').eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "Malformed CSF header: line {} should be {:?}, found {:?}",
+            line_number, marker, found
+        ));
+    }
+    Ok(())
+}
+
+/// Parse a whitespace-separated list of subshell labels
+fn parse_subshell_list(line: &str, line_number: usize) -> Result<Vec<Orbital>, String> {
+    line.split_whitespace()
+        .map(|label| {
+            label.parse::<Orbital>().map_err(|e| {
+                format!("Malformed CSF header: line {}: {}", line_number, e)
+            })
+        })
+        .collect()
+}
+
+/// Format a subshell list the way GRASP writes it (5 characters per label)
+fn format_subshell_list(orbitals: &[Orbital]) -> String {
+    orbitals
+        .iter()
+        .map(|o| format_label(&o.to_string()))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+impl CsfHeader {
+    /// Parse the header from its 5 lines
+    ///
+    /// # Arguments
+    /// * `lines` - The first 5 lines of a CSF file
+    ///
+    /// # Returns
+    /// * `Ok(CsfHeader)` - Parsed header
+    /// * `Err(String)` - Which line is malformed and why
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Result<Self, String> {
+        if lines.len() < CSF_HEADER_LINE_COUNT {
+            return Err(format!(
+                "Malformed CSF header: expected {} lines, found {}",
+                CSF_HEADER_LINE_COUNT,
+                lines.len()
+            ));
+        }
+        let line = |i: usize| lines[i].as_ref();
+
+        expect_marker(line(0), CORE_MARKER, 1)?;
+        let core_subshells = parse_subshell_list(line(1), 2)?;
+        expect_marker(line(2), PEEL_MARKER, 3)?;
+        let peel_subshells = parse_subshell_list(line(3), 4)?;
+        if peel_subshells.is_empty() {
+            return Err("Malformed CSF header: peel subshell list (line 4) is empty".to_string());
+        }
+        expect_marker(line(4), CSF_MARKER, 5)?;
+
+        Ok(CsfHeader {
+            core_subshells,
+            peel_subshells,
+            format_marker: line(4).trim().to_string(),
+        })
+    }
+
+    /// Read the header from the first 5 lines of a `.csf` file
+    pub fn from_csf_file(csfs_path: &Path) -> Result<Self, String> {
+        let input = CsfInput::open(csfs_path).map_err(|e| format!("Failed to open CSF file: {}", e))?;
+        let lines = input
+            .header_lines()
+            .map_err(|e| format!("Failed to read CSF header: {}", e))?;
+        Self::parse(&lines)
+    }
+
+    /// Read the header from a `[stem]_header.toml` file written by the converter
+    pub fn from_header_toml(header_path: &Path) -> Result<Self, String> {
+        Self::parse(&read_header_lines_from_toml(header_path)?)
+    }
+
+    /// Read the header of a CSF table written by rCSFs, from its Parquet
+    /// metadata or else the `_header.toml` next to it
+    pub fn from_parquet(parquet_path: &Path) -> Result<Self, String> {
+        let (header_data, _) = crate::csfs_conversion::load_header_data(parquet_path, None)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("No CSF header found for {:?}", parquet_path))?;
+        Self::parse(&header_data.header_info.header_lines)
+    }
+
+    /// Number of electrons in the closed core
+    pub fn closed_core_electrons(&self) -> u32 {
+        self.core_subshells.iter().map(|o| o.max_occupancy()).sum()
+    }
+
+    /// Peel subshell labels, as used by `CSFDescriptorGenerator`
+    pub fn peel_labels(&self) -> Vec<String> {
+        self.peel_subshells.iter().map(|o| o.to_string()).collect()
+    }
+
+    /// Core subshell labels
+    pub fn core_labels(&self) -> Vec<String> {
+        self.core_subshells.iter().map(|o| o.to_string()).collect()
+    }
+
+    /// Format the header back into its 5 GRASP lines
+    pub fn to_lines(&self) -> [String; CSF_HEADER_LINE_COUNT] {
+        [
+            CORE_MARKER.to_string(),
+            format_subshell_list(&self.core_subshells),
+            PEEL_MARKER.to_string(),
+            format_subshell_list(&self.peel_subshells),
+            self.format_marker.clone(),
+        ]
+    }
+}
+
+/// Read the raw `header_info.header_lines` array from a header TOML file
+pub fn read_header_lines_from_toml(header_path: &Path) -> Result<Vec<String>, String> {
+    use toml::Value;
+
+    let toml_content = std::fs::read_to_string(header_path)
+        .map_err(|e| format!("Failed to read header file: {}", e))?;
+
+    // Normalize line endings and trim whitespace
+    let toml_content = toml_content.replace("\r\n", "\n");
+    let toml_value: Value = toml::from_str(toml_content.trim())
+        .map_err(|e| format!("Failed to parse TOML: {}", e))?;
+
+    let header_lines = toml_value
+        .get("header_info")
+        .and_then(|v| v.get("header_lines"))
+        .and_then(|v| v.as_array())
+        .ok_or("header_info.header_lines not found in TOML")?;
+
+    header_lines
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            v.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("header_lines[{}] is not a string", i))
+        })
+        .collect()
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Python-exposed CSF header
+#[cfg(feature = "python")]
+#[pyclass(name = "CsfHeader")]
+pub struct PyCsfHeader {
+    inner: CsfHeader,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyCsfHeader {
+    /// Parse a header from its 5 lines
+    #[new]
+    fn new(lines: Vec<String>) -> PyResult<Self> {
+        CsfHeader::parse(&lines)
+            .map(|inner| Self { inner })
+            .map_err(crate::error::CSFHeaderError::new_err)
+    }
+
+    /// Read the header of a `.csf` file
+    #[staticmethod]
+    fn from_csf_file(path: String) -> PyResult<Self> {
+        CsfHeader::from_csf_file(Path::new(&path))
+            .map(|inner| Self { inner })
+            .map_err(crate::error::CSFHeaderError::new_err)
+    }
+
+    /// Read the header from a converter `_header.toml` file
+    #[staticmethod]
+    fn from_header_toml(path: String) -> PyResult<Self> {
+        CsfHeader::from_header_toml(Path::new(&path))
+            .map(|inner| Self { inner })
+            .map_err(crate::error::CSFHeaderError::new_err)
+    }
+
+    /// Read the header of a converted `.parquet` file, from its metadata or
+    /// else its `_header.toml`
+    #[staticmethod]
+    fn from_parquet(path: String) -> PyResult<Self> {
+        CsfHeader::from_parquet(Path::new(&path))
+            .map(|inner| Self { inner })
+            .map_err(crate::error::CSFHeaderError::new_err)
+    }
+
+    #[getter]
+    fn core_subshells(&self) -> Vec<String> {
+        self.inner.core_labels()
+    }
+
+    #[getter]
+    fn peel_subshells(&self) -> Vec<String> {
+        self.inner.peel_labels()
+    }
+
+    #[getter]
+    fn closed_core_electrons(&self) -> u32 {
+        self.inner.closed_core_electrons()
+    }
+
+    #[getter]
+    fn format_marker(&self) -> String {
+        self.inner.format_marker.clone()
+    }
+
+    /// Format back into the 5 header lines
+    fn to_lines(&self) -> Vec<String> {
+        self.inner.to_lines().to_vec()
+    }
+}
+
+/// Register the Python classes of this module
+#[cfg(feature = "python")]
+pub fn register_header_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyCsfHeader>()?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: [&str; 5] = [
+        "Core subshells:",
+        "  1s   2s   2p-  2p",
+        "Peel subshells:",
+        "  3s   3p-  3p   3d-  3d",
+        "CSF(s):",
+    ];
+
+    #[test]
+    fn test_parse_header() {
+        let header = CsfHeader::parse(&HEADER).unwrap();
+        assert_eq!(header.core_labels(), ["1s", "2s", "2p-", "2p"]);
+        assert_eq!(header.peel_labels(), ["3s", "3p-", "3p", "3d-", "3d"]);
+        assert_eq!(header.closed_core_electrons(), 10);
+        assert_eq!(header.format_marker, "CSF(s):");
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = CsfHeader::parse(&HEADER).unwrap();
+        assert_eq!(header.to_lines(), HEADER);
+    }
+
+    #[test]
+    fn test_empty_core() {
+        let mut lines = HEADER;
+        lines[1] = "";
+        let header = CsfHeader::parse(&lines).unwrap();
+        assert!(header.core_subshells.is_empty());
+        assert_eq!(header.closed_core_electrons(), 0);
+    }
+
+    #[test]
+    fn test_malformed_header() {
+        let mut lines = HEADER;
+        lines[2] = "  3s   3p-";
+        let err = CsfHeader::parse(&lines).unwrap_err();
+        assert!(err.contains("line 3"), "{}", err);
+
+        let mut lines = HEADER;
+        lines[3] = "  3s   3x";
+        let err = CsfHeader::parse(&lines).unwrap_err();
+        assert!(err.contains("line 4"), "{}", err);
+
+        let mut lines = HEADER;
+        lines[3] = "";
+        assert!(CsfHeader::parse(&lines).is_err());
+
+        assert!(CsfHeader::parse(&HEADER[..4]).is_err());
+    }
+}
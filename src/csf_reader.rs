@@ -0,0 +1,545 @@
+//! Memory-Mapped CSF Reader
+//!
+//! A `.csf` file is opened once and mapped into memory. The header lines and
+//! the CSF triples are read from the same map, and each triple borrows its
+//! lines as byte slices, so scanning a file makes no heap allocation per line.
+//! The converters, the validator and the excitation analysis all read `.csf`
+//! files through [`CsfInput`].
+//!
+//! Lines end at `\n`; a trailing `\r` is dropped, as `BufRead::lines` does.
+//!
+//! Compressed files (gzip, zstd or xz, recognised by their magic bytes) cannot
+//! be mapped. [`CsfInput`] opens either kind: a compressed file is decompressed
+//! while it is read and handed out in chunks of whole CSFs by [`CsfStream`], so
+//! it is never decompressed to disk or held in memory as a whole.
+
+use crate::csf_header::CSF_HEADER_LINE_COUNT;
+use crate::error::{CsfError, Result};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// A `.csf` file mapped into memory
+pub struct CsfFile {
+    path: PathBuf,
+    mmap: Mmap,
+    /// Byte offset of the first line after the header
+    data_start: usize,
+}
+
+impl CsfFile {
+    /// Open `path` and map it into memory
+    ///
+    /// The file must not be truncated or rewritten while it is mapped.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| CsfError::io(path, e))?;
+        Self::from_file(path, file)
+    }
+
+    /// Map `file`, already opened from `path`
+    pub fn from_file(path: &Path, file: File) -> Result<Self> {
+        // SAFETY: the map is only read, and CSF files are not modified while
+        // they are converted or scanned
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| CsfError::io(path, e))?;
+
+        let mut lines = Lines { data: &mmap, pos: 0 };
+        for _ in 0..CSF_HEADER_LINE_COUNT {
+            if lines.next().is_none() {
+                break;
+            }
+        }
+        let data_start = lines.pos;
+
+        Ok(CsfFile {
+            path: path.to_path_buf(),
+            mmap,
+            data_start,
+        })
+    }
+
+    /// Path the file was opened from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Contents of the whole file
+    pub fn bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Byte offset of the first line after the 5 header lines
+    pub fn data_start(&self) -> usize {
+        self.data_start
+    }
+
+    /// The header lines; fewer than 5 if the file is shorter
+    pub fn header_lines(&self) -> Result<Vec<String>> {
+        Lines { data: &self.mmap[..self.data_start], pos: 0 }
+            .enumerate()
+            .map(|(i, line)| {
+                std::str::from_utf8(line)
+                    .map(str::to_string)
+                    .map_err(|_| CsfError::Header(format!("Header line {} is not valid UTF-8", i + 1)))
+            })
+            .collect()
+    }
+
+    /// CSF triples following the header
+    pub fn records(&self) -> CsfRecords<'_> {
+        self.records_in(self.data_start, self.mmap.len())
+    }
+
+    /// CSF triples in the bytes `start..end`, which must begin at a line start
+    ///
+    /// Block indices, line numbers and CSF indices of the returned records and
+    /// errors count from `start`.
+    pub fn records_in(&self, start: usize, end: usize) -> CsfRecords<'_> {
+        let end = end.min(self.mmap.len());
+        CsfRecords::new(&self.mmap[start.min(end)..end])
+    }
+
+    /// Byte offset of the first CSF starting at or after `pos`
+    ///
+    /// Line1 of a CSF is the only line containing the `(` of a subshell field
+    /// such as `  5s ( 2)`; line2, line3 and block separators never do. Returns
+    /// the file length when no CSF follows.
+    pub fn csf_boundary(&self, pos: usize) -> usize {
+        let data = &self.mmap[..];
+        if pos <= self.data_start {
+            return self.data_start;
+        }
+        if pos >= data.len() {
+            return data.len();
+        }
+        // Start of the first line at or after `pos`
+        let mut lines = Lines {
+            data,
+            pos: match memchr::memchr(b'\n', &data[pos - 1..]) {
+                Some(i) => pos + i,
+                None => return data.len(),
+            },
+        };
+        loop {
+            let start = lines.pos;
+            match lines.next() {
+                Some(line) if memchr::memchr(b'(', line).is_some() => return start,
+                Some(_) => {}
+                None => return data.len(),
+            }
+        }
+    }
+}
+
+/// Compression format of a CSF file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// Recognise the format from the first bytes of a file
+    pub fn detect(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    /// Compression of `file`, or `None` for a plain file
+    ///
+    /// Reads the first bytes and seeks back to the start, so the same handle
+    /// can then be mapped or decompressed.
+    pub fn of_file(mut file: &File) -> std::io::Result<Option<Self>> {
+        let mut magic = Vec::with_capacity(6);
+        file.take(6).read_to_end(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(Self::detect(&magic))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Xz => "xz",
+        }
+    }
+}
+
+/// A compressed `.csf` file, decompressed while it is read
+pub struct CsfStream {
+    path: PathBuf,
+    compression: Compression,
+    reader: Box<dyn BufRead + Send>,
+    header_lines: Vec<Vec<u8>>,
+    /// First line of the next chunk, read while looking for the chunk's end
+    carry: Vec<u8>,
+}
+
+/// Bytes of whole CSFs decompressed from a [`CsfStream`]
+pub struct CsfChunk {
+    pub data: Vec<u8>,
+    /// The chunk ends at the end of the file
+    pub last: bool,
+}
+
+impl CsfStream {
+    /// Open `path` and read its header lines
+    pub fn open(path: &Path, compression: Compression) -> Result<Self> {
+        let file = File::open(path).map_err(|e| CsfError::io(path, e))?;
+        Self::from_file(path, file, compression)
+    }
+
+    /// Read `file`, already opened from `path` and positioned at its start
+    pub fn from_file(path: &Path, file: File, compression: Compression) -> Result<Self> {
+        let decoder: Box<dyn Read + Send> = match compression {
+            // Concatenated members, as written by `pigz` or `cat a.gz b.gz`
+            Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(file)),
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(file).map_err(|e| CsfError::io(path, e))?),
+            Compression::Xz => Box::new(liblzma::read::XzDecoder::new_multi_decoder(file)),
+        };
+        let mut stream = CsfStream {
+            path: path.to_path_buf(),
+            compression,
+            reader: Box::new(BufReader::with_capacity(1 << 20, decoder)),
+            header_lines: Vec::with_capacity(CSF_HEADER_LINE_COUNT),
+            carry: Vec::new(),
+        };
+
+        for _ in 0..CSF_HEADER_LINE_COUNT {
+            let mut line = Vec::new();
+            if stream.read_line(&mut line)? == 0 {
+                break;
+            }
+            let line = Lines { data: &line, pos: 0 }.next().unwrap_or_default();
+            stream.header_lines.push(line.to_vec());
+        }
+        Ok(stream)
+    }
+
+    /// Path the file was opened from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// The header lines; fewer than 5 if the file is shorter
+    pub fn header_lines(&self) -> Result<Vec<String>> {
+        self.header_lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                String::from_utf8(line.clone())
+                    .map_err(|_| CsfError::Header(format!("Header line {} is not valid UTF-8", i + 1)))
+            })
+            .collect()
+    }
+
+    /// Append the next line, with its line ending, to `buf`
+    fn read_line(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+        self.reader.read_until(b'\n', buf).map_err(|e| CsfError::io(&self.path, e))
+    }
+
+    /// Decompress the next chunk of at least `min_bytes` bytes
+    ///
+    /// Every chunk but the last ends right before line1 of a CSF, so chunks
+    /// hold whole CSFs and can be read with [`CsfRecords::new`] on their own.
+    pub fn next_chunk(&mut self, min_bytes: usize) -> Result<Option<CsfChunk>> {
+        let mut data = Vec::with_capacity(min_bytes + (min_bytes >> 4));
+        data.append(&mut self.carry);
+        loop {
+            let line_start = data.len();
+            if self.read_line(&mut data)? == 0 {
+                break;
+            }
+            if line_start >= min_bytes && memchr::memchr(b'(', &data[line_start..]).is_some() {
+                self.carry = data.split_off(line_start);
+                return Ok(Some(CsfChunk { data, last: false }));
+            }
+        }
+        Ok((!data.is_empty()).then_some(CsfChunk { data, last: true }))
+    }
+}
+
+/// Size of the chunks a compressed file is read in by sequential scans
+pub const STREAM_CHUNK_BYTES: usize = 1 << 20;
+
+/// A `.csf` file opened for reading: mapped when plain, streamed when compressed
+pub enum CsfInput {
+    Mapped(CsfFile),
+    Compressed(CsfStream),
+}
+
+impl CsfInput {
+    /// Open `path`, detecting gzip, zstd and xz compression from its first bytes
+    ///
+    /// The file is opened once; the handle the magic bytes are read from is
+    /// the one that is mapped or decompressed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| CsfError::io(path, e))?;
+        match Compression::of_file(&file).map_err(|e| CsfError::io(path, e))? {
+            Some(compression) => Ok(CsfInput::Compressed(CsfStream::from_file(path, file, compression)?)),
+            None => Ok(CsfInput::Mapped(CsfFile::from_file(path, file)?)),
+        }
+    }
+
+    /// Compression of the file, or `None` for a plain file
+    pub fn compression(&self) -> Option<Compression> {
+        match self {
+            CsfInput::Mapped(_) => None,
+            CsfInput::Compressed(stream) => Some(stream.compression()),
+        }
+    }
+
+    /// The header lines; fewer than 5 if the file is shorter
+    pub fn header_lines(&self) -> Result<Vec<String>> {
+        match self {
+            CsfInput::Mapped(file) => file.header_lines(),
+            CsfInput::Compressed(stream) => stream.header_lines(),
+        }
+    }
+
+    /// Read the CSF triples in file order
+    ///
+    /// `visit` is called once for a mapped file and once per chunk for a
+    /// compressed one, and must consume the records it is given. Block indices
+    /// and line numbers continue from chunk to chunk. Returns the line count and
+    /// the CSFs per block, like the fields of [`CsfRecords`].
+    pub fn scan(
+        &mut self,
+        mut visit: impl FnMut(&mut CsfRecords<'_>) -> Result<()>,
+    ) -> Result<(usize, Vec<usize>)> {
+        match self {
+            CsfInput::Mapped(file) => {
+                let mut records = file.records();
+                visit(&mut records)?;
+                Ok((records.total_lines, records.block_csf_counts))
+            }
+            CsfInput::Compressed(stream) => {
+                let mut records = CsfRecords::new(&[]);
+                while let Some(chunk) = stream.next_chunk(STREAM_CHUNK_BYTES)? {
+                    let mut chunk_records = records.resume(&chunk.data)?;
+                    visit(&mut chunk_records)?;
+                    if chunk.last {
+                        return Ok((chunk_records.total_lines, chunk_records.block_csf_counts));
+                    }
+                    records = chunk_records.resume(&[])?;
+                }
+                Ok((records.total_lines, records.block_csf_counts))
+            }
+        }
+    }
+}
+
+/// Lines of a byte slice, without their line ending
+struct Lines<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let rest = &self.data[self.pos..];
+        if rest.is_empty() {
+            return None;
+        }
+        let (line, consumed) = match memchr::memchr(b'\n', rest) {
+            Some(i) => (&rest[..i], i + 1),
+            None => (rest, rest.len()),
+        };
+        self.pos += consumed;
+        Some(line.strip_suffix(b"\r").unwrap_or(line))
+    }
+}
+
+/// Returns true for the ` *` line GRASP writes between two J/parity blocks.
+pub fn is_block_separator(line: &[u8]) -> bool {
+    line.trim_ascii() == b"*"
+}
+
+/// One CSF borrowed from a mapped file
+#[derive(Debug, Clone, Copy)]
+pub struct CsfRecord<'a> {
+    /// Index of the J/parity block, counted from where reading started
+    pub block: u32,
+    /// 1-based line number of line1, counted from where reading started
+    pub line: usize,
+    pub lines: [&'a [u8]; 3],
+}
+
+impl<'a> CsfRecord<'a> {
+    /// The three lines as text; `idx` locates the CSF in the error
+    pub fn str_lines(&self, idx: usize) -> Result<[&'a str; 3]> {
+        let mut text = [""; 3];
+        for (i, line) in self.lines.iter().enumerate() {
+            text[i] = std::str::from_utf8(line).map_err(|_| CsfError::MalformedCsf {
+                idx: Some(idx),
+                line: Some(self.line + i),
+                message: "Line is not valid UTF-8".to_string(),
+            })?;
+        }
+        Ok(text)
+    }
+}
+
+/// Groups the lines following the header into CSF triples.
+///
+/// Block separator lines (` *`) are consumed here and advance the block index,
+/// so every yielded triple is aligned regardless of how many blocks the file has.
+///
+/// A CSF cut short by the next one, recognised by the `(` of its line1 as in
+/// [`CsfFile::csf_boundary`], is an error rather than a shift of every later
+/// CSF. An incomplete trailing CSF is dropped, matching the historical
+/// behaviour.
+pub struct CsfRecords<'a> {
+    lines: Lines<'a>,
+    pending: [&'a [u8]; 3],
+    pending_len: usize,
+    block: u32,
+    /// Lines read so far, separators included
+    pub total_lines: usize,
+    /// CSFs in each block read so far
+    pub block_csf_counts: Vec<usize>,
+}
+
+impl<'a> CsfRecords<'a> {
+    /// CSF triples of `data`, which must begin at a line start
+    pub fn new(data: &'a [u8]) -> Self {
+        CsfRecords {
+            lines: Lines { data, pos: 0 },
+            pending: [&[]; 3],
+            pending_len: 0,
+            block: 0,
+            total_lines: 0,
+            block_csf_counts: vec![0],
+        }
+    }
+
+    /// Fail if the bytes read so far end inside a CSF
+    ///
+    /// For data that is followed by line1 of another CSF, such as a byte range
+    /// or a decompressed chunk that is not the last one.
+    pub fn check_complete(&self) -> Result<()> {
+        match self.pending_len {
+            0 => Ok(()),
+            _ => Err(self.incomplete(CSF_HEADER_LINE_COUNT + self.total_lines + 1)),
+        }
+    }
+
+    /// Error for the pending CSF, cut short by a CSF starting at `line`
+    fn incomplete(&self, line: usize) -> CsfError {
+        CsfError::MalformedCsf {
+            idx: Some(self.block_csf_counts.iter().sum()),
+            line: Some(line),
+            message: format!("Incomplete CSF ({} of 3 lines) before the next CSF", self.pending_len),
+        }
+    }
+
+    /// Continue with `data`, the bytes following those read so far
+    ///
+    /// Block indices and line numbers carry on. Fails if the bytes read so far
+    /// end inside a CSF.
+    pub fn resume<'b>(self, data: &'b [u8]) -> Result<CsfRecords<'b>> {
+        self.check_complete()?;
+        Ok(CsfRecords {
+            lines: Lines { data, pos: 0 },
+            pending: [&[]; 3],
+            pending_len: 0,
+            block: self.block,
+            total_lines: self.total_lines,
+            block_csf_counts: self.block_csf_counts,
+        })
+    }
+}
+
+impl<'a> Iterator for CsfRecords<'a> {
+    type Item = Result<CsfRecord<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            self.total_lines += 1;
+
+            if is_block_separator(line) {
+                if self.pending_len > 0 {
+                    return Some(Err(CsfError::MalformedCsf {
+                        idx: Some(self.block_csf_counts.iter().sum()),
+                        line: Some(CSF_HEADER_LINE_COUNT + self.total_lines),
+                        message: format!(
+                            "Block separator found inside a CSF (after {} of 3 lines)",
+                            self.pending_len
+                        ),
+                    }));
+                }
+                self.block += 1;
+                self.block_csf_counts.push(0);
+                continue;
+            }
+
+            // Only line1 holds subshell fields such as `  5s ( 2)`
+            if self.pending_len > 0 && memchr::memchr(b'(', line).is_some() {
+                return Some(Err(self.incomplete(CSF_HEADER_LINE_COUNT + self.total_lines)));
+            }
+
+            self.pending[self.pending_len] = line;
+            self.pending_len += 1;
+            if self.pending_len == 3 {
+                self.pending_len = 0;
+                self.block_csf_counts[self.block as usize] += 1;
+                return Some(Ok(CsfRecord {
+                    block: self.block,
+                    line: CSF_HEADER_LINE_COUNT + self.total_lines - 2,
+                    lines: self.pending,
+                }));
+            }
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_of(text: &str) -> Vec<&[u8]> {
+        Lines { data: text.as_bytes(), pos: 0 }.collect()
+    }
+
+    #[test]
+    fn test_lines_strip_line_endings() {
+        assert_eq!(lines_of("a\nb\r\n\nc"), vec![&b"a"[..], b"b", b"", b"c"]);
+        assert_eq!(lines_of("a\n"), vec![&b"a"[..]]);
+        assert!(lines_of("").is_empty());
+    }
+
+    #[test]
+    fn test_compression_detect() {
+        assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Some(Compression::Gzip));
+        assert_eq!(Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]), Some(Compression::Zstd));
+        assert_eq!(Compression::detect(b"\xfd7zXZ\x00"), Some(Compression::Xz));
+        assert_eq!(Compression::detect(b"Core subshells:"), None);
+        assert_eq!(Compression::detect(&[0x1f]), None);
+    }
+
+    #[test]
+    fn test_is_block_separator() {
+        assert!(is_block_separator(b" *"));
+        assert!(is_block_separator(b"*  "));
+        assert!(!is_block_separator(b"  3s ( 1)"));
+    }
+}
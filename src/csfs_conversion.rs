@@ -1,19 +1,27 @@
-use arrow::array::{StringBuilder, UInt64Builder};
+use arrow::array::{
+    AsArray, Int8Builder, StringArray, StringBuilder, UInt32Array, UInt32Builder, UInt64Array, UInt64Builder,
+};
+use arrow::compute::cast;
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
+use crossbeam_channel::bounded;
 use parquet::arrow::ArrowWriter;
-use parquet::file::properties::WriterProperties;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use parquet::file::reader::{FileReader, SerializedFileReader};
-use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use toml;
 
-/// Number of header lines at the beginning of a CSF file
-const CSF_HEADER_LINE_COUNT: usize = 5;
+use crate::csf::{configuration_summary, declared_parity, Parity};
+use crate::csf_header::{CsfHeader, CSF_HEADER_LINE_COUNT};
+use crate::csf_reader::{CsfChunk, CsfFile, CsfInput, CsfRecord, CsfRecords, STREAM_CHUNK_BYTES};
+use crate::error::{CsfError, Result};
+use crate::writer_options::{compression_name, WriterOptions};
+
 
 /// Maximum line length (in bytes) before emitting a strong warning about memory usage.
 /// The BufRead::lines() iterator allocates the full line before truncation.
@@ -21,6 +29,7 @@ const CSF_HEADER_LINE_COUNT: usize = 5;
 /// 1. OS/system limits typically bound single line length
 /// 2. The max_line_len parameter limits what we actually store
 /// 3. Temporary allocations are freed immediately
+///
 /// Lines exceeding this threshold will trigger a warning but still be processed.
 const MAX_LINE_WARNING_THRESHOLD: usize = 1024 * 1024; // 1 MB
 
@@ -36,24 +45,10 @@ fn safe_parent_dir(path: &Path) -> PathBuf {
 
 /// Extracts the first CSF_HEADER_LINE_COUNT header lines from a CSF file.
 /// Returns a vector of exactly CSF_HEADER_LINE_COUNT strings (empty strings if file has fewer lines).
-fn extract_header_lines(csfs_path: &Path) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-    let input_file = File::open(csfs_path)?;
-    let reader = BufReader::new(input_file);
-    let mut lines_iter = reader.lines();
-
-    let mut headers = Vec::with_capacity(CSF_HEADER_LINE_COUNT);
-
-    for _i in 0..CSF_HEADER_LINE_COUNT {
-        match lines_iter.next() {
-            Some(Ok(line)) => {
-                headers.push(line);
-            }
-            Some(Err(e)) => return Err(e.into()),
-            None => {
-                println!("警告: 文件少于 {} 行 Header", CSF_HEADER_LINE_COUNT);
-                break;
-            }
-        }
+fn extract_header_lines(input: &CsfInput) -> Result<Vec<String>> {
+    let mut headers = input.header_lines()?;
+    if headers.len() < CSF_HEADER_LINE_COUNT {
+        println!("警告: 文件少于 {} 行 Header", CSF_HEADER_LINE_COUNT);
     }
 
     // Ensure headers has exactly CSF_HEADER_LINE_COUNT lines, fill with empty strings if needed
@@ -64,9 +59,23 @@ fn extract_header_lines(csfs_path: &Path) -> Result<Vec<String>, Box<dyn std::er
     Ok(headers)
 }
 
+/// The raw header lines are always stored verbatim; a header that does not
+/// follow the GRASP layout is reported but does not stop the conversion.
+///
+/// Returns the number of closed core electrons, 0 if the header is malformed.
+fn warn_if_header_malformed(headers: &[String]) -> u32 {
+    match CsfHeader::parse(headers) {
+        Ok(header) => header.closed_core_electrons(),
+        Err(e) => {
+            println!("警告: {}", e);
+            0
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
-struct HeaderInfo {
-    header_lines: Vec<String>,
+pub(crate) struct HeaderInfo {
+    pub(crate) header_lines: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -74,20 +83,732 @@ pub struct ConversionStats {
     pub csf_count: usize,
     pub total_lines: usize,
     pub truncated_count: usize,
+    /// Number of CSFs in each J/parity block, in file order
+    #[serde(default)]
+    pub block_csf_counts: Vec<usize>,
+    /// Total electron count (core + peel) of the first CSF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub electron_count: Option<u32>,
+    /// CSFs whose electron count differs from `electron_count`
+    #[serde(default)]
+    pub electron_count_mismatch_count: usize,
+    /// CSFs whose parity sign on line3 disagrees with their occupations
+    #[serde(default)]
+    pub parity_mismatch_count: usize,
+    /// CSFs whose line1 could not be parsed (null `electron_count` and `parity`)
+    #[serde(default)]
+    pub unparsed_count: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-struct HeaderData {
-    header_info: HeaderInfo,
-    conversion_stats: ConversionStats,
+pub(crate) struct HeaderData {
+    pub(crate) header_info: HeaderInfo,
+    pub(crate) conversion_stats: ConversionStats,
+}
+
+/// Truncate a line to `max_len` characters, reporting whether anything was cut.
+fn truncate_line(line: &str, max_len: usize) -> (&str, bool) {
+    if line.len() > MAX_LINE_WARNING_THRESHOLD {
+        eprintln!("警告: 行过长 ({} bytes)", line.len());
+    }
+    if line.len() > max_len {
+        let end = line.char_indices().nth(max_len).map_or(line.len(), |(i, _)| i);
+        (&line[..end], true)
+    } else {
+        (line, false)
+    }
+}
+
+/// A single CSF, tagged with its block index.
+pub(crate) struct RawCsf<S = String> {
+    pub(crate) block: u32,
+    pub(crate) lines: [S; 3],
+}
+
+/// Borrow the lines of CSF `idx` as text, truncated to `max_line_len`
+/// characters, and report whether any line was cut.
+fn decode_record<'a>(record: &CsfRecord<'a>, idx: usize, max_line_len: usize) -> Result<(RawCsf<&'a str>, bool)> {
+    let lines = record.str_lines(idx)?;
+    let mut truncated = false;
+    let lines = lines.map(|line| {
+        let (line, t) = truncate_line(line, max_line_len);
+        truncated |= t;
+        line
+    });
+    Ok((RawCsf { block: record.block, lines }, truncated))
+}
+
+/// Total electron count, parity and whether line3 declares that parity,
+/// or why line1 could not be parsed.
+pub(crate) type CsfSummary = std::result::Result<(u32, Parity, bool), String>;
+
+/// Summarize one CSF; needs no shared state, so batches can be summarized in parallel.
+fn summarize_csf(core_electrons: u32, line1: &str, line3: &str) -> CsfSummary {
+    let (peel_electrons, parity) = configuration_summary(line1)?;
+    Ok((core_electrons + peel_electrons, parity, declared_parity(line3) == Some(parity)))
+}
+
+/// Running electron count and parity checks over the CSFs of one conversion.
+///
+/// The first CSF with a parsable line1 sets the reference electron count;
+/// every later CSF is compared against it.
+#[derive(Default)]
+pub(crate) struct ConsistencyCheck {
+    core_electrons: u32,
+    pub(crate) electron_count: Option<u32>,
+    pub(crate) electron_count_mismatch_count: usize,
+    pub(crate) parity_mismatch_count: usize,
+    pub(crate) unparsed_count: usize,
+}
+
+impl ConsistencyCheck {
+    pub(crate) fn new(core_electrons: u32) -> Self {
+        Self {
+            core_electrons,
+            ..Default::default()
+        }
+    }
+
+    /// Record the summary of CSF `idx`, counting and reporting any
+    /// inconsistency. Summaries must be recorded in CSF order.
+    fn record(&mut self, idx: usize, summary: &CsfSummary) {
+        let (electrons, parity, parity_declared) = match summary {
+            Ok(summary) => *summary,
+            Err(e) => {
+                self.unparsed_count += 1;
+                if self.unparsed_count <= 5 {
+                    println!("警告: 第 {} 个 CSF 无法解析: {}", idx, e);
+                }
+                return;
+            }
+        };
+
+        let reference = *self.electron_count.get_or_insert(electrons);
+        if electrons != reference {
+            self.electron_count_mismatch_count += 1;
+            if self.electron_count_mismatch_count <= 5 {
+                println!(
+                    "警告: 第 {} 个 CSF 电子数为 {}，与第一个 CSF 的 {} 不一致",
+                    idx, electrons, reference
+                );
+            }
+        }
+
+        if !parity_declared {
+            self.parity_mismatch_count += 1;
+            if self.parity_mismatch_count <= 5 {
+                println!("警告: 第 {} 个 CSF 的宇称与占据数不一致 (应为 {})", idx, parity);
+            }
+        }
+    }
+
+    /// Print a summary of all inconsistencies found
+    fn report(&self) {
+        if self.electron_count_mismatch_count > 0 {
+            println!(
+                "警告: {} 个 CSF 的电子数与第一个 CSF 不一致",
+                self.electron_count_mismatch_count
+            );
+        }
+        if self.parity_mismatch_count > 0 {
+            println!("警告: {} 个 CSF 的宇称与占据数不一致", self.parity_mismatch_count);
+        }
+        if self.unparsed_count > 0 {
+            println!("警告: {} 个 CSF 的组态行无法解析", self.unparsed_count);
+        }
+    }
+}
+
+/// Arrow schema of a converted CSF table.
+pub(crate) fn csf_table_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("idx", DataType::UInt64, false),
+        Field::new("line1", DataType::Utf8, false),
+        Field::new("line2", DataType::Utf8, false),
+        Field::new("line3", DataType::Utf8, false),
+        Field::new("block", DataType::UInt32, false),
+        Field::new("electron_count", DataType::UInt32, true),
+        Field::new("parity", DataType::Int8, true),
+    ]))
+}
+
+/// Build a record batch of CSFs numbered consecutively from `first_idx`.
+///
+/// The `electron_count` and `parity` columns (+1 even, -1 odd) are derived
+/// from line1 and checked along the way.
+pub(crate) fn build_csf_batch<S: AsRef<str>>(
+    schema: &Arc<Schema>,
+    first_idx: usize,
+    records: &[RawCsf<S>],
+    max_line_len: usize,
+    consistency: &mut ConsistencyCheck,
+) -> Result<RecordBatch> {
+    let (batch, summaries) =
+        build_csf_columns(schema, first_idx, records, max_line_len, consistency.core_electrons)?;
+    for (i, summary) in summaries.iter().enumerate() {
+        consistency.record(first_idx + i, summary);
+    }
+    Ok(batch)
+}
+
+/// Build a record batch of CSFs numbered from `first_idx` without checking it.
+///
+/// Returns the per-CSF summaries so the caller can record them in order.
+fn build_csf_columns<S: AsRef<str>>(
+    schema: &Arc<Schema>,
+    first_idx: usize,
+    records: &[RawCsf<S>],
+    max_line_len: usize,
+    core_electrons: u32,
+) -> Result<(RecordBatch, Vec<CsfSummary>)> {
+    let n = records.len();
+    let mut idx_builder = UInt64Builder::with_capacity(n);
+    let mut line1_builder = StringBuilder::with_capacity(n, n * max_line_len);
+    let mut line2_builder = StringBuilder::with_capacity(n, n * max_line_len);
+    let mut line3_builder = StringBuilder::with_capacity(n, n * max_line_len);
+    let mut block_builder = UInt32Builder::with_capacity(n);
+    let mut electron_builder = UInt32Builder::with_capacity(n);
+    let mut parity_builder = Int8Builder::with_capacity(n);
+    let mut summaries = Vec::with_capacity(n);
+
+    for (i, record) in records.iter().enumerate() {
+        idx_builder.append_value((first_idx + i) as u64);
+        let [line1, line2, line3] = &record.lines;
+        line1_builder.append_value(line1);
+        line2_builder.append_value(line2);
+        line3_builder.append_value(line3);
+        block_builder.append_value(record.block);
+
+        let summary = summarize_csf(core_electrons, line1.as_ref(), line3.as_ref());
+        let summary_ok = summary.as_ref().ok();
+        electron_builder.append_option(summary_ok.map(|&(electrons, _, _)| electrons));
+        parity_builder.append_option(summary_ok.map(|&(_, parity, _)| match parity {
+            Parity::Even => 1,
+            Parity::Odd => -1,
+        }));
+        summaries.push(summary);
+    }
+
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(idx_builder.finish()),
+            Arc::new(line1_builder.finish()),
+            Arc::new(line2_builder.finish()),
+            Arc::new(line3_builder.finish()),
+            Arc::new(block_builder.finish()),
+            Arc::new(electron_builder.finish()),
+            Arc::new(parity_builder.finish()),
+        ],
+    )?;
+    Ok((batch, summaries))
+}
+
+/// Path of the `[input_file_stem]_header.toml` file written next to the output file.
+///
+/// A compression extension is dropped first, so `a.csf.gz` gets the header
+/// file of `a.csf`.
+pub fn header_file_path(csfs_path: &Path, output_path: &Path) -> PathBuf {
+    let header_dir = safe_parent_dir(output_path);
+    let input_path = match csfs_path.extension().and_then(|e| e.to_str()) {
+        Some("gz" | "zst" | "xz") => csfs_path.with_extension(""),
+        _ => csfs_path.to_path_buf(),
+    };
+    let input_file_stem = input_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("csfs");
+    header_dir.join(format!("{}_header.toml", input_file_stem))
+}
+
+/// Write `[input_file_stem]_header.toml` next to the output file.
+pub(crate) fn write_header_file(
+    csfs_path: &Path,
+    output_path: &Path,
+    header_data: &HeaderData,
+) -> Result<PathBuf> {
+    let header_path = header_file_path(csfs_path, output_path);
+    let toml_string = toml::to_string_pretty(header_data)?;
+    std::fs::write(&header_path, toml_string).map_err(|e| CsfError::io(&header_path, e))?;
+    Ok(header_path)
+}
+
+/// Output files of a conversion that are removed again unless it completes.
+///
+/// Declare the guard before the Parquet writer so the writer is closed first
+/// when both are dropped on an error path.
+#[derive(Default)]
+pub(crate) struct PartialOutput {
+    paths: Vec<PathBuf>,
+}
+
+impl PartialOutput {
+    /// Remove `path` if the conversion fails from now on
+    pub(crate) fn track(&mut self, path: PathBuf) {
+        self.paths.push(path);
+    }
+
+    /// The conversion completed; keep every tracked file
+    pub(crate) fn keep(mut self) {
+        self.paths.clear();
+    }
+}
+
+impl Drop for PartialOutput {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            if path.exists() && std::fs::remove_file(path).is_ok() {
+                println!("已删除不完整的输出文件: {:?}", path);
+            }
+        }
+    }
+}
+
+/// Read a `_header.toml` file written during conversion.
+pub(crate) fn read_header_data(header_path: &Path) -> Result<HeaderData> {
+    let text = std::fs::read_to_string(header_path).map_err(|e| CsfError::io(header_path, e))?;
+    toml::from_str(&text)
+        .map_err(|e| CsfError::Header(format!("Failed to parse header file {:?}: {}", header_path, e)))
+}
+
+/// Parquet key-value metadata key of the rCSFs version that wrote the file
+pub const METADATA_VERSION_KEY: &str = "rcsfs.version";
+/// Parquet key-value metadata key of the header, stored as the `_header.toml` text
+pub const METADATA_HEADER_KEY: &str = "rcsfs.header";
+/// Parquet key-value metadata key of the space-separated core subshells
+pub const METADATA_CORE_SUBSHELLS_KEY: &str = "rcsfs.core_subshells";
+/// Parquet key-value metadata key of the space-separated peel subshells
+pub const METADATA_PEEL_SUBSHELLS_KEY: &str = "rcsfs.peel_subshells";
+/// Parquet key-value metadata key of the conversion parameters, stored as TOML
+pub const METADATA_CONVERSION_KEY: &str = "rcsfs.conversion";
+
+/// Parameters of a conversion, recorded in the output file's metadata
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConversionParameters {
+    pub input_file: String,
+    /// Compression of the input file (`gzip`, `zstd` or `xz`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_compression: Option<String>,
+    /// `sequential`, `lines` or `byte_ranges`
+    pub mode: String,
+    pub max_line_len: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_bytes: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_workers: Option<usize>,
+    /// Parquet compression, e.g. `ZSTD(ZstdLevel(3))`
+    pub compression: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_group_rows: Option<usize>,
+    pub dictionary: bool,
+    pub statistics: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_page_size: Option<usize>,
+}
+
+impl ConversionParameters {
+    fn new(
+        csfs_path: &Path,
+        input: &CsfInput,
+        mode: &str,
+        max_line_len: usize,
+        writer_options: &WriterOptions,
+        props: &parquet::file::properties::WriterProperties,
+    ) -> Self {
+        ConversionParameters {
+            input_file: csfs_path.display().to_string(),
+            input_compression: input.compression().map(|c| c.name().to_string()),
+            mode: mode.to_string(),
+            max_line_len,
+            chunk_size: None,
+            range_bytes: None,
+            num_workers: None,
+            compression: compression_name(props),
+            row_group_rows: writer_options.row_group_rows,
+            dictionary: writer_options.dictionary,
+            statistics: writer_options.statistics,
+            data_page_size: writer_options.data_page_size,
+        }
+    }
+}
+
+/// Store the header, and the conversion parameters if any, in the key-value
+/// metadata of a CSF table before it is closed.
+///
+/// The subshell keys are only written when the header follows the GRASP layout.
+pub(crate) fn append_header_metadata<W: Write + Send>(
+    writer: &mut ArrowWriter<W>,
+    header_data: &HeaderData,
+    parameters: Option<&ConversionParameters>,
+) -> Result<()> {
+    use parquet::file::metadata::KeyValue;
+
+    writer.append_key_value_metadata(KeyValue::new(
+        METADATA_VERSION_KEY.to_string(),
+        env!("CARGO_PKG_VERSION").to_string(),
+    ));
+    writer.append_key_value_metadata(KeyValue::new(
+        METADATA_HEADER_KEY.to_string(),
+        toml::to_string_pretty(header_data)?,
+    ));
+    if let Ok(header) = CsfHeader::parse(&header_data.header_info.header_lines) {
+        writer.append_key_value_metadata(KeyValue::new(
+            METADATA_CORE_SUBSHELLS_KEY.to_string(),
+            header.core_labels().join(" "),
+        ));
+        writer.append_key_value_metadata(KeyValue::new(
+            METADATA_PEEL_SUBSHELLS_KEY.to_string(),
+            header.peel_labels().join(" "),
+        ));
+    }
+    if let Some(parameters) = parameters {
+        writer.append_key_value_metadata(KeyValue::new(
+            METADATA_CONVERSION_KEY.to_string(),
+            toml::to_string_pretty(parameters)?,
+        ));
+    }
+    Ok(())
+}
+
+/// Read the header stored in the `rcsfs.header` metadata of a Parquet file.
+///
+/// Returns `None` for files written without it, e.g. by older versions.
+pub(crate) fn read_header_metadata(parquet_path: &Path) -> Result<Option<HeaderData>> {
+    let file = File::open(parquet_path).map_err(|e| CsfError::io(parquet_path, e))?;
+    let reader = SerializedFileReader::new(file)?;
+    let text = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|kvs| kvs.iter().find(|kv| kv.key == METADATA_HEADER_KEY))
+        .and_then(|kv| kv.value.clone());
+    text.map(|text| parse_header_metadata(&text, parquet_path)).transpose()
+}
+
+/// Parse the `rcsfs.header` metadata value of `parquet_path`
+pub(crate) fn parse_header_metadata(text: &str, parquet_path: &Path) -> Result<HeaderData> {
+    toml::from_str(text)
+        .map_err(|e| CsfError::Header(format!("Failed to parse header metadata of {:?}: {}", parquet_path, e)))
+}
+
+/// Load the header of a converted CSF table, with the file it was read from.
+///
+/// `header_path` is used when given. Otherwise the header stored in the
+/// table's own metadata is preferred over a `_header.toml` found next to it,
+/// so tables keep their header when renamed or moved. Returns `None` if
+/// neither exists.
+pub(crate) fn load_header_data(
+    parquet_path: &Path,
+    header_path: Option<&Path>,
+) -> Result<Option<(HeaderData, PathBuf)>> {
+    if let Some(header_path) = header_path {
+        return Ok(Some((read_header_data(header_path)?, header_path.to_path_buf())));
+    }
+    if let Some(header_data) = read_header_metadata(parquet_path)? {
+        return Ok(Some((header_data, parquet_path.to_path_buf())));
+    }
+    match crate::csfs_descriptor::parquet_batch::find_header_file(parquet_path) {
+        Some(header_path) => Ok(Some((read_header_data(&header_path)?, header_path))),
+        None => Ok(None),
+    }
+}
+
+/// Build a rayon thread pool owned by one conversion or processor.
+///
+/// `None` uses rayon's default size (one thread per CPU core, or
+/// `RAYON_NUM_THREADS` if set). The global rayon pool is never touched, so
+/// conversions with different worker counts can run in the same process.
+pub fn build_thread_pool(num_workers: Option<usize>) -> Result<rayon::ThreadPool> {
+    let mut builder = rayon::ThreadPoolBuilder::new();
+    if let Some(n) = num_workers {
+        if n == 0 {
+            return Err(CsfError::InvalidInput("num_workers must be greater than 0".to_string()));
+        }
+        builder = builder.num_threads(n);
+    }
+    builder
+        .build()
+        .map_err(|e| CsfError::InvalidInput(format!("Failed to build thread pool: {}", e)))
+}
+
+/// Most CSFs in one work item of the parallel converter
+const WORK_ITEM_CSFS: usize = 8192;
+
+/// Default size of the byte ranges read concurrently by
+/// [`convert_csfs_to_parquet_by_ranges`] (8 MiB)
+pub const DEFAULT_RANGE_BYTES: usize = 8 << 20;
+
+/// A batch of CSFs sent from the reader to the workers
+struct ConversionWork<'a> {
+    /// Index of the first CSF of the batch
+    first_idx: usize,
+    records: Vec<CsfRecord<'a>>,
+}
+
+/// A batch processed by a worker, waiting to be recorded and written
+struct ProcessedBatch {
+    batch: RecordBatch,
+    summaries: Vec<CsfSummary>,
+    /// CSFs with at least one truncated line
+    truncated_count: usize,
+}
+
+/// Truncate the lines of a batch and build its Arrow columns.
+fn process_csf_batch(
+    schema: &Arc<Schema>,
+    first_idx: usize,
+    records: &[CsfRecord],
+    max_line_len: usize,
+    core_electrons: u32,
+) -> Result<ProcessedBatch> {
+    let mut truncated_count = 0;
+    let mut decoded = Vec::with_capacity(records.len());
+    for (i, record) in records.iter().enumerate() {
+        let (record, truncated) = decode_record(record, first_idx + i, max_line_len)?;
+        truncated_count += truncated as usize;
+        decoded.push(record);
+    }
+    let (batch, summaries) = build_csf_columns(schema, first_idx, &decoded, max_line_len, core_electrons)?;
+    Ok(ProcessedBatch { batch, summaries, truncated_count })
+}
+
+/// Writer side of the parallel converters: checks and writes batches in CSF order.
+struct OrderedOutput<'a> {
+    writer: &'a mut ArrowWriter<File>,
+    consistency: &'a mut ConsistencyCheck,
+    csf_count: usize,
+    truncated_count: usize,
+}
+
+impl OrderedOutput<'_> {
+    fn write(&mut self, processed: ProcessedBatch) -> Result<()> {
+        for (i, summary) in processed.summaries.iter().enumerate() {
+            self.consistency.record(self.csf_count + i, summary);
+        }
+        self.writer.write(&processed.batch)?;
+
+        let previous = self.csf_count;
+        self.csf_count += processed.batch.num_rows();
+        self.truncated_count += processed.truncated_count;
+        if self.csf_count / 1_000_000 > previous / 1_000_000 {
+            println!("已处理 {} 个 CSF", self.csf_count);
+        }
+        Ok(())
+    }
+}
+
+/// Reader → workers → ordered writer pipeline of the parallel converters.
+///
+/// `read` runs on its own thread and passes work items to `send`, which
+/// blocks while `2 * num_workers + 2` items are between reading and writing
+/// and returns `false` once the writer has stopped. `process` runs on one task
+/// per thread of `pool`, and `write` receives the results in the order the
+/// items were sent. A writer error is returned before a reader error, since
+/// it stops the reader early.
+pub(crate) fn run_pipeline<W, R, T>(
+    pool: &rayon::ThreadPool,
+    read: impl FnOnce(&mut dyn FnMut(W) -> bool) -> Result<T> + Send,
+    process: impl Fn(W) -> R + Sync,
+    mut write: impl FnMut(R) -> Result<()> + Send,
+) -> Result<T>
+where
+    W: Send,
+    R: Send,
+    T: Send,
+{
+    let num_workers = pool.current_num_threads();
+
+    // Every item holds a slot from reading until it is written
+    let max_in_flight = 2 * num_workers + 2;
+    let (slot_tx, slot_rx) = bounded::<()>(max_in_flight);
+    for _ in 0..max_in_flight {
+        let _ = slot_tx.send(());
+    }
+    let (work_tx, work_rx) = bounded::<(usize, W)>(num_workers);
+    let (result_tx, result_rx) = bounded::<(usize, R)>(num_workers);
+
+    let (read_result, write_result) = std::thread::scope(|scope| {
+        let reader_handle = scope.spawn(move || {
+            let mut batch_idx = 0;
+            read(&mut |item| {
+                // Both fail only when the writer stopped and reports its own error
+                if slot_rx.recv().is_err() || work_tx.send((batch_idx, item)).is_err() {
+                    return false;
+                }
+                batch_idx += 1;
+                true
+            })
+        });
+
+        let writer_handle = scope.spawn(move || -> Result<()> {
+            let mut pending: BTreeMap<usize, R> = BTreeMap::new();
+            let mut next_write_idx = 0;
+            while let Ok((batch_idx, result)) = result_rx.recv() {
+                pending.insert(batch_idx, result);
+                while let Some(result) = pending.remove(&next_write_idx) {
+                    write(result)?;
+                    next_write_idx += 1;
+                    // Let the reader start another item
+                    let _ = slot_tx.send(());
+                }
+            }
+            Ok(())
+        });
+
+        let process = &process;
+        pool.scope(move |workers| {
+            for _ in 0..num_workers {
+                let work_rx = work_rx.clone();
+                let result_tx = result_tx.clone();
+                workers.spawn(move |_| {
+                    while let Ok((batch_idx, item)) = work_rx.recv() {
+                        if result_tx.send((batch_idx, process(item))).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        // Panics in any stage are propagated to the caller
+        let read_result = reader_handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+        let write_result = writer_handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+        (read_result, write_result)
+    });
+
+    write_result?;
+    read_result
+}
+
+/// CSFs of one byte range, numbered from 0 with block indices local to the range
+struct RangeBatch {
+    processed: ProcessedBatch,
+    total_lines: usize,
+    block_csf_counts: Vec<usize>,
+}
+
+/// Read and process byte range `k` of a file split into `range_count` ranges.
+///
+/// Range `k` runs from the first CSF at or after `data_start + k * range_bytes`
+/// to the first CSF of range `k + 1`, so every range holds whole CSFs.
+fn read_csf_range(
+    csf_file: &CsfFile,
+    k: usize,
+    range_count: usize,
+    range_bytes: usize,
+    schema: &Arc<Schema>,
+    max_line_len: usize,
+    core_electrons: u32,
+) -> Result<RangeBatch> {
+    let data_start = csf_file.data_start();
+    let start = csf_file.csf_boundary(data_start + k * range_bytes);
+    let end = match k + 1 {
+        next if next == range_count => csf_file.bytes().len(),
+        next => csf_file.csf_boundary(data_start + next * range_bytes),
+    };
+    let records = csf_file.records_in(start, end);
+    process_csf_range(records, k + 1 == range_count, schema, max_line_len, core_electrons)
+}
+
+/// Process the CSFs of a byte range or decompressed chunk that starts at a CSF.
+///
+/// Errors carry the CSF index and line number within the range.
+fn process_csf_range(
+    mut records: CsfRecords,
+    last: bool,
+    schema: &Arc<Schema>,
+    max_line_len: usize,
+    core_electrons: u32,
+) -> Result<RangeBatch> {
+    let mut batch = Vec::new();
+    for record in records.by_ref() {
+        batch.push(record?);
+    }
+
+    // Only the last range may end inside a CSF; it is dropped like in the
+    // sequential reader
+    if !last {
+        records.check_complete()?;
+    }
+
+    Ok(RangeBatch {
+        processed: process_csf_batch(schema, 0, &batch, max_line_len, core_electrons)?,
+        total_lines: records.total_lines,
+        block_csf_counts: records.block_csf_counts,
+    })
+}
+
+/// Lines and blocks of the ranges written so far
+struct RangeLayout {
+    total_lines: usize,
+    block_csf_counts: Vec<usize>,
+}
+
+impl RangeLayout {
+    fn new() -> Self {
+        RangeLayout {
+            total_lines: 0,
+            block_csf_counts: vec![0],
+        }
+    }
+
+    /// Place the next range after the `csf_count` CSFs written so far
+    fn place(&mut self, range: Result<RangeBatch>, csf_count: usize) -> Result<ProcessedBatch> {
+        // Errors of a range are located relative to its first CSF and line
+        let range = range.map_err(|e| match e {
+            CsfError::MalformedCsf { idx, line, message } => CsfError::MalformedCsf {
+                idx: idx.map(|i| i + csf_count),
+                line: line.map(|l| l + self.total_lines),
+                message,
+            },
+            e => e,
+        })?;
+
+        // The first block of a range continues the last block so far
+        let block_offset = (self.block_csf_counts.len() - 1) as u32;
+        if let Some((first, rest)) = range.block_csf_counts.split_first() {
+            *self.block_csf_counts.last_mut().unwrap_or(&mut 0) += first;
+            self.block_csf_counts.extend_from_slice(rest);
+        }
+        self.total_lines += range.total_lines;
+
+        let batch = rebase_csf_batch(&range.processed.batch, csf_count, block_offset)?;
+        Ok(ProcessedBatch { batch, ..range.processed })
+    }
+}
+
+/// Shift the `idx` and `block` columns of a range's batch to file-wide values
+fn rebase_csf_batch(batch: &RecordBatch, first_idx: usize, block_offset: u32) -> Result<RecordBatch> {
+    let n = batch.num_rows() as u64;
+    let first_idx = first_idx as u64;
+    let blocks = batch
+        .column(4)
+        .as_any()
+        .downcast_ref::<UInt32Array>()
+        .ok_or_else(|| CsfError::Schema("block column is not uint32 type".to_string()))?;
+
+    let mut columns = batch.columns().to_vec();
+    columns[0] = Arc::new(UInt64Array::from_iter_values(first_idx..first_idx + n));
+    columns[4] = Arc::new(UInt32Array::from_iter_values(blocks.values().iter().map(|b| b + block_offset)));
+    Ok(RecordBatch::try_new(batch.schema(), columns)?)
+}
+
+/// Where the parallel converter's work items come from
+enum CsfSource {
+    /// One reader thread streams the lines in batches of `chunk_size` lines
+    Lines { chunk_size: usize },
+    /// Workers read byte ranges of about `range_bytes` bytes concurrently
+    ByteRanges { range_bytes: usize },
 }
 
 /// Convert CSF text file to Parquet format using parallel processing.
 ///
-/// This function is optimized for large-scale data processing. It uses a streaming
-/// approach with rayon-based parallel processing:
+/// This function is optimized for large-scale data processing. Reading,
+/// processing and writing run at the same time in a pipeline:
 /// - Stream: Read file in batches to avoid loading 34GB+ into memory
-/// - Parallel: Process each batch with rayon's work-stealing
+/// - Parallel: Worker tasks on the thread pool truncate lines and build the Arrow columns
 /// - Order: Maintain CSF order in output
 ///
 /// # Arguments
@@ -95,7 +816,10 @@ struct HeaderData {
 /// * `csfs_path` - Path to input CSF file
 /// * `output_path` - Path to output Parquet file
 /// * `max_line_len` - Maximum line length (lines longer than this are truncated)
-/// * `chunk_size` - Number of lines per read batch (larger = fewer I/O ops but more memory)
+/// * `chunk_size` - Number of lines per read batch, capped at 8192 CSFs
+/// * `num_workers` - Size of the thread pool built for this call (default: CPU core count)
+/// * `writer_options` - Codec, row groups and encodings of the Parquet file
+///   (uncompressed unless a codec is set)
 ///
 /// # Returns
 ///
@@ -103,210 +827,282 @@ struct HeaderData {
 /// * `csf_count` - Number of CSFs processed
 /// * `total_lines` - Total number of lines processed
 /// * `truncated_count` - Number of lines that were truncated
+/// * `block_csf_counts` - Number of CSFs in each J/parity block
 ///
 /// # Architecture
 ///
-/// ```
-/// File → [Read batch] → [Rayon parallel process] → [Write ordered] → repeat
+/// ```text
+/// Reader thread → bounded channel → N worker tasks → bounded channel → Writer thread
+///   (CSF batches)                    (columns, checks)                 (ordered write)
 /// ```
 ///
 /// - **Streaming**: Read file in batches (don't load entire 34GB into memory)
-/// - **Parallel**: Each batch processed with rayon's par_iter (all cores used automatically)
-/// - **Ordered**: Results written in CSF order (par_iter + collect preserves order)
+/// - **Parallel**: `num_workers` tasks on the conversion's pool process batches (all cores by default)
+/// - **Ordered**: The writer buffers out-of-order batches and writes them in CSF order
+/// - **Bounded memory**: Batches hold at most 8192 CSFs (fewer if `chunk_size` is
+///   smaller) and at most `2 * num_workers + 2` of them are held between reading
+///   and writing, so the reader waits when the writer falls behind
+///
+/// # Multi-block Files
+///
+/// `rcsfgenerate` separates J/parity blocks with a line containing only ` *`.
+/// Separators are skipped while reading and each CSF gets a `block` column
+/// holding the zero-based index of the block it belongs to.
+///
+/// # Compressed Input
+///
+/// gzip, zstd and xz files are recognised by their magic bytes. The reader
+/// thread decompresses them into chunks of whole CSFs, which the workers
+/// process like the byte ranges of [`convert_csfs_to_parquet_by_ranges`].
 ///
 /// # Header File
 ///
 /// Automatically generates `[input_file_stem]_header.toml` in the output directory
-/// containing the 5-line header and conversion statistics.
+/// containing the 5-line header and conversion statistics. The same header, the
+/// core and peel subshells, the crate version and the conversion parameters are
+/// stored in the Parquet key-value metadata under the `rcsfs.*` keys, so readers
+/// find the header even when the output is renamed.
+///
+/// If the conversion fails after the output file was created, the partial
+/// Parquet file and the header file are removed before the error is returned.
 pub fn convert_csfs_to_parquet_parallel(
     csfs_path: &Path,
     output_path: &Path,
     max_line_len: usize,
     chunk_size: usize,
     num_workers: Option<usize>,
-) -> Result<ConversionStats, Box<dyn std::error::Error + Send + Sync>> {
-    // Configure rayon thread pool if num_workers is specified
-    if let Some(n) = num_workers {
-        println!("配置 Rayon 线程池，使用 {} 个 worker", n);
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(n)
-            .build_global()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    writer_options: &WriterOptions,
+) -> Result<ConversionStats> {
+    let pool = build_thread_pool(num_workers)?;
+    convert_csfs_to_parquet_in_pool(csfs_path, output_path, max_line_len, chunk_size, &pool, writer_options)
+}
+
+/// Convert CSF text file to Parquet format on a caller-owned thread pool.
+///
+/// Same as [`convert_csfs_to_parquet_parallel`], but the batches are processed
+/// on `pool`, so a long-lived caller can reuse one pool across conversions.
+pub fn convert_csfs_to_parquet_in_pool(
+    csfs_path: &Path,
+    output_path: &Path,
+    max_line_len: usize,
+    chunk_size: usize,
+    pool: &rayon::ThreadPool,
+    writer_options: &WriterOptions,
+) -> Result<ConversionStats> {
+    let source = CsfSource::Lines { chunk_size };
+    convert_csfs_in_pipeline(csfs_path, output_path, max_line_len, source, pool, writer_options)
+}
+
+/// Convert CSF text file to Parquet format, reading byte ranges in parallel.
+///
+/// A single reader thread limits [`convert_csfs_to_parquet_in_pool`] to the
+/// speed of one core reading lines. Here the data after the header is split
+/// into ranges of about `range_bytes` bytes (see [`DEFAULT_RANGE_BYTES`]) and
+/// every worker on `pool` reads whole ranges of the memory-mapped file, which
+/// scales on NVMe drives and parallel filesystems.
+///
+/// Each range starts at the first CSF at or after its nominal offset; line1 of
+/// a CSF is recognised by the `(` of its subshell fields. The writer numbers
+/// the CSFs of each range after those of the previous ranges and continues
+/// their block index, so the output is identical to the line-based converter.
+/// A CSF cut short by the next CSF is reported as malformed instead of being
+/// regrouped with the following lines.
+///
+/// A compressed file cannot be split; it is decompressed by one reader thread
+/// into chunks of about `range_bytes` bytes that are processed the same way.
+pub fn convert_csfs_to_parquet_by_ranges(
+    csfs_path: &Path,
+    output_path: &Path,
+    max_line_len: usize,
+    range_bytes: usize,
+    pool: &rayon::ThreadPool,
+    writer_options: &WriterOptions,
+) -> Result<ConversionStats> {
+    if range_bytes == 0 {
+        return Err(CsfError::InvalidInput("range_bytes must be greater than 0".to_string()));
     }
+    let source = CsfSource::ByteRanges { range_bytes };
+    convert_csfs_in_pipeline(csfs_path, output_path, max_line_len, source, pool, writer_options)
+}
 
+/// Shared body of the parallel converters
+fn convert_csfs_in_pipeline(
+    csfs_path: &Path,
+    output_path: &Path,
+    max_line_len: usize,
+    source: CsfSource,
+    pool: &rayon::ThreadPool,
+    writer_options: &WriterOptions,
+) -> Result<ConversionStats> {
+    let props = writer_options.properties(parquet::basic::Compression::UNCOMPRESSED)?;
     println!("开始并行转换 CSF 文件");
     println!("输入文件: {:?}", csfs_path);
     println!("输出文件: {:?}", output_path);
     println!("最大行长度: {}", max_line_len);
-    println!("批处理大小: {}", chunk_size);
+    match source {
+        CsfSource::Lines { chunk_size } => println!("批处理大小: {}", chunk_size),
+        CsfSource::ByteRanges { range_bytes } => println!("按字节范围并行读取，每段 {} 字节", range_bytes),
+    }
+    println!("Rayon 线程池: {} 个 worker", pool.current_num_threads());
 
     // --- 1. 读取 Header (5行) ---
-    let headers = extract_header_lines(csfs_path)?;
+    let mut input = CsfInput::open(csfs_path)?;
+    if let Some(compression) = input.compression() {
+        println!("检测到 {} 压缩输入，由读取线程边读边解压", compression.name());
+    }
+    let headers = extract_header_lines(&input)?;
+    let mut consistency = ConsistencyCheck::new(warn_if_header_malformed(&headers));
+    let core_electrons = consistency.core_electrons;
+
+    let mut parameters = match source {
+        CsfSource::Lines { chunk_size } => ConversionParameters {
+            chunk_size: Some(chunk_size),
+            ..ConversionParameters::new(csfs_path, &input, "lines", max_line_len, writer_options, &props)
+        },
+        CsfSource::ByteRanges { range_bytes } => ConversionParameters {
+            range_bytes: Some(range_bytes),
+            ..ConversionParameters::new(csfs_path, &input, "byte_ranges", max_line_len, writer_options, &props)
+        },
+    };
+    parameters.num_workers = Some(pool.current_num_threads());
 
     // --- 2. 创建 Parquet 写入器 ---
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("idx", DataType::UInt64, false),
-        Field::new("line1", DataType::Utf8, false),
-        Field::new("line2", DataType::Utf8, false),
-        Field::new("line3", DataType::Utf8, false),
-    ]));
+    let schema = csf_table_schema();
 
-    let output_file = File::create(&output_path)?;
-    let props = WriterProperties::builder()
-        .set_compression(parquet::basic::Compression::UNCOMPRESSED)
-        .build();
+    let mut partial = PartialOutput::default();
+    let output_file = File::create(output_path).map_err(|e| CsfError::io(output_path, e))?;
+    partial.track(output_path.to_path_buf());
+    partial.track(header_file_path(csfs_path, output_path));
+    println!("Parquet 写入器已创建，压缩: {}", compression_name(&props));
     let mut writer = ArrowWriter::try_new(output_file, schema.clone(), Some(props))?;
-    println!("Parquet 写入器已创建，使用无压缩");
-
-    // --- 3. 流式读取 + 批量并行处理 ---
-    let input_file = File::open(csfs_path)?;
-    let reader = BufReader::new(input_file);
-    let mut lines_iter = reader.lines();
-
-    // Skip header lines
-    for _ in 0..CSF_HEADER_LINE_COUNT {
-        if lines_iter.next().is_none() {
-            break;
-        }
-    }
-
-    let mut batch_lines = Vec::with_capacity(chunk_size);
-    let mut csf_count = 0;
-    let mut total_lines = 0;
-    let mut truncated_count = 0;
 
+    // --- 3. 流水线: 读取 → 并行处理 → 按序写入 ---
     println!("开始并行处理 CSF 数据...");
-
-    loop {
-        // Read a batch of lines
-        let mut lines_read = 0;
-        for _ in 0..chunk_size {
-            match lines_iter.next() {
-                Some(Ok(line)) => {
-                    total_lines += 1;
-                    lines_read += 1;
-                    batch_lines.push(line);
-                }
-                Some(Err(e)) => return Err(e.into()),
-                None => break,
-            }
-        }
-
-        if batch_lines.is_empty() {
-            break;
-        }
-
-        // Ensure we have complete CSFs (3 lines each)
-        let num_full_csfs = batch_lines.len() / 3;
-        if num_full_csfs == 0 {
-            if lines_read == 0 {
-                break;
-            }
-            continue;
+    let mut output = OrderedOutput {
+        writer: &mut writer,
+        consistency: &mut consistency,
+        csf_count: 0,
+        truncated_count: 0,
+    };
+    let schema = &schema;
+
+    let (total_lines, block_csf_counts) = match (&mut input, source) {
+        (CsfInput::Compressed(stream), source) => {
+            let chunk_bytes = match source {
+                CsfSource::Lines { .. } => STREAM_CHUNK_BYTES,
+                CsfSource::ByteRanges { range_bytes } => range_bytes,
+            };
+            let mut layout = RangeLayout::new();
+
+            run_pipeline(
+                pool,
+                |send| {
+                    while let Some(chunk) = stream.next_chunk(chunk_bytes)? {
+                        if !send(chunk) {
+                            break;
+                        }
+                    }
+                    Ok(())
+                },
+                |chunk: CsfChunk| {
+                    let records = CsfRecords::new(&chunk.data);
+                    process_csf_range(records, chunk.last, schema, max_line_len, core_electrons)
+                },
+                |range| output.write(layout.place(range, output.csf_count)?),
+            )?;
+            (layout.total_lines, layout.block_csf_counts)
         }
-
-        let lines_to_process: Vec<String> = batch_lines.drain(..num_full_csfs * 3).collect();
-
-        // Create chunks for parallel processing
-        let chunks: Vec<&[String]> = lines_to_process.chunks(3).collect();
-
-        // Process batch in parallel using rayon
-        let batch_results: Vec<(u64, String, String, String, bool)> = chunks
-            .into_par_iter()
-            .enumerate()
-            .map(|(i, chunk)| {
-                if chunk.len() != 3 {
-                    return ((csf_count + i) as u64, String::new(), String::new(), String::new(), false);
-                }
-
-                let process_line = |line: &str, max_len: usize| -> (String, bool) {
-                    if line.len() > MAX_LINE_WARNING_THRESHOLD {
-                        eprintln!("警告: 行过长 ({} bytes)", line.len());
+        (CsfInput::Mapped(csf_file), CsfSource::Lines { chunk_size }) => {
+            let csfs_per_batch = (chunk_size / 3).clamp(1, WORK_ITEM_CSFS);
+
+            run_pipeline(
+                pool,
+                |send| {
+                    let mut records = csf_file.records();
+                    let mut first_idx = 0;
+                    loop {
+                        let mut batch = Vec::with_capacity(csfs_per_batch);
+                        for record in records.by_ref().take(csfs_per_batch) {
+                            batch.push(record?);
+                        }
+                        if batch.is_empty() {
+                            break;
+                        }
+                        let count = batch.len();
+                        if !send(ConversionWork { first_idx, records: batch }) {
+                            break;
+                        }
+                        first_idx += count;
                     }
-                    if line.len() > max_len {
-                        (line.chars().take(max_len).collect::<String>(), true)
-                    } else {
-                        (line.to_string(), false)
+                    Ok((records.total_lines, records.block_csf_counts))
+                },
+                |work: ConversionWork| {
+                    process_csf_batch(schema, work.first_idx, &work.records, max_line_len, core_electrons)
+                },
+                |processed| output.write(processed?),
+            )?
+        }
+        (CsfInput::Mapped(csf_file), CsfSource::ByteRanges { range_bytes }) => {
+            let csf_file = &*csf_file;
+            let range_count = (csf_file.bytes().len() - csf_file.data_start()).div_ceil(range_bytes);
+            let mut layout = RangeLayout::new();
+
+            run_pipeline(
+                pool,
+                |send| {
+                    for k in 0..range_count {
+                        if !send(k) {
+                            break;
+                        }
                     }
-                };
-
-                let (line1, t1) = process_line(&chunk[0], max_line_len);
-                let (line2, t2) = process_line(&chunk[1], max_line_len);
-                let (line3, t3) = process_line(&chunk[2], max_line_len);
-
-                ((csf_count + i) as u64, line1, line2, line3, t1 || t2 || t3)
-            })
-            .collect();
-
-        // Write results in order (par_iter + collect preserves order)
-        let mut idx_builder = UInt64Builder::with_capacity(num_full_csfs);
-        let mut line1_builder = StringBuilder::with_capacity(num_full_csfs, num_full_csfs * max_line_len);
-        let mut line2_builder = StringBuilder::with_capacity(num_full_csfs, num_full_csfs * max_line_len);
-        let mut line3_builder = StringBuilder::with_capacity(num_full_csfs, num_full_csfs * max_line_len);
-
-        for (idx, line1, line2, line3, truncated) in batch_results {
-            idx_builder.append_value(idx);
-            line1_builder.append_value(&line1);
-            line2_builder.append_value(&line2);
-            line3_builder.append_value(&line3);
-            if truncated {
-                truncated_count += 1;
-            }
+                    Ok(())
+                },
+                |k| read_csf_range(csf_file, k, range_count, range_bytes, schema, max_line_len, core_electrons),
+                |range| output.write(layout.place(range, output.csf_count)?),
+            )?;
+            (layout.total_lines, layout.block_csf_counts)
         }
-
-        let batch = RecordBatch::try_new(
-            schema.clone(),
-            vec![
-                Arc::new(idx_builder.finish()),
-                Arc::new(line1_builder.finish()),
-                Arc::new(line2_builder.finish()),
-                Arc::new(line3_builder.finish()),
-            ],
-        )?;
-
-        writer.write(&batch)?;
-
-        csf_count += num_full_csfs;
-    }
-
-    // --- 4. 完成写入 ---
-    writer.close()?;
+    };
+    let OrderedOutput { csf_count, truncated_count, .. } = output;
 
     let final_stats = ConversionStats {
         csf_count,
         total_lines,
         truncated_count,
+        block_csf_counts,
+        electron_count: consistency.electron_count,
+        electron_count_mismatch_count: consistency.electron_count_mismatch_count,
+        parity_mismatch_count: consistency.parity_mismatch_count,
+        unparsed_count: consistency.unparsed_count,
     };
 
     println!("\n并行转换完成！");
-    println!("总行数: {}", total_lines);
+    println!("总行数: {}", final_stats.total_lines);
     println!("CSF 数量: {}", csf_count);
+    println!("Block 数量: {}", final_stats.block_csf_counts.len());
     println!("截断行数: {}", truncated_count);
     if truncated_count > 0 {
         println!("警告: {} 行被截断，考虑增加 max_line_len 参数", truncated_count);
     }
+    consistency.report();
 
-    // --- 5. 创建 TOML 头部文件 ---
+    // --- 4. 写入 Parquet 元数据并完成写入 ---
     let header_data = HeaderData {
         header_info: HeaderInfo {
             header_lines: headers,
         },
-        conversion_stats: final_stats.clone(),
+        conversion_stats: final_stats,
     };
+    append_header_metadata(&mut writer, &header_data, Some(&parameters))?;
+    writer.close()?;
 
-    let header_dir = safe_parent_dir(output_path);
-    let input_file_stem = csfs_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("csfs");
-    let header_filename = format!("{}_header.toml", input_file_stem);
-    let header_path = header_dir.join(header_filename);
-    let toml_string = toml::to_string_pretty(&header_data)?;
-    std::fs::write(&header_path, toml_string)?;
+    // --- 5. 创建 TOML 头部文件 ---
+    let header_path = write_header_file(csfs_path, output_path, &header_data)?;
+    partial.keep();
 
     println!("Header 文件: {:?}", header_path);
 
-    Ok(final_stats)
+    Ok(header_data.conversion_stats)
 }
 
 /// Convert CSF text file to Parquet format using sequential processing.
@@ -320,6 +1116,8 @@ pub fn convert_csfs_to_parquet_parallel(
 /// * `output_path` - Path to output Parquet file
 /// * `max_line_len` - Maximum line length (lines longer than this are truncated)
 /// * `chunk_size` - Number of lines per batch processing (larger = better efficiency)
+/// * `writer_options` - Codec, row groups and encodings of the Parquet file
+///   (uncompressed unless a codec is set)
 ///
 /// # Returns
 ///
@@ -327,6 +1125,7 @@ pub fn convert_csfs_to_parquet_parallel(
 /// * `csf_count` - Number of CSFs processed
 /// * `total_lines` - Total number of lines processed
 /// * `truncated_count` - Number of lines that were truncated
+/// * `block_csf_counts` - Number of CSFs in each J/parity block
 ///
 /// # CSF File Format
 ///
@@ -336,200 +1135,143 @@ pub fn convert_csfs_to_parquet_parallel(
 ///   * Line 1: CSF identifier/configuration
 ///   * Line 2: Additional parameters
 ///   * Line 3: More parameters or coefficients
+/// * A line containing only ` *` separates two J/parity blocks
 ///
 /// # Header File
 ///
 /// Automatically generates `[input_file_stem]_header.toml` in the output directory
-/// containing the 5-line header and conversion statistics.
+/// containing the 5-line header and conversion statistics. The same header, the
+/// core and peel subshells, the crate version and the conversion parameters are
+/// stored in the Parquet key-value metadata under the `rcsfs.*` keys, so readers
+/// find the header even when the output is renamed.
+///
+/// If the conversion fails after the output file was created, the partial
+/// Parquet file and the header file are removed before the error is returned.
 pub fn convert_csfs_to_parquet(
     csfs_path: &Path,
     output_path: &Path,
     max_line_len: usize,
     chunk_size: usize,
-) -> Result<ConversionStats, Box<dyn std::error::Error + Send + Sync>> {
+    writer_options: &WriterOptions,
+) -> Result<ConversionStats> {
+    let props = writer_options
+        .builder(parquet::basic::Compression::UNCOMPRESSED)?
+        .set_write_batch_size(chunk_size)
+        .build();
     println!("开始转换，最大行长度: {}", max_line_len);
     println!("输入文件: {:?}", csfs_path);
     println!("输出文件: {:?}", output_path);
 
     // 打开输入文件
-    let input_file = File::open(csfs_path)?;
-    let reader = BufReader::new(input_file);
-    let mut lines_iter = reader.lines();
+    let mut input = CsfInput::open(csfs_path)?;
+    if let Some(compression) = input.compression() {
+        println!("检测到 {} 压缩输入，边读边解压", compression.name());
+    }
 
     // --- 1. 处理 Header (5行) ---
-    let headers = extract_header_lines(csfs_path)?;
-
-    // Skip header lines in the main iterator since extract_header_lines uses its own file handle
-    for _ in 0..CSF_HEADER_LINE_COUNT {
-        if lines_iter.next().is_none() {
-            break;
-        }
-    }
+    let headers = extract_header_lines(&input)?;
+    let mut consistency = ConsistencyCheck::new(warn_if_header_malformed(&headers));
+    let parameters = ConversionParameters {
+        chunk_size: Some(chunk_size),
+        ..ConversionParameters::new(csfs_path, &input, "sequential", max_line_len, writer_options, &props)
+    };
 
     // --- 2. 创建 Arrow Schema ---
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("idx", DataType::UInt64, false),
-        Field::new("line1", DataType::Utf8, false),
-        Field::new("line2", DataType::Utf8, false),
-        Field::new("line3", DataType::Utf8, false),
-    ]));
+    let schema = csf_table_schema();
 
     // --- 3. 创建 Parquet 写入器 ---
-    let output_file = File::create(output_path)?;
-    let props = WriterProperties::builder()
-        .set_compression(parquet::basic::Compression::UNCOMPRESSED)
-        .set_write_batch_size(chunk_size as usize)
-        .build();
-
+    let mut partial = PartialOutput::default();
+    let output_file = File::create(output_path).map_err(|e| CsfError::io(output_path, e))?;
+    partial.track(output_path.to_path_buf());
+    partial.track(header_file_path(csfs_path, output_path));
+    println!("Parquet 写入器已创建，压缩: {}", compression_name(&props));
     let mut writer = ArrowWriter::try_new(output_file, schema.clone(), Some(props))?;
-    println!("Parquet 写入器已创建，使用无压缩");
 
     // --- 4. 批量处理 ---
-    let mut batch_lines = Vec::with_capacity(chunk_size);
+    let csfs_per_batch = (chunk_size / 3).max(1);
     let mut csf_count = 0;
-    let mut total_lines = 0;
     let mut truncated_count = 0;
 
     println!("开始处理 CSF 数据...");
 
-    loop {
-        // 读取 chunk_size 行
-        let mut lines_read_this_iteration = 0;
-        for _ in 0..chunk_size {
-            match lines_iter.next() {
-                Some(Ok(line)) => {
-                    total_lines += 1;
-                    lines_read_this_iteration += 1;
-
-                    // Warn about excessively long lines (potential memory issue)
-                    if line.len() > MAX_LINE_WARNING_THRESHOLD {
-                        eprintln!(
-                            "警告: 第 {} 行过长 ({} bytes)，可能消耗大量内存",
-                            total_lines, line.len()
+    // 压缩输入按解压出的块调用一次，批次不跨块
+    let (total_lines, block_csf_counts) = input.scan(|records| {
+        let mut batch_records = Vec::with_capacity(csfs_per_batch);
+        loop {
+            // 读取 chunk_size 行对应的完整 CSF
+            for record in records.by_ref().take(csfs_per_batch) {
+                let (record, truncated) = decode_record(&record?, csf_count + batch_records.len(), max_line_len)?;
+                if truncated {
+                    truncated_count += 1;
+                    if truncated_count <= 5 {
+                        // 只打印前5个截断警告
+                        println!(
+                            "警告: 第 {} 个 CSF 被截断 (> {})",
+                            csf_count + batch_records.len(),
+                            max_line_len
                         );
                     }
-
-                    // 检查是否需要截断
-                    if line.len() > max_line_len {
-                        truncated_count += 1;
-                        if truncated_count <= 5 {
-                            // 只打印前5个截断警告
-                            println!(
-                                "警告: 第 {} 行被截断 ({} > {})",
-                                total_lines,
-                                line.len(),
-                                max_line_len
-                            );
-                        }
-                    }
-
-                    // 截断到 max_line_len
-                    let processed_line = if line.len() > max_line_len {
-                        line.chars().take(max_line_len).collect::<String>()
-                    } else {
-                        line
-                    };
-                    batch_lines.push(processed_line);
                 }
-                Some(Err(e)) => return Err(e.into()),
-                None => break,
+                batch_records.push(record);
             }
-        }
 
-        if batch_lines.is_empty() {
-            break;
-        }
-
-        // 确保是 3 的倍数
-        let num_full_csfs = batch_lines.len() / 3;
-        if num_full_csfs == 0 {
-            // No more lines to read and incomplete CSF remaining - discard and exit
-            if lines_read_this_iteration == 0 {
-                break;
+            if batch_records.is_empty() {
+                return Ok(());
             }
-            // 保留剩余行给下一轮
-            continue;
-        }
 
-        // 处理完整的 CSF 块
-        let lines_to_process = batch_lines.drain(..num_full_csfs * 3).collect::<Vec<_>>();
-
-        // 创建 Arrow 数组
-        let mut idx_builder = UInt64Builder::with_capacity(num_full_csfs);
-        let mut line1_builder =
-            StringBuilder::with_capacity(num_full_csfs, num_full_csfs * max_line_len);
-        let mut line2_builder =
-            StringBuilder::with_capacity(num_full_csfs, num_full_csfs * max_line_len);
-        let mut line3_builder =
-            StringBuilder::with_capacity(num_full_csfs, num_full_csfs * max_line_len);
-
-        for (i, chunk) in lines_to_process.chunks(3).enumerate() {
-            if chunk.len() == 3 {
-                idx_builder.append_value((csf_count + i) as u64);
-                line1_builder.append_value(&chunk[0]);
-                line2_builder.append_value(&chunk[1]);
-                line3_builder.append_value(&chunk[2]);
-            }
-        }
+            // 构建 RecordBatch 并写入 Parquet
+            let batch = build_csf_batch(&schema, csf_count, &batch_records, max_line_len, &mut consistency)?;
+            writer.write(&batch)?;
 
-        // 构建 RecordBatch
-        let batch = RecordBatch::try_new(
-            schema.clone(),
-            vec![
-                Arc::new(idx_builder.finish()),
-                Arc::new(line1_builder.finish()),
-                Arc::new(line2_builder.finish()),
-                Arc::new(line3_builder.finish()),
-            ],
-        )?;
-
-        // 写入 Parquet
-        writer.write(&batch)?;
-
-        csf_count += num_full_csfs;
-        if csf_count % 100000 == 0 {
-            println!("已处理 {} 个 CSF", csf_count);
+            let previous = csf_count;
+            csf_count += batch_records.len();
+            batch_records.clear();
+            if csf_count / 100000 > previous / 100000 {
+                println!("已处理 {} 个 CSF", csf_count);
+            }
         }
-    }
-
-    // 完成写入
-    writer.close()?;
+    })?;
 
     // 创建 TOML 头部数据
     let header_data = HeaderData {
         header_info: HeaderInfo {
-            header_lines: headers.clone(),
+            header_lines: headers,
         },
         conversion_stats: ConversionStats {
             csf_count,
             total_lines,
             truncated_count,
+            block_csf_counts,
+            electron_count: consistency.electron_count,
+            electron_count_mismatch_count: consistency.electron_count_mismatch_count,
+            parity_mismatch_count: consistency.parity_mismatch_count,
+            unparsed_count: consistency.unparsed_count,
         },
     };
 
+    // 头部数据同时写入 Parquet 元数据，然后完成写入
+    append_header_metadata(&mut writer, &header_data, Some(&parameters))?;
+    writer.close()?;
+
     // 保存头部数据为 [输入文件名前缀]_header.toml 文件
-    let header_dir = safe_parent_dir(output_path);
-    let input_file_stem = csfs_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("csfs");
-    let header_filename = format!("{}_header.toml", input_file_stem);
-    let header_path = header_dir.join(header_filename);
-    let toml_string = toml::to_string_pretty(&header_data)?;
-    std::fs::write(&header_path, toml_string)?;
+    let header_path = write_header_file(csfs_path, output_path, &header_data)?;
+    partial.keep();
+    let stats = &header_data.conversion_stats;
 
     // 统计信息
     println!("\n转换完成！");
     println!("================ 统计信息 ================");
-    println!("总行数: {}", total_lines);
-    println!("CSF 数量: {}", csf_count);
-    println!("截断行数: {}", truncated_count);
-    if truncated_count > 0 {
+    println!("总行数: {}", stats.total_lines);
+    println!("CSF 数量: {}", stats.csf_count);
+    println!("Block 数量: {}", stats.block_csf_counts.len());
+    println!("截断行数: {}", stats.truncated_count);
+    if stats.truncated_count > 0 {
         println!(
             "警告: {} 行被截断，考虑增加 max_line_len 参数",
-            truncated_count
+            stats.truncated_count
         );
     }
+    consistency.report();
     println!("输出文件: {:?}", output_path);
     println!("Header 文件: {:?}", header_path);
     println!("==========================================");
@@ -537,61 +1279,135 @@ pub fn convert_csfs_to_parquet(
     Ok(header_data.conversion_stats)
 }
 
-/// Get Parquet file metadata without reading the actual data.
-///
-/// This function efficiently retrieves file information and metadata
-/// without loading the entire file into memory.
+/// Text written between two J/parity blocks when rebuilding a CSF file
+const BLOCK_SEPARATOR: &str = " *";
+
+/// Statistics returned by `parquet_to_csfs`
+#[derive(Debug, Clone)]
+pub struct CsfExportStats {
+    pub csf_count: usize,
+    pub block_count: usize,
+    /// File the header was read from: the Parquet file itself when the header
+    /// came from its metadata, otherwise the header TOML file
+    pub header_file: PathBuf,
+}
+
+/// Get a string column of a CSF table by name
 ///
-/// # Arguments
+/// Tables filtered with Polars or pyarrow may store the lines as `LargeUtf8`
+/// or `Utf8View`; those columns are cast to `Utf8`.
+pub(crate) fn string_column(batch: &RecordBatch, name: &str) -> Result<StringArray> {
+    let column = batch
+        .column_by_name(name)
+        .ok_or_else(|| CsfError::Schema(format!("{} column not found", name)))?;
+    match column.data_type() {
+        DataType::Utf8 => Ok(column.as_string::<i32>().clone()),
+        DataType::LargeUtf8 | DataType::Utf8View => Ok(cast(column, &DataType::Utf8)?.as_string::<i32>().clone()),
+        _ => Err(CsfError::Schema(format!("{} column is not string type", name))),
+    }
+}
+
+/// Write a Parquet CSF table back to a GRASP-readable CSF text file.
 ///
-/// * `parquet_path` - Path to the Parquet file
+/// The 5 header lines are taken verbatim from the header stored during
+/// conversion, in the Parquet metadata or the `_header.toml`, followed by
+/// one `line1`/`line2`/`line3` triple per row in file order. Whenever the
+/// `block` column changes value a ` *` separator line is inserted; tables
+/// without a `block` column are written as a single block.
 ///
-/// # Returns
+/// # Arguments
 ///
-/// Returns a Python dictionary containing:
-/// * `file_path` - Path to the Parquet file
-/// * `file_size` - File size in bytes
-/// * `num_rows` - Number of rows in the file
-/// * `num_columns` - Number of columns
-/// * `compression` - Compression method used (e.g., "GZIP")
+/// * `input_parquet` - Path to a Parquet file produced by `convert_csfs_to_parquet*`
+///   (possibly filtered or re-selected afterwards)
+/// * `output_path` - Path of the CSF text file to write
+/// * `header_path` - Header TOML file; if `None` the header is read from the
+///   Parquet metadata, or from a `_header.toml` next to the Parquet file
 ///
-/// # Errors
+/// # Round-trip Guarantee
 ///
-/// Returns an error if the file cannot be opened or is not a valid Parquet file.
-pub fn get_parquet_metadata(
-    parquet_path: &Path,
-) -> Result<pyo3::Py<pyo3::PyAny>, Box<dyn std::error::Error + Send + Sync>> {
-    use pyo3::{
-        Python,
-        types::{PyDict, PyDictMethods},
-    };
+/// Converting a `.csf` file and writing it back reproduces the original bytes,
+/// provided no line was truncated during conversion (`truncated_count == 0`),
+/// the file uses LF line endings and ends with a newline, and block separators
+/// are written as ` *` — which is what `rcsfgenerate` produces.
+pub fn parquet_to_csfs(
+    input_parquet: &Path,
+    output_path: &Path,
+    header_path: Option<&Path>,
+) -> Result<CsfExportStats> {
+    // --- 1. 读取 Header (Parquet 元数据或 TOML 文件) ---
+    let (header_data, header_path) = load_header_data(input_parquet, header_path)?.ok_or_else(|| {
+        CsfError::Header(
+            "Could not find the header in the Parquet metadata or a header file. Please provide header_path."
+                .to_string(),
+        )
+    })?;
+    if header_data.conversion_stats.truncated_count > 0 {
+        println!(
+            "警告: 转换时有 {} 行被截断，输出文件与原始 CSF 文件不一致",
+            header_data.conversion_stats.truncated_count
+        );
+    }
 
-    // 获取文件大小
-    let file_metadata = std::fs::metadata(parquet_path)?;
-    let file_size = file_metadata.len();
+    println!("开始写出 CSF 文件");
+    println!("输入文件: {:?}", input_parquet);
+    println!("输出文件: {:?}", output_path);
+    println!("Header 文件: {:?}", header_path);
 
-    // 打开 Parquet 文件
-    let file = File::open(parquet_path)?;
-    let reader = SerializedFileReader::new(file)?;
-    let metadata = reader.metadata();
-
-    // 获取行数和列数
-    let num_rows = metadata.file_metadata().num_rows();
-    let schema = metadata.file_metadata().schema();
-    let num_columns = schema.get_fields().len();
-
-    // 创建 Python 字典并返回
-    Python::attach(|py| {
-        let dict = PyDict::new(py);
-        dict.set_item("file_path", parquet_path.to_string_lossy().as_ref())?;
-        dict.set_item("file_size", file_size)?;
-        dict.set_item("num_rows", num_rows)?;
-        dict.set_item("num_columns", num_columns)?;
-        dict.set_item(
-            "compression",
-            metadata.file_metadata().created_by().unwrap_or("Unknown"),
-        )?;
-
-        Ok(dict.into())
+    // --- 2. 打开 Parquet 读取器 ---
+    let input_file = File::open(input_parquet).map_err(|e| CsfError::io(input_parquet, e))?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(input_file)?.build()?;
+
+    // --- 3. 写出 Header 和 CSF ---
+    let mut partial = PartialOutput::default();
+    let mut out = BufWriter::new(File::create(output_path).map_err(|e| CsfError::io(output_path, e))?);
+    partial.track(output_path.to_path_buf());
+    for line in &header_data.header_info.header_lines {
+        writeln!(out, "{}", line)?;
+    }
+
+    let mut csf_count = 0;
+    let mut block_count = 0;
+    let mut current_block: Option<u32> = None;
+
+    for batch in reader {
+        let batch = batch?;
+        let line1 = string_column(&batch, "line1")?;
+        let line2 = string_column(&batch, "line2")?;
+        let line3 = string_column(&batch, "line3")?;
+        let block = match batch.column_by_name("block") {
+            Some(col) => Some(
+                col.as_any()
+                    .downcast_ref::<UInt32Array>()
+                    .ok_or_else(|| CsfError::Schema("block column is not uint32 type".to_string()))?,
+            ),
+            None => None,
+        };
+
+        for i in 0..batch.num_rows() {
+            let row_block = block.map_or(0, |b| b.value(i));
+            if current_block != Some(row_block) {
+                if current_block.is_some() {
+                    writeln!(out, "{}", BLOCK_SEPARATOR)?;
+                }
+                current_block = Some(row_block);
+                block_count += 1;
+            }
+            writeln!(out, "{}", line1.value(i))?;
+            writeln!(out, "{}", line2.value(i))?;
+            writeln!(out, "{}", line3.value(i))?;
+        }
+        csf_count += batch.num_rows();
+    }
+
+    out.flush()?;
+    drop(out);
+    partial.keep();
+
+    println!("\n写出完成！CSF 数量: {} | Block 数量: {}", csf_count, block_count);
+
+    Ok(CsfExportStats {
+        csf_count,
+        block_count,
+        header_file: header_path,
     })
 }
@@ -4,14 +4,21 @@
 //! for machine learning applications. Each CSF is parsed into a fixed-length array
 //! containing electron counts and angular momentum coupling values.
 
+use crate::coupling::{subshell_j_multiplicity, subshell_seniorities, triangle};
+use crate::csf::{double_j_to_string, Csf, CsfSubshell, Parity};
+use crate::csf_header::CsfHeader;
+use crate::orbital::Orbital;
+use crate::validation::check_coupling;
 use std::collections::HashMap;
-use std::fs::read_to_string;
 use std::path::Path;
 
 /// Parquet reading/writing support
 pub mod parquet_batch {
     use super::*;
-    use arrow::array::{StringArray, UInt64Array};
+    use crate::csfs_conversion::string_column;
+    use crate::error::{CsfError, Result};
+    use crate::writer_options::WriterOptions;
+    use arrow::array::UInt64Array;
     use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
     use std::path::PathBuf;
     use std::sync::Arc;
@@ -23,54 +30,38 @@ pub mod parquet_batch {
     ///
     /// # Returns
     /// * `Ok(Vec<String>)` - List of peel subshell names
-    /// * `Err(String)` - Error message if parsing fails
-    pub fn read_peel_subshells_from_header(header_path: &Path) -> Result<Vec<String>, String> {
-        use toml::Value;
-
-        let mut toml_content = read_to_string(header_path)
-            .map_err(|e| format!("Failed to read header file: {}", e))?;
-
-        // Normalize line endings and trim whitespace
-        toml_content = toml_content.replace("\r\n", "\n");
-        let toml_content = toml_content.trim();
-
-        // Parse the TOML content using from_str instead of parse()
-        let toml_value: Value =
-            toml::from_str(toml_content).map_err(|e| format!("Failed to parse TOML: {}", e))?;
-
-        // Get header_lines from [header_info] section
-        let header_lines = toml_value
-            .get("header_info")
-            .and_then(|v| v.get("header_lines"))
-            .and_then(|v| v.as_array())
-            .ok_or("header_info.header_lines not found in TOML")?;
-
-        // Peel subshells are on line 4 (index 3): "  2s   2p-  2p   3s..."
-        if let Some(line_value) = header_lines.get(3) {
-            let line = line_value
-                .as_str()
-                .ok_or("header_lines[3] is not a string")?;
-
-            // Parse space-separated subshell names
-            let parts: Vec<&str> = line.split_whitespace().collect();
-
-            // Filter valid subshell names (must contain at least one letter)
-            let subshells: Vec<String> = parts
-                .into_iter()
-                .filter(|s| {
-                    s.chars().any(|c| c.is_alphabetic())
-                        && s.chars()
-                            .all(|c| c.is_alphanumeric() || c == '+' || c == '-' || c == '_')
-                })
-                .map(|s| s.to_string())
-                .collect();
-
-            if !subshells.is_empty() {
-                return Ok(subshells);
-            }
-        }
+    /// * `Err(CsfError::Header)` - If the file cannot be read or parsed
+    pub fn read_peel_subshells_from_header(header_path: &Path) -> Result<Vec<String>> {
+        CsfHeader::from_header_toml(header_path)
+            .map(|header| header.peel_labels())
+            .map_err(CsfError::Header)
+    }
 
-        Err("Could not find peel subshells in header file".to_string())
+    /// Read peel subshells of a converted CSF table
+    ///
+    /// The header is read from `header_path` when given, otherwise from the
+    /// table's Parquet metadata, otherwise from a header file found by
+    /// [`find_header_file`].
+    ///
+    /// # Arguments
+    /// * `parquet_path` - Path to a Parquet file written by rCSFs
+    /// * `header_path` - Optional path to a header TOML file
+    ///
+    /// # Returns
+    /// * `Ok(Vec<String>)` - List of peel subshell names
+    /// * `Err(CsfError::Header)` - If no header is found or it cannot be parsed
+    pub fn read_peel_subshells_from_parquet(parquet_path: &Path, header_path: Option<&Path>) -> Result<Vec<String>> {
+        let (header_data, source) = crate::csfs_conversion::load_header_data(parquet_path, header_path)?
+            .ok_or_else(|| {
+                CsfError::Header(
+                    "Could not find the header in the Parquet metadata or a header file. \
+                     Please provide peel_subshells or header_path."
+                        .to_string(),
+                )
+            })?;
+        CsfHeader::parse(&header_data.header_info.header_lines)
+            .map(|header| header.peel_labels())
+            .map_err(|e| CsfError::Header(format!("{:?}: {}", source, e)))
     }
 
     /// Find the header file for a given parquet file
@@ -102,6 +93,285 @@ pub mod parquet_batch {
         None
     }
 
+    /// Output schema of a descriptor file: `idx`, the passthrough columns, then
+    /// `col_0..col_N`
+    ///
+    /// # Returns
+    /// * `Ok((indices, schema))` - Input columns copied unchanged, and the output schema
+    /// * `Err(CsfError::Schema)` - If `idx` or a requested passthrough column is missing
+    fn descriptor_output_schema(
+        input_schema: &arrow::datatypes::Schema,
+        passthrough_columns: &[String],
+        descriptor_size: usize,
+        error_policy: ErrorPolicy,
+    ) -> Result<(Vec<usize>, Arc<arrow::datatypes::Schema>)> {
+        use arrow::datatypes::{DataType, Field, Schema};
+
+        let mut indices: Vec<usize> = Vec::with_capacity(passthrough_columns.len() + 1);
+        for name in std::iter::once("idx").chain(passthrough_columns.iter().map(String::as_str)) {
+            let index = input_schema
+                .index_of(name)
+                .map_err(|_| CsfError::Schema(format!("Column '{}' not found in input parquet", name)))?;
+            if indices.contains(&index) {
+                continue;
+            }
+            if name.starts_with("col_") {
+                return Err(CsfError::InvalidInput(format!(
+                    "Passthrough column '{}' clashes with the descriptor columns",
+                    name
+                )));
+            }
+            indices.push(index);
+        }
+
+        let mut fields: Vec<Field> = indices
+            .iter()
+            .map(|&i| input_schema.field(i).clone())
+            .collect();
+        for i in 0..descriptor_size {
+            fields.push(Field::new(
+                format!("col_{}", i),
+                DataType::Int32,
+                error_policy == ErrorPolicy::Nulls,
+            ));
+        }
+        Ok((indices, Arc::new(Schema::new(fields))))
+    }
+
+    /// Writer properties shared by the descriptor and rejects files
+    ///
+    /// Without a codec in `writer_options` both are ZSTD (level 3) compressed.
+    fn descriptor_writer_properties(
+        writer_options: &WriterOptions,
+    ) -> Result<parquet::file::properties::WriterProperties> {
+        let zstd_3 = parquet::basic::ZstdLevel::try_new(3).expect("3 is a valid ZSTD level");
+        writer_options.properties(parquet::basic::Compression::ZSTD(zstd_3))
+    }
+
+    /// What to write for a CSF whose descriptor cannot be generated
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum ErrorPolicy {
+        /// Abort on the first failure
+        Fail,
+        /// Leave the row out of the output
+        Skip,
+        /// Write an all-zero descriptor
+        #[default]
+        Zeros,
+        /// Write null descriptor values
+        Nulls,
+    }
+
+    impl std::str::FromStr for ErrorPolicy {
+        type Err = String;
+
+        fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+            match s.to_ascii_lowercase().as_str() {
+                "fail" => Ok(ErrorPolicy::Fail),
+                "skip" => Ok(ErrorPolicy::Skip),
+                "zeros" => Ok(ErrorPolicy::Zeros),
+                "nulls" => Ok(ErrorPolicy::Nulls),
+                _ => Err(format!(
+                    "Invalid error policy '{}': expected 'fail', 'skip', 'zeros' or 'nulls'",
+                    s
+                )),
+            }
+        }
+    }
+
+    /// A CSF whose descriptor could not be generated
+    struct Reject {
+        idx: u64,
+        lines: [Arc<str>; 3],
+        error: String,
+    }
+
+    /// Sidecar `[output_stem]_rejects.parquet` listing every failed CSF
+    ///
+    /// Failures are written to `[output_stem]_rejects.parquet.tmp`, which only
+    /// replaces the rejects file of an earlier run in [`RejectsWriter::finish`].
+    /// A run that fails for any other reason removes the temporary file and
+    /// leaves the earlier rejects file untouched.
+    struct RejectsWriter {
+        path: PathBuf,
+        temp_path: PathBuf,
+        properties: parquet::file::properties::WriterProperties,
+        writer: Option<parquet::arrow::arrow_writer::ArrowWriter<std::fs::File>>,
+        count: usize,
+    }
+
+    impl RejectsWriter {
+        fn new(output_file: &Path, properties: parquet::file::properties::WriterProperties) -> Self {
+            let stem = output_file.file_stem().unwrap_or_default().to_string_lossy();
+            let path = output_file.with_file_name(format!("{}_rejects.parquet", stem));
+            let temp_path = output_file.with_file_name(format!("{}_rejects.parquet.tmp", stem));
+            Self { path, temp_path, properties, writer: None, count: 0 }
+        }
+
+        fn write(&mut self, rejects: &[Reject]) -> Result<()> {
+            use arrow::array::{StringArray, UInt64Array};
+            use arrow::datatypes::{DataType, Field, Schema};
+            use arrow::record_batch::RecordBatch;
+
+            if rejects.is_empty() {
+                return Ok(());
+            }
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("idx", DataType::UInt64, false),
+                Field::new("line1", DataType::Utf8, false),
+                Field::new("line2", DataType::Utf8, false),
+                Field::new("line3", DataType::Utf8, false),
+                Field::new("error", DataType::Utf8, false),
+            ]));
+            if self.writer.is_none() {
+                let file = std::fs::File::create(&self.temp_path).map_err(|e| CsfError::io(&self.temp_path, e))?;
+                let writer = parquet::arrow::arrow_writer::ArrowWriter::try_new(
+                    file,
+                    schema.clone(),
+                    Some(self.properties.clone()),
+                )?;
+                self.writer = Some(writer);
+            }
+            let writer = self.writer.as_mut().unwrap();
+
+            let line = |n: usize| -> StringArray { rejects.iter().map(|r| Some(&*r.lines[n])).collect() };
+            let batch = RecordBatch::try_new(
+                schema,
+                vec![
+                    Arc::new(UInt64Array::from_iter_values(rejects.iter().map(|r| r.idx))),
+                    Arc::new(line(0)),
+                    Arc::new(line(1)),
+                    Arc::new(line(2)),
+                    Arc::new(rejects.iter().map(|r| Some(r.error.as_str())).collect::<StringArray>()),
+                ],
+            )?;
+            writer.write(&batch)?;
+            self.count += rejects.len();
+            Ok(())
+        }
+
+        /// Close the file and return its path if anything was written
+        ///
+        /// The rejects file of an earlier run is replaced, or removed when
+        /// this run had no failures, since it would describe another output.
+        fn finish(mut self) -> Result<Option<PathBuf>> {
+            match self.writer.take() {
+                Some(writer) => {
+                    writer.close()?;
+                    std::fs::rename(&self.temp_path, &self.path).map_err(|e| CsfError::io(&self.path, e))?;
+                    println!("警告: {} 个 CSF 解析失败，已记录到 {:?}", self.count, self.path);
+                    Ok(Some(self.path.clone()))
+                }
+                None => {
+                    match std::fs::remove_file(&self.path) {
+                        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(CsfError::io(&self.path, e)),
+                        _ => {}
+                    }
+                    Ok(None)
+                }
+            }
+        }
+
+        /// Record the CSF that stopped an `ErrorPolicy::Fail` run and return its error
+        fn fail(mut self, reject: Reject) -> CsfError {
+            let error = CsfError::malformed(reject.idx as usize, reject.error.clone());
+            match self.write(std::slice::from_ref(&reject)).and_then(|()| self.finish()) {
+                Ok(_) => error,
+                Err(e) => e,
+            }
+        }
+    }
+
+    impl Drop for RejectsWriter {
+        fn drop(&mut self) {
+            // Still open: the run failed before `finish`
+            if self.writer.take().is_some() {
+                let _ = std::fs::remove_file(&self.temp_path);
+            }
+        }
+    }
+
+    /// Descriptors of one batch, stored row after row in a single buffer
+    struct DescriptorRows {
+        values: Vec<i32>,
+        /// `false` for CSFs that failed to parse
+        parsed: Vec<bool>,
+    }
+
+    impl DescriptorRows {
+        fn with_capacity(rows: usize, descriptor_size: usize) -> Self {
+            DescriptorRows {
+                values: vec![0; rows * descriptor_size],
+                parsed: Vec::with_capacity(rows),
+            }
+        }
+
+        /// Parse the next CSF into its slot of the buffer
+        fn push(
+            &mut self,
+            generator: &CSFDescriptorGenerator,
+            descriptor_size: usize,
+            lines: [&str; 3],
+        ) -> std::result::Result<(), String> {
+            let row = self.parsed.len();
+            let slot = &mut self.values[row * descriptor_size..(row + 1) * descriptor_size];
+            let result = generator.parse_csf_into(lines[0], lines[1], lines[2], slot);
+            if result.is_err() {
+                slot.fill(0);
+            }
+            self.parsed.push(result.is_ok());
+            result
+        }
+    }
+
+    /// Build one output batch from the passthrough columns and the parsed
+    /// descriptors, applying the error policy to failed rows
+    fn build_descriptor_batch(
+        schema: &Arc<arrow::datatypes::Schema>,
+        passthrough: Vec<arrow::array::ArrayRef>,
+        rows: &DescriptorRows,
+        descriptor_size: usize,
+        error_policy: ErrorPolicy,
+    ) -> Result<arrow::record_batch::RecordBatch> {
+        use arrow::array::{Array, BooleanArray, Int32Builder};
+        use arrow::record_batch::RecordBatch;
+
+        let passthrough = if error_policy == ErrorPolicy::Skip && rows.parsed.contains(&false) {
+            let keep = BooleanArray::from(rows.parsed.clone());
+            passthrough
+                .iter()
+                .map(|column| arrow::compute::filter(column, &keep))
+                .collect::<std::result::Result<Vec<_>, _>>()?
+        } else {
+            passthrough
+        };
+
+        // Initialize builders for each column (avoids transpose overhead)
+        let mut builders: Vec<Int32Builder> = (0..descriptor_size)
+            .map(|_| Int32Builder::with_capacity(rows.parsed.len()))
+            .collect();
+        // Failed rows were zeroed when they were parsed
+        let descriptors = rows.values.chunks_exact(descriptor_size.max(1));
+        for (&parsed, desc) in rows.parsed.iter().zip(descriptors) {
+            match (parsed, error_policy) {
+                (true, _) | (false, ErrorPolicy::Zeros | ErrorPolicy::Fail) => {
+                    for (builder, &val) in builders.iter_mut().zip(desc) {
+                        builder.append_value(val);
+                    }
+                }
+                (false, ErrorPolicy::Nulls) => builders.iter_mut().for_each(|b| b.append_null()),
+                (false, ErrorPolicy::Skip) => {}
+            }
+        }
+
+        // Passthrough columns first, then one array per descriptor column
+        let column_arrays: Vec<Arc<dyn Array>> = passthrough
+            .into_iter()
+            .chain(builders.into_iter().map(|mut b| Arc::new(b.finish()) as Arc<dyn Array>))
+            .collect();
+        Ok(RecordBatch::try_new(schema.clone(), column_arrays)?)
+    }
+
     /// Result statistics for batch descriptor generation
     #[derive(Debug)]
     pub struct BatchDescriptorStats {
@@ -111,6 +381,25 @@ pub mod parquet_batch {
         pub descriptor_count: usize,
         pub orbital_count: usize,
         pub descriptor_size: usize,
+        /// CSFs whose descriptor could not be generated
+        pub failed_count: usize,
+        /// Sidecar file listing the failed CSFs, if there were any
+        pub rejects_file: Option<String>,
+        /// Threads that parsed CSFs (1 for the serial version)
+        pub num_workers: usize,
+        /// Wall-clock time of the whole run
+        pub elapsed_seconds: f64,
+    }
+
+    impl BatchDescriptorStats {
+        /// Input CSFs processed per second of wall-clock time
+        pub fn csfs_per_second(&self) -> f64 {
+            if self.elapsed_seconds > 0.0 {
+                self.csf_count as f64 / self.elapsed_seconds
+            } else {
+                0.0
+            }
+        }
     }
 
     /// Generate descriptors from a parquet file and write to Parquet file
@@ -118,34 +407,44 @@ pub mod parquet_batch {
     /// # Arguments
     /// * `input_parquet` - Path to input parquet file (must have line1, line2, line3 columns)
     /// * `output_file` - Path to output Parquet file for descriptors
-    /// * `peel_subshells` - Optional list of subshell names (auto-detected if None)
+    /// * `peel_subshells` - Optional list of subshell names (read from the
+    ///   Parquet metadata or a header file if None)
     /// * `header_path` - Optional path to header TOML file
+    /// * `passthrough_columns` - Input columns copied next to `idx`, e.g. `["block"]`
+    /// * `error_policy` - What to write for CSFs that fail to parse
+    /// * `writer_options` - Codec, row groups and encodings of the output files
     ///
     /// # Returns
     /// * `Ok(BatchDescriptorStats)` - Statistics about the batch operation
-    /// * `Err(String)` - Error message if operation fails
+    /// * `Err(CsfError)` - If a file cannot be read or written, the input lacks a
+    ///   required column, or a CSF fails to parse under `ErrorPolicy::Fail`
     ///
     /// # Output Format
-    /// Parquet with ZSTD compression (level 3) - columnar format, Polars compatible
+    /// Parquet with ZSTD compression (level 3) unless `writer_options` picks
+    /// another codec - columnar format, Polars compatible
     /// Read with: `polars.read_parquet()` or `pyarrow.parquet.read_table()`
+    ///
+    /// Columns are `idx`, the passthrough columns, then `col_0..col_N`. Unless
+    /// the policy is `Skip` there is one output row per input row. Every failed
+    /// CSF is recorded in `[output_stem]_rejects.parquet` (idx, line1..line3,
+    /// error); with `Fail` the first failure is recorded and returned as an
+    /// error. The rejects file of an earlier run is only replaced when the run
+    /// completes or stops under `Fail`. On any error the descriptor file is removed.
     pub fn generate_descriptors_from_parquet(
         input_parquet: &Path,
         output_file: &Path,
         peel_subshells: Option<Vec<String>>,
         header_path: Option<PathBuf>,
-    ) -> Result<BatchDescriptorStats, String> {
+        passthrough_columns: &[String],
+        error_policy: ErrorPolicy,
+        writer_options: &WriterOptions,
+    ) -> Result<BatchDescriptorStats> {
+        let start = std::time::Instant::now();
+        let properties = descriptor_writer_properties(writer_options)?;
         // Step 1: Determine peel_subshells
         let peel_subshells = match peel_subshells {
             Some(s) => s,
-            None => {
-                // Try to find and read header file
-                let header = match header_path {
-                    Some(h) => h,
-                    None => find_header_file(input_parquet)
-                        .ok_or("Could not auto-detect header file. Please provide peel_subshells or header_path.")?,
-                };
-                read_peel_subshells_from_header(&header)?
-            }
+            None => read_peel_subshells_from_parquet(input_parquet, header_path.as_deref())?,
         };
 
         let orbital_count = peel_subshells.len();
@@ -155,47 +454,29 @@ pub mod parquet_batch {
         let generator = super::CSFDescriptorGenerator::new(peel_subshells.clone());
 
         // Step 3: Open input parquet file
-        let file = std::fs::File::open(input_parquet)
-            .map_err(|e| format!("Failed to open input parquet: {}", e))?;
+        let file = std::fs::File::open(input_parquet).map_err(|e| CsfError::io(input_parquet, e))?;
 
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)
-            .map_err(|e| format!("Failed to create parquet reader: {}", e))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
 
-        let _schema = builder.schema();
+        let (passthrough_indices, output_schema) =
+            descriptor_output_schema(builder.schema(), passthrough_columns, descriptor_size, error_policy)?;
 
-        let mut reader = builder
-            .build()
-            .map_err(|e| format!("Failed to build parquet reader: {}", e))?;
+        let mut reader = builder.build()?;
 
-        // Step 4: Create output Parquet writer (ZSTD compression)
-        use arrow::datatypes::{DataType, Field, Schema};
+        // Step 4: Create output Parquet writer (ZSTD compression by default)
+        use crate::csfs_conversion::PartialOutput;
         use parquet::arrow::arrow_writer::ArrowWriter;
-        use parquet::file::properties::WriterProperties;
-        use std::sync::Arc;
 
-        // Output schema: descriptor columns (one column per descriptor element)
-        let mut fields = Vec::with_capacity(descriptor_size);
-        for i in 0..descriptor_size {
-            fields.push(Field::new(format!("col_{}", i), DataType::Int32, false));
-        }
-        let output_schema = Arc::new(Schema::new(fields));
-
-        let output_file_handle = std::fs::File::create(output_file)
-            .map_err(|e| format!("Failed to create output file: {}", e))?;
-
-        // Use ZSTD compression for better I/O performance and smaller file size
-        let props = WriterProperties::builder()
-            .set_compression(parquet::basic::Compression::ZSTD(
-                parquet::basic::ZstdLevel::try_new(3).unwrap(),
-            ))
-            .build();
+        let mut partial = PartialOutput::default();
+        let output_file_handle = std::fs::File::create(output_file).map_err(|e| CsfError::io(output_file, e))?;
+        partial.track(output_file.to_path_buf());
 
         let mut writer = ArrowWriter::try_new(
             output_file_handle,
             output_schema.clone(),
-            Some(props),
-        )
-            .map_err(|e| format!("Failed to create Parquet writer: {}", e))?;
+            Some(properties.clone()),
+        )?;
+        let mut rejects_writer = RejectsWriter::new(output_file, properties);
 
         // Step 5: Process each batch
         let mut total_csfs = 0;
@@ -206,90 +487,69 @@ pub mod parquet_batch {
                 Some(Ok(batch)) => {
                     let batch_size = batch.num_rows();
 
-                    // Get columns by index (parquet schema: idx, line1, line2, line3)
                     let idx_col = batch
-                        .column(0)
-                        .as_any()
-                        .downcast_ref::<UInt64Array>()
-                        .ok_or("idx column is not uint64 type")?;
-
-                    let line1_col = batch
-                        .column(1)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .ok_or("line1 column is not string type")?;
-
-                    let line2_col = batch
-                        .column(2)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .ok_or("line2 column is not string type")?;
-
-                    let line3_col = batch
-                        .column(3)
-                        .as_any()
-                        .downcast_ref::<StringArray>()
-                        .ok_or("line3 column is not string type")?;
+                        .column_by_name("idx")
+                        .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+                        .ok_or_else(|| CsfError::Schema("idx column is not uint64 type".to_string()))?;
+                    let line1_col = string_column(&batch, "line1")?;
+                    let line2_col = string_column(&batch, "line2")?;
+                    let line3_col = string_column(&batch, "line3")?;
 
                     // Process each row
-                    use arrow::array::{Array, Int32Builder};
-                    use std::sync::Arc;
-
-                    // Initialize builders for each column (avoids transpose overhead)
-                    let mut builders: Vec<Int32Builder> =
-                        (0..descriptor_size)
-                            .map(|_| arrow::array::Int32Builder::with_capacity(batch_size))
-                            .collect();
-
+                    let mut descriptors = DescriptorRows::with_capacity(batch_size, descriptor_size);
+                    let mut rejects = Vec::new();
                     for i in 0..batch_size {
                         let line1 = line1_col.value(i);
                         let line2 = line2_col.value(i);
                         let line3 = line3_col.value(i);
                         let idx = idx_col.value(i);
 
-                        match generator.parse_csf(line1, line2, line3) {
-                            Ok(descriptor) => {
-                                // Append directly to column builders
-                                for (col_idx, &val) in descriptor.iter().enumerate() {
-                                    builders[col_idx].append_value(val);
-                                }
-                                descriptor_count += 1;
+                        match descriptors.push(&generator, descriptor_size, [line1, line2, line3]) {
+                            Ok(()) => {}
+                            Err(e) if error_policy == ErrorPolicy::Fail => {
+                                let lines = [line1.into(), line2.into(), line3.into()];
+                                return Err(rejects_writer.fail(Reject { idx, lines, error: e }));
                             }
                             Err(e) => {
-                                eprintln!("Warning: Failed to parse CSF at index {}: {}", idx, e);
+                                rejects.push(Reject {
+                                    idx,
+                                    lines: [line1.into(), line2.into(), line3.into()],
+                                    error: e,
+                                });
                             }
                         }
                     }
 
                     total_csfs += batch_size;
+                    rejects_writer.write(&rejects)?;
 
-                    // Convert builders to Arrow arrays (one per column)
-                    let column_arrays: Vec<Arc<dyn Array>> = builders
-                        .into_iter()
-                        .map(|mut b| Arc::new(b.finish()) as Arc<dyn Array>)
+                    let passthrough = passthrough_indices
+                        .iter()
+                        .map(|&i| batch.column(i).clone())
                         .collect();
-
-                    // Create output record batch
-                    use arrow::record_batch::RecordBatch;
-                    let output_batch =
-                        RecordBatch::try_new(output_schema.clone(), column_arrays)
-                            .map_err(|e| format!("Failed to create output batch: {}", e))?;
-
-                    writer
-                        .write(&output_batch)
-                        .map_err(|e| format!("Failed to write batch: {}", e))?;
+                    let output_batch = build_descriptor_batch(
+                        &output_schema,
+                        passthrough,
+                        &descriptors,
+                        descriptor_size,
+                        error_policy,
+                    )?;
+                    descriptor_count += output_batch.num_rows();
+
+                    writer.write(&output_batch)?;
                 }
                 Some(Err(e)) => {
-                    return Err(format!("Error reading parquet batch: {}", e));
+                    return Err(e.into());
                 }
                 None => break,
             }
         }
 
         // Step 6: Finalize writer
-        writer
-            .finish()
-            .map_err(|e| format!("Failed to finish writer: {}", e))?;
+        writer.close()?;
+        let failed_count = rejects_writer.count;
+        let rejects_file = rejects_writer.finish()?;
+        partial.keep();
 
         Ok(BatchDescriptorStats {
             input_file: input_parquet.to_string_lossy().to_string(),
@@ -298,6 +558,10 @@ pub mod parquet_batch {
             descriptor_count,
             orbital_count,
             descriptor_size,
+            failed_count,
+            rejects_file: rejects_file.map(|p| p.to_string_lossy().to_string()),
+            num_workers: 1,
+            elapsed_seconds: start.elapsed().as_secs_f64(),
         })
     }
 
@@ -305,54 +569,103 @@ pub mod parquet_batch {
     // Pipeline Parallel Descriptor Generation
     ////////////////////////////////////////////////////////////////////////////////
 
+    /// A range of CSF rows shared across threads
+    ///
+    /// The arrays are slices of the input batch's columns, so sending a range
+    /// copies no line and workers parse the `&str` values in place.
+    struct CsfRows {
+        idx: UInt64Array,
+        lines: [arrow::array::StringArray; 3],
+    }
+
+    impl CsfRows {
+        fn len(&self) -> usize {
+            self.idx.len()
+        }
+    }
+
+    /// Rows per work item; input batches are split so every worker gets work
+    const WORK_ITEM_ROWS: usize = 8192;
+
     /// Work item sent from reader to workers
     struct WorkItem {
-        batch_idx: usize,
-        rows: Vec<(u64, Arc<str>, Arc<str>, Arc<str>)>,
+        rows: CsfRows,
+        /// Columns copied unchanged into the output (`idx` first)
+        passthrough: Vec<arrow::array::ArrayRef>,
     }
 
     /// Result item sent from workers to writer
     struct ResultItem {
-        batch_idx: usize,
-        descriptors: Vec<Vec<i32>>,
+        descriptors: DescriptorRows,
+        rejects: Vec<Reject>,
+        passthrough: Vec<arrow::array::ArrayRef>,
+    }
+
+    /// Parse the rows of one work item, collecting the failures
+    fn parse_work_item(generator: &CSFDescriptorGenerator, descriptor_size: usize, work: WorkItem) -> ResultItem {
+        let rows = work.rows;
+        let mut descriptors = DescriptorRows::with_capacity(rows.len(), descriptor_size);
+        let mut rejects = Vec::new();
+        for i in 0..rows.len() {
+            let lines = [rows.lines[0].value(i), rows.lines[1].value(i), rows.lines[2].value(i)];
+            if let Err(error) = descriptors.push(generator, descriptor_size, lines) {
+                rejects.push(Reject {
+                    idx: rows.idx.value(i),
+                    lines: lines.map(Arc::from),
+                    error,
+                });
+            }
+        }
+        ResultItem { descriptors, rejects, passthrough: work.passthrough }
     }
 
     /// Generate descriptors from parquet with full pipeline parallelization
     ///
-    /// This implementation uses a producer-consumer pipeline with three stages:
-    /// 1. **Reader thread**: Continuously reads parquet batches and sends to work channel
-    /// 2. **Worker threads (Rayon)**: Parse CSFs in parallel, send results to result channel
+    /// Runs on the reader → workers → ordered writer pipeline of the parallel
+    /// CSF converters:
+    /// 1. **Reader thread**: Reads parquet batches and splits them into work
+    ///    items of `WORK_ITEM_ROWS` CSFs
+    /// 2. **Worker pool**: A thread pool of `num_workers` threads built for this
+    ///    call parses the work items
     /// 3. **Writer thread**: Receives results in order and writes to parquet file
     ///
-    /// All three stages run concurrently, maximizing CPU utilization and I/O overlap.
+    /// All three stages run concurrently. Parsing never runs outside the
+    /// call's pool, so at most `num_workers` threads are busy parsing (plus the
+    /// reader and writer), and at most `2 * num_workers + 2` work items are held
+    /// between reading and writing, even when one item is slow to parse.
     ///
-    /// Output format: Parquet with single List<int32> column and ZSTD compression (level 3)
+    /// Output format: Parquet with `col_0..col_N` Int32 columns and ZSTD compression
+    /// (level 3) unless `writer_options` picks another codec
     ///
     /// # Arguments
     /// * `input_parquet` - Path to input parquet file
     /// * `output_file` - Path to output Parquet file
     /// * `peel_subshells` - List of subshell names
-    /// * `num_workers` - Number of worker threads (default: CPU core count)
+    /// * `num_workers` - Size of the thread pool built for this call (default: CPU core count)
+    /// * `passthrough_columns` - Input columns copied next to `idx`, e.g. `["block"]`
+    /// * `error_policy` - What to write for CSFs that fail to parse
+    /// * `writer_options` - Codec, row groups and encodings of the output files
+    ///
+    /// The output has `idx`, the passthrough columns, then `col_0..col_N` in
+    /// input order. Failed CSFs are handled as in `generate_descriptors_from_parquet`.
     pub fn generate_descriptors_from_parquet_parallel(
         input_parquet: &Path,
         output_file: &Path,
         peel_subshells: Vec<String>,
         num_workers: Option<usize>,
-    ) -> Result<BatchDescriptorStats, String> {
-        use arrow::array::{Array, StringArray, UInt64Array};
-        use arrow::datatypes::{DataType, Field, Schema};
-        use arrow::record_batch::RecordBatch;
+        passthrough_columns: &[String],
+        error_policy: ErrorPolicy,
+        writer_options: &WriterOptions,
+    ) -> Result<BatchDescriptorStats> {
+        use crate::csfs_conversion::{build_thread_pool, run_pipeline, PartialOutput};
+        use arrow::array::{Array, UInt64Array};
         use parquet::arrow::arrow_writer::ArrowWriter;
-        use parquet::file::properties::WriterProperties;
-        use std::sync::Arc;
-        use crossbeam_channel::{bounded, Sender, Receiver};
-        use std::collections::BTreeMap;
 
-        // Determine worker count
-        let num_workers = num_workers.unwrap_or_else(|| {
-            let default = num_cpus::get();
-            default
-        });
+        let start = std::time::Instant::now();
+
+        let pool = build_thread_pool(num_workers)?;
+        let num_workers = pool.current_num_threads();
+        let properties = descriptor_writer_properties(writer_options)?;
 
         let orbital_count = peel_subshells.len();
         let descriptor_size = 3 * orbital_count;
@@ -362,300 +675,154 @@ pub mod parquet_batch {
         println!("Worker: {} | 轨道: {} | 描述符大小: {}", num_workers, orbital_count, descriptor_size);
 
         ////////////////////////////////////////////////////////////////////////////////
-        // Phase 1: Setup channels with bounded capacity
-        ////////////////////////////////////////////////////////////////////////////////
-        let channel_capacity = num_workers * 2;
-        let (work_tx, work_rx): (Sender<WorkItem>, Receiver<WorkItem>) = bounded(channel_capacity);
-        let (result_tx, result_rx): (Sender<ResultItem>, Receiver<ResultItem>) = bounded(channel_capacity);
-
-        ////////////////////////////////////////////////////////////////////////////////
-        // Phase 2: Setup output schema and writer (multi-column format for better performance)
+        // Phase 1: Setup output schema and writer (multi-column format for better performance)
         ////////////////////////////////////////////////////////////////////////////////
         // Create descriptor columns (one column per descriptor element)
         // This is much faster than List column format for large datasets
-        let mut fields = Vec::with_capacity(descriptor_size);
-        for i in 0..descriptor_size {
-            fields.push(Field::new(format!("col_{}", i), DataType::Int32, false));
-        }
-        let schema = Arc::new(Schema::new(fields));
+        let input_file = std::fs::File::open(input_parquet).map_err(|e| CsfError::io(input_parquet, e))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(input_file)?;
+        let (passthrough_indices, schema) =
+            descriptor_output_schema(builder.schema(), passthrough_columns, descriptor_size, error_policy)?;
+        let reader = builder.with_batch_size(65536).build()?;
 
-        let output_file_handle = std::fs::File::create(output_file)
-            .map_err(|e| format!("Failed to create output file: {}", e))?;
-
-        let props = WriterProperties::builder()
-            .set_compression(parquet::basic::Compression::ZSTD(
-                parquet::basic::ZstdLevel::try_new(3).unwrap(),
-            ))
-            .build();
+        let mut partial = PartialOutput::default();
+        let output_file_handle = std::fs::File::create(output_file).map_err(|e| CsfError::io(output_file, e))?;
+        partial.track(output_file.to_path_buf());
 
         let mut writer = ArrowWriter::try_new(
             output_file_handle,
             schema.clone(),
-            Some(props),
-        )
-            .map_err(|e| format!("Failed to create Parquet writer: {}", e))?;
+            Some(properties.clone()),
+        )?;
+        let mut rejects_writer = RejectsWriter::new(output_file, properties);
 
         ////////////////////////////////////////////////////////////////////////////////
-        // Phase 3: Spawn reader thread
+        // Phase 2: Pipeline - read and split batches, parse on the pool, write in order
         ////////////////////////////////////////////////////////////////////////////////
-        let input_path = input_parquet.to_path_buf();
-        let reader_handle = std::thread::spawn(move || {
-            use std::fs::File;
-            let file = match File::open(&input_path) {
-                Ok(f) => f,
-                Err(e) => {
-                    let _ = work_tx.send(WorkItem {
-                        batch_idx: usize::MAX,  // Error sentinel
-                        rows: vec![],
-                    });
-                    return Err(format!("Failed to open input parquet: {}", e));
-                }
-            };
-
-            let builder = match ParquetRecordBatchReaderBuilder::try_new(file) {
-                Ok(b) => b,
-                Err(e) => {
-                    let _ = work_tx.send(WorkItem {
-                        batch_idx: usize::MAX,
-                        rows: vec![],
-                    });
-                    return Err(format!("Failed to create parquet reader: {}", e));
-                }
-            };
-
-            let mut reader = match builder.with_batch_size(65536).build() {
-                Ok(r) => r,
-                Err(e) => {
-                    let _ = work_tx.send(WorkItem {
-                        batch_idx: usize::MAX,
-                        rows: vec![],
-                    });
-                    return Err(format!("Failed to build parquet reader: {}", e));
-                }
-            };
-
-            let mut batch_idx = 0usize;
-            let mut total_csfs = 0usize;
-
-            loop {
-                match reader.next() {
-                    Some(Ok(batch)) => {
-                        let batch_size = batch.num_rows();
-                        total_csfs += batch_size;
-
-                        let idx_col = match batch.column(0).as_any().downcast_ref::<UInt64Array>() {
-                            Some(col) => col,
-                            None => return Err("idx column is not uint64 type".to_string()),
-                        };
-
-                        let line1_col = match batch.column(1).as_any().downcast_ref::<StringArray>() {
-                            Some(col) => col,
-                            None => return Err("line1 column is not string type".to_string()),
-                        };
-
-                        let line2_col = match batch.column(2).as_any().downcast_ref::<StringArray>() {
-                            Some(col) => col,
-                            None => return Err("line2 column is not string type".to_string()),
-                        };
+        let generator = super::CSFDescriptorGenerator::new(peel_subshells);
+        let mut total_descriptors = 0usize;
+        let mut total_batches_written = 0usize;
+        // The CSF that stopped an `ErrorPolicy::Fail` run
+        let mut failure = None;
+
+        let pipeline_result = run_pipeline(
+            &pool,
+            |send| {
+                let mut total_csfs = 0usize;
+                for batch in reader {
+                    let batch = batch?;
+                    let batch_size = batch.num_rows();
 
-                        let line3_col = match batch.column(3).as_any().downcast_ref::<StringArray>() {
-                            Some(col) => col,
-                            None => return Err("line3 column is not string type".to_string()),
+                    let idx_col = batch
+                        .column_by_name("idx")
+                        .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+                        .ok_or_else(|| CsfError::Schema("idx column is not uint64 type".to_string()))?;
+                    let line1_col = string_column(&batch, "line1")?;
+                    let line2_col = string_column(&batch, "line2")?;
+                    let line3_col = string_column(&batch, "line3")?;
+
+                    for offset in (0..batch_size).step_by(WORK_ITEM_ROWS) {
+                        let len = WORK_ITEM_ROWS.min(batch_size - offset);
+
+                        // Slices share the column buffers; no line is copied
+                        let rows = CsfRows {
+                            idx: idx_col.slice(offset, len),
+                            lines: [
+                                line1_col.slice(offset, len),
+                                line2_col.slice(offset, len),
+                                line3_col.slice(offset, len),
+                            ],
                         };
-
-                        // Extract rows as Arc<str> for zero-copy sharing across threads
-                        let rows: Vec<(u64, Arc<str>, Arc<str>, Arc<str>)> = (0..batch_size)
-                            .map(|i| (
-                                idx_col.value(i),
-                                line1_col.value(i).into(),
-                                line2_col.value(i).into(),
-                                line3_col.value(i).into(),
-                            ))
+                        let passthrough = passthrough_indices
+                            .iter()
+                            .map(|&i| batch.column(i).slice(offset, len))
                             .collect();
-
-                        let work_item = WorkItem { batch_idx, rows };
-                        match work_tx.send(work_item) {
-                            Ok(()) => {},
-                            Err(_) => return Err("Failed to send work item".to_string()),
-                        }
-                        batch_idx += 1;
-
-                        if total_csfs % 10_000_000 == 0 {
-                            println!("[读取进度] {} 个 CSF", total_csfs);
+                        // The writer stopped early and reports its own error
+                        if !send(WorkItem { rows, passthrough }) {
+                            return Ok(total_csfs);
                         }
                     }
-                    Some(Err(e)) => {
-                        return Err(format!("Error reading parquet batch: {}", e));
-                    }
-                    None => break,
-                }
-            }
-
-            println!("[读取完成] {} 个 CSF", total_csfs);
-            Ok((total_csfs, batch_idx))
-        });
-
-        ////////////////////////////////////////////////////////////////////////////////
-        // Phase 4: Multiple Worker threads - compete to process items from channel
-        ////////////////////////////////////////////////////////////////////////////////
-        let generator = Arc::new(super::CSFDescriptorGenerator::new(peel_subshells));
-        let mut worker_handles = Vec::new();
-
-        // Spawn multiple worker threads, all competing on the same channel
-        for _worker_id in 0..num_workers {
-            let generator_clone = generator.clone();
-            let result_tx_clone = result_tx.clone();
-            let work_rx_clone = work_rx.clone();
-
-            worker_handles.push(std::thread::spawn(move || {
-                use rayon::prelude::*;
-
-                let descriptor_size = 3 * generator_clone.orbital_count();
-
-                // Each worker competes to receive work items
-                while let Ok(work_item) = work_rx_clone.recv() {
-                    // Check for error sentinel
-                    if work_item.batch_idx == usize::MAX {
-                        return Err("Reader thread encountered an error".to_string());
-                    }
 
-                    let batch_idx = work_item.batch_idx;
-                    let descriptors: Vec<Vec<i32>> = work_item.rows
-                        .into_par_iter()
-                        .map(|(idx, ref line1, ref line2, ref line3)| {
-                            match generator_clone.parse_csf(line1, line2, line3) {
-                                Ok(desc) => desc,
-                                Err(e) => {
-                                    eprintln!("Warning: Failed to parse CSF at index {}: {}", idx, e);
-                                    vec![0i32; descriptor_size]
-                                }
-                            }
-                        })
-                        .collect();
-
-                    let result_item = ResultItem { batch_idx, descriptors };
-                    if result_tx_clone.send(result_item).is_err() {
-                        return Err("Failed to send result item".to_string());
+                    let previous = total_csfs;
+                    total_csfs += batch_size;
+                    if total_csfs / 10_000_000 > previous / 10_000_000 {
+                        println!("[读取进度] {} 个 CSF", total_csfs);
                     }
                 }
 
-                Ok(())
-            }));
-        }
-
-        // Drop our clone of the result_tx so the writer can properly detect when workers are done
-        drop(result_tx);
-
-        ////////////////////////////////////////////////////////////////////////////////
-        // Phase 5: Writer thread - maintain order and write to parquet (multi-column format)
-        ////////////////////////////////////////////////////////////////////////////////
-        let writer_handle = std::thread::spawn(move || {
-            use arrow::array::Int32Builder;
-
-            let mut pending: BTreeMap<usize, Vec<Vec<i32>>> = BTreeMap::new();
-            let mut next_write_idx = 0usize;
-            let mut total_descriptors = 0usize;
-            let mut total_batches_written = 0usize;
-
-            while let Ok(result_item) = result_rx.recv() {
-                let batch_idx = result_item.batch_idx;
-                let descriptors = result_item.descriptors;
-
-                // Insert into pending map
-                pending.insert(batch_idx, descriptors);
-
-                // Write all consecutive batches we have
-                while let Some(descriptors) = pending.remove(&next_write_idx) {
-                    let batch_size = descriptors.len();
-                    if batch_size == 0 {
-                        next_write_idx += 1;
-                        continue;
-                    }
-                    total_descriptors += batch_size;
-
-                    // Initialize builders for each column (avoids transpose overhead)
-                    let mut builders: Vec<Int32Builder> =
-                        (0..descriptor_size)
-                            .map(|_| Int32Builder::with_capacity(batch_size))
-                            .collect();
-
-                    // Build columns directly (no ListArray overhead)
-                    for desc in &descriptors {
-                        for (col_idx, &val) in desc.iter().enumerate() {
-                            builders[col_idx].append_value(val);
-                        }
-                    }
-
-                    // Convert builders to Arrow arrays
-                    let column_arrays: Vec<Arc<dyn Array>> = builders
-                        .into_iter()
-                        .map(|mut b| Arc::new(b.finish()) as Arc<dyn Array>)
-                        .collect();
-
-                    let output_batch = match RecordBatch::try_new(schema.clone(), column_arrays) {
-                        Ok(b) => b,
-                        Err(e) => return Err(format!("Failed to create output batch: {}", e)),
-                    };
-
-                    if writer.write(&output_batch).is_err() {
-                        return Err("Failed to write batch".to_string());
-                    }
-
-                    total_batches_written += 1;
-                    next_write_idx += 1;
-
-                    if total_batches_written % 100 == 0 {
-                        println!("[写入进度] {} 个描述符", total_descriptors);
-                    }
+                println!("[读取完成] {} 个 CSF", total_csfs);
+                Ok(total_csfs)
+            },
+            |work| parse_work_item(&generator, descriptor_size, work),
+            |ResultItem { descriptors, mut rejects, passthrough }| {
+                // Items arrive in file order, so this is the first failure
+                if error_policy == ErrorPolicy::Fail && !rejects.is_empty() {
+                    let reject = rejects.swap_remove(0);
+                    let error = CsfError::malformed(reject.idx as usize, reject.error.clone());
+                    failure = Some(reject);
+                    return Err(error);
                 }
-            }
-
-            // Finalize writer
-            match writer.close() {
-                Ok(_) => {
-                    println!("[写入完成] {} 个描述符", total_descriptors);
-                    Ok((total_descriptors, total_batches_written))
+                rejects_writer.write(&rejects)?;
+
+                // Build columns directly (no ListArray overhead)
+                let output_batch = build_descriptor_batch(
+                    &schema,
+                    passthrough,
+                    &descriptors,
+                    descriptor_size,
+                    error_policy,
+                )?;
+                total_descriptors += output_batch.num_rows();
+                writer.write(&output_batch)?;
+
+                total_batches_written += 1;
+                if total_batches_written.is_multiple_of(100) {
+                    println!("[写入进度] {} 个描述符", total_descriptors);
                 }
-                Err(e) => Err(format!("Failed to close writer: {}", e)),
-            }
-        });
+                Ok(())
+            },
+        );
 
         ////////////////////////////////////////////////////////////////////////////////
-        // Phase 6: Wait for all threads and collect results
+        // Phase 3: Finish the files once every stage succeeded
         ////////////////////////////////////////////////////////////////////////////////
-        let reader_result = reader_handle.join()
-            .map_err(|e| format!("Reader thread panicked: {:?}", e))?
-            .map_err(|e| format!("Reader thread failed: {}", e))?;
-
-        let (total_csfs, _) = reader_result;
-
-        // Wait for all worker threads
-        for (i, handle) in worker_handles.into_iter().enumerate() {
-            handle.join()
-                .map_err(|e| format!("Worker thread {} panicked: {:?}", i, e))?
-                .map_err(|e| format!("Worker thread {} failed: {}", i, e))?;
+        if let Some(reject) = failure {
+            return Err(rejects_writer.fail(reject));
         }
+        let total_csfs = pipeline_result?;
 
-        let writer_result = writer_handle.join()
-            .map_err(|e| format!("Writer thread panicked: {:?}", e))?
-            .map_err(|e| format!("Writer thread failed: {}", e))?;
-
-        let (total_descriptors, _) = writer_result;
-
-        println!("====================================");
-        println!("处理完成！");
-        println!("输入 CSF: {} | 生成描述符: {}", total_csfs, total_descriptors);
-        println!("轨道数: {} | 描述符大小: {}", orbital_count, descriptor_size);
-        println!("====================================");
+        writer.close()?;
+        println!("[写入完成] {} 个描述符", total_descriptors);
+        let failed_count = rejects_writer.count;
+        let rejects_file = rejects_writer.finish()?;
+        partial.keep();
 
-        Ok(BatchDescriptorStats {
+        let stats = BatchDescriptorStats {
             input_file: input_parquet.to_string_lossy().to_string(),
             output_file: output_file.to_string_lossy().to_string(),
             csf_count: total_csfs,
             descriptor_count: total_descriptors,
             orbital_count,
             descriptor_size,
-        })
+            failed_count,
+            rejects_file: rejects_file.map(|p| p.to_string_lossy().to_string()),
+            num_workers,
+            elapsed_seconds: start.elapsed().as_secs_f64(),
+        };
+
+        println!("====================================");
+        println!("处理完成！");
+        println!("输入 CSF: {} | 生成描述符: {}", stats.csf_count, stats.descriptor_count);
+        println!("轨道数: {} | 描述符大小: {}", orbital_count, descriptor_size);
+        println!(
+            "耗时: {:.2} 秒 | 吞吐量: {:.0} CSF/秒 ({} 个 worker)",
+            stats.elapsed_seconds,
+            stats.csfs_per_second(),
+            num_workers
+        );
+        println!("====================================");
+
+        Ok(stats)
     }
 }
 
@@ -679,38 +846,54 @@ pub mod parquet_batch {
 /// j_to_double_j("4")  => Ok(8)
 /// ```
 pub fn j_to_double_j(j_str: &str) -> Result<i32, String> {
+    parse_double_j(j_str).ok_or_else(|| {
+        let trimmed = j_str.trim();
+        if trimmed.contains('/') {
+            format!("Invalid J value numerator: {}", trimmed)
+        } else {
+            format!("Invalid J value: {}", trimmed)
+        }
+    })
+}
+
+/// Allocation-free core of `j_to_double_j`
+fn parse_double_j(j_str: &str) -> Option<i32> {
     let trimmed = j_str.trim();
 
     // Handle fractional J values (e.g., "3/2" -> 3)
     if let Some(slash_pos) = trimmed.find('/') {
-        let numerator: i32 = trimmed[..slash_pos]
-            .parse()
-            .map_err(|_| format!("Invalid J value numerator: {}", trimmed))?;
-        return Ok(numerator);
+        return trimmed[..slash_pos].parse().ok();
     }
 
     // Handle integer J values (e.g., "2" -> 4, "4-" -> 8)
     // Remove trailing parity indicator if present
     let cleaned = trimmed.trim_end_matches('-').trim_end_matches('+');
-    cleaned
-        .parse::<i32>()
-        .map(|j| j * 2)
-        .map_err(|_| format!("Invalid J value: {}", trimmed))
+    cleaned.parse::<i32>().ok().map(|j| j * 2)
 }
 
-/// Chunk a string into fixed-size pieces
+/// Width of one subshell field in the fixed-width CSF lines
+const FIELD_WIDTH: usize = 9;
+
+/// Pack a subshell label of up to 8 bytes into an integer code
 ///
-/// # Arguments
-/// * `s` - The string to chunk
-/// * `chunk_size` - Size of each chunk
+/// Labels are compared by code so the parser never builds a `String` for the
+/// 5-byte name field. Longer labels have no code; they cannot fit the field.
+fn orbital_code(label: &str) -> Option<u64> {
+    let bytes = label.as_bytes();
+    if bytes.len() > 8 {
+        return None;
+    }
+    Some(bytes.iter().fold(0u64, |code, &b| (code << 8) | b as u64))
+}
+
+/// The `i`-th 9-byte field of a line, clamped to the line length
 ///
-/// # Returns
-/// Vector of string chunks
-fn chunk_string(s: &str, chunk_size: usize) -> Vec<&str> {
-    s.as_bytes()
-        .chunks(chunk_size)
-        .map(|chunk| std::str::from_utf8(chunk).unwrap_or(""))
-        .collect()
+/// Matches splitting the line into 9-byte chunks: fields past the end are
+/// empty, and a field that would split a UTF-8 character is empty as well.
+fn field(line: &str, i: usize) -> &str {
+    let start = (i * FIELD_WIDTH).min(line.len());
+    let end = (start + FIELD_WIDTH).min(line.len());
+    line.get(start..end).unwrap_or("")
 }
 
 /// CSF Descriptor Generator
@@ -719,8 +902,8 @@ fn chunk_string(s: &str, chunk_size: usize) -> Vec<&str> {
 pub struct CSFDescriptorGenerator {
     /// List of peel subshell names (e.g., ["5s", "4d-", "4d", ...])
     peel_subshells: Vec<String>,
-    /// Map from subshell name to index for O(1) lookup
-    orbital_index_map: HashMap<String, usize>,
+    /// `(orbital_code(name), index)` sorted by code for binary search
+    orbital_codes: Vec<(u64, usize)>,
     /// Number of orbitals (cached for performance)
     orbital_count: usize,
 }
@@ -735,19 +918,32 @@ impl CSFDescriptorGenerator {
     /// A new generator instance
     pub fn new(peel_subshells: Vec<String>) -> Self {
         let orbital_count = peel_subshells.len();
-        let orbital_index_map: HashMap<_, _> = peel_subshells
-            .iter()
-            .enumerate()
-            .map(|(i, name)| (name.clone(), i))
-            .collect();
+        // A repeated name maps to its last position
+        let mut codes: HashMap<u64, usize> = HashMap::new();
+        for (i, name) in peel_subshells.iter().enumerate() {
+            if let Some(code) = orbital_code(name) {
+                codes.insert(code, i);
+            }
+        }
+        let mut orbital_codes: Vec<(u64, usize)> = codes.into_iter().collect();
+        orbital_codes.sort_unstable();
 
         Self {
             peel_subshells,
-            orbital_index_map,
+            orbital_codes,
             orbital_count,
         }
     }
 
+    /// Position of a subshell label in the peel list
+    fn orbital_index(&self, label: &str) -> Option<usize> {
+        let code = orbital_code(label)?;
+        self.orbital_codes
+            .binary_search_by_key(&code, |&(c, _)| c)
+            .ok()
+            .map(|i| self.orbital_codes[i].1)
+    }
+
     /// Get the number of orbitals
     pub fn orbital_count(&self) -> usize {
         self.orbital_count
@@ -766,7 +962,9 @@ impl CSFDescriptorGenerator {
     /// * `line3` - Third line: final coupling and total J value
     ///
     /// # Returns
-    /// A vector of i32 descriptor values
+    /// * `Ok(Vec<i32>)` - Descriptor array
+    /// * `Err(String)` - If the final J is unreadable or a subshell is not in
+    ///   the peel list
     ///
     /// # CSF Format Example
     /// ```text
@@ -775,125 +973,338 @@ impl CSFDescriptorGenerator {
     /// line3: "                        4-  "
     /// ```
     pub fn parse_csf(&self, line1: &str, line2: &str, line3: &str) -> Result<Vec<i32>, String> {
-        // Initialize descriptor array with zeros
         let mut descriptor = vec![0i32; 3 * self.orbital_count];
-        let mut occupied_orbitals = Vec::new();
+        self.parse_csf_into(line1, line2, line3, &mut descriptor)?;
+        Ok(descriptor)
+    }
 
-        // Step 1: Preprocess the three lines
-        let subshells_line = line1.trim_end();
-        let line_length = subshells_line.len();
+    /// Parse a single CSF into a caller-provided descriptor buffer
+    ///
+    /// Same result as `parse_csf`, but nothing is allocated unless the CSF is
+    /// malformed: the lines are read in place as 9-byte fields and subshell
+    /// names are matched by their precomputed orbital codes. Batch code reuses
+    /// one buffer per batch.
+    ///
+    /// # Arguments
+    /// * `descriptor` - Output buffer of length `3 * orbital_count`, fully
+    ///   overwritten on success and unspecified on error
+    pub fn parse_csf_into(
+        &self,
+        line1: &str,
+        line2: &str,
+        line3: &str,
+        descriptor: &mut [i32],
+    ) -> Result<(), String> {
+        if descriptor.len() != 3 * self.orbital_count {
+            return Err(format!(
+                "Descriptor buffer has length {}, expected {}",
+                descriptor.len(),
+                3 * self.orbital_count
+            ));
+        }
 
-        // Pad middle line to match line1 length
-        let middle_line = format!("{:<width$}", line2.trim_end(), width = line_length);
+        let subshells_line = line1.trim_end();
+        let middle_line = line2.trim_end();
 
-        // Extract coupling line (remove first 4 and last 5 characters)
+        // Coupling fields sit between the first 4 and the last 5 characters of
+        // line3; the final J is in the last 5 minus the trailing parity sign
         let coupling_line_raw = line3.trim_end();
-        let coupling_trimmed = coupling_line_raw
-            .get(4..coupling_line_raw.len().saturating_sub(5))
+        let len3 = coupling_line_raw.len();
+        let coupling_line = coupling_line_raw
+            .get(4..len3.saturating_sub(5))
             .unwrap_or(coupling_line_raw);
-        let coupling_line = format!("{:<width$}", coupling_trimmed, width = line_length);
-
-        // Step 2: Extract final J value from the end of line3
-        // Extract final J value from the end of line3 (last 5 chars, minus 1 trailing char)
         let final_j_str = coupling_line_raw
-            .get(
-                coupling_line_raw.len().saturating_sub(5)
-                    ..coupling_line_raw.len().saturating_sub(1),
-            )
+            .get(len3.saturating_sub(5)..len3.saturating_sub(1))
             .unwrap_or("");
-        let final_double_j = j_to_double_j(final_j_str)?;
+        let final_double_j = match parse_double_j(final_j_str) {
+            Some(j) => j,
+            None => return Err(j_to_double_j(final_j_str).unwrap_err()),
+        };
 
-        // Step 3: Chunk lines into 9-character blocks
-        let subshell_list = chunk_string(subshells_line, 9);
-        let middle_list = chunk_string(&middle_line, 9);
-        let coupling_list = chunk_string(&coupling_line, 9);
+        // Unoccupied orbitals keep [0, 0, final 2J]; occupied ones are
+        // overwritten below
+        for orbital in descriptor.chunks_exact_mut(3) {
+            orbital.copy_from_slice(&[0, 0, final_double_j]);
+        }
 
-        // Step 4: Process each subshell block
-        for (i, ((subshell_charges, middle_item), coupling_item)) in subshell_list
-            .iter()
-            .zip(middle_list.iter())
-            .zip(coupling_list.iter())
-            .enumerate()
-        {
-            // Extract subshell name (first 5 characters, trimmed)
-            let subshell = subshell_charges
-                .get(0..5)
-                .map(|s: &str| s.trim())
-                .unwrap_or("")
-                .to_string();
-
-            // Extract electron number (characters 6-8, i.e., indices 6 and 7)
-            let subshell_electron_num: i32 = if subshell_charges.len() >= 8 {
-                subshell_charges[6..8].trim().parse().unwrap_or(0)
+        let field_count = subshells_line.len().div_ceil(FIELD_WIDTH);
+        for i in 0..field_count {
+            let subshell_charges = field(subshells_line, i);
+            let middle_item = field(middle_line, i);
+            let coupling_item = field(coupling_line, i);
+
+            // Subshell name (first 5 characters) and electron count (indices 6-7)
+            let subshell = subshell_charges.get(0..5).map(str::trim).unwrap_or("");
+            let subshell_electron_num: i32 = subshell_charges
+                .get(6..8)
+                .and_then(|n| n.trim().parse().ok())
+                .unwrap_or(0);
+
+            // Intermediate J (line 2); for "a;b" the value after the semicolon
+            let middle_present = !middle_item.trim().is_empty();
+            let temp_middle_item = if middle_present {
+                let middle_value = match middle_item.find(';') {
+                    Some(semi_pos) => &middle_item[semi_pos + 1..],
+                    None => middle_item,
+                };
+                parse_double_j(middle_value).unwrap_or(0)
             } else {
                 0
             };
 
-            // Check if this is the last subshell
-            let is_last = i == subshell_list.len() - 1;
-
-            // Process middle J coupling value (line 2)
-            let mut temp_middle_item: i32 = 0;
-            if !middle_item.trim().is_empty() {
-                // If semicolon separated, take the last value
-                let middle_value = if let Some(semi_pos) = middle_item.find(';') {
-                    &middle_item[semi_pos + 1..]
-                } else {
-                    middle_item
-                };
-                temp_middle_item = j_to_double_j(middle_value).unwrap_or(0);
-            }
+            // Coupled J (line 3), falling back to line 2; the last subshell
+            // carries the final J
+            let temp_coupling_item = if i == field_count - 1 {
+                final_double_j
+            } else if !coupling_item.trim().is_empty() {
+                parse_double_j(coupling_item).unwrap_or(0)
+            } else {
+                temp_middle_item
+            };
 
-            // Process coupling J value (line 3)
-            let mut temp_coupling_item: i32 = 0;
-            if !coupling_item.trim().is_empty() {
-                temp_coupling_item = j_to_double_j(coupling_item).unwrap_or(0);
-            } else if !middle_item.trim().is_empty() {
-                // If line 3 is empty but line 2 has a value, use line 2's value
-                temp_coupling_item = temp_middle_item;
-            }
+            // A subshell outside the peel list has no slot; the error policy
+            // decides what to do with the CSF
+            let orbs_idx = self
+                .orbital_index(subshell)
+                .ok_or_else(|| format!("{} is not a peel subshell", subshell))?;
+            let values = &mut descriptor[orbs_idx * 3..orbs_idx * 3 + 3];
+            values.copy_from_slice(&[subshell_electron_num, temp_middle_item, temp_coupling_item]);
+        }
 
-            // Special handling: last subshell uses final J value
-            if is_last {
-                temp_coupling_item = final_double_j;
-            }
+        Ok(())
+    }
 
-            // Step 5: Find orbital index in the peel subshells list
-            if let Some(&orbs_idx) = self.orbital_index_map.get(&subshell) {
-                let descriptor_idx = orbs_idx * 3;
+    /// Parse a single CSF into a descriptor array, rejecting invalid couplings
+    ///
+    /// Unlike `parse_csf`, malformed fields are errors instead of silently
+    /// becoming 0 and the CSF must pass `validation::check_coupling`.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<i32>)` - Descriptor array, identical to `parse_csf` for valid CSFs
+    /// * `Err(String)` - All problems found, separated by "; "
+    pub fn parse_csf_validated(&self, line1: &str, line2: &str, line3: &str) -> Result<Vec<i32>, String> {
+        let csf = Csf::parse(line1, line2, line3)?;
+        let mut errors: Vec<String> = csf
+            .subshells
+            .iter()
+            .filter(|s| self.orbital_index(&s.label).is_none())
+            .map(|s| format!("{} is not a peel subshell", s.label))
+            .collect();
+        errors.extend(check_coupling(&csf));
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+        self.descriptor_from_csf(&csf)
+    }
 
-                // Ensure we don't go out of bounds
-                if descriptor_idx + 3 <= descriptor.len() {
-                    descriptor[descriptor_idx] = subshell_electron_num;
-                    descriptor[descriptor_idx + 1] = temp_middle_item;
-                    descriptor[descriptor_idx + 2] = temp_coupling_item;
+    /// Build the descriptor array of an already parsed CSF
+    ///
+    /// Produces the same layout as `parse_csf` without touching the fixed-width
+    /// text again: for each peel orbital `[electrons, intermediate_2J, coupled_2J]`,
+    /// where a blank coupled J falls back to the intermediate J, the last subshell
+    /// and all unoccupied orbitals carry the final 2J.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<i32>)` - Descriptor array
+    /// * `Err(String)` - If a subshell is not a peel subshell, with the same
+    ///   message as `parse_csf`
+    pub fn descriptor_from_csf(&self, csf: &Csf) -> Result<Vec<i32>, String> {
+        let mut descriptor = vec![0i32; 3 * self.orbital_count];
+        let mut occupied = vec![false; self.orbital_count];
+        let last = csf.subshells.len().saturating_sub(1);
 
-                    occupied_orbitals.push(orbs_idx);
-                }
+        for (i, subshell) in csf.subshells.iter().enumerate() {
+            let orbs_idx = self
+                .orbital_index(&subshell.label)
+                .ok_or_else(|| format!("{} is not a peel subshell", subshell.label))?;
+
+            let middle = subshell.intermediate_double_j.unwrap_or(0);
+            let coupling = if i == last {
+                csf.final_double_j
             } else {
-                // Warning: subshell not in list (skip as Python does)
-                eprintln!("Warning: {} not found in orbs list", subshell);
+                subshell.coupled_double_j.unwrap_or(middle)
+            };
+
+            descriptor[orbs_idx * 3] = subshell.occupation as i32;
+            descriptor[orbs_idx * 3 + 1] = middle;
+            descriptor[orbs_idx * 3 + 2] = coupling;
+            occupied[orbs_idx] = true;
+        }
+
+        for (idx, _) in occupied.iter().enumerate().filter(|(_, o)| !**o) {
+            descriptor[idx * 3 + 2] = csf.final_double_j;
+        }
+
+        Ok(descriptor)
+    }
+
+    /// Rebuild a typed CSF from a descriptor array
+    ///
+    /// This is the inverse of `parse_csf`. Subshells with a non-zero electron
+    /// count are written to line1 in peel order, line2 gets the J of every open
+    /// subshell and line3 the coupled J of every open subshell after the first,
+    /// followed by the final J and the parity derived from the orbital l values.
+    ///
+    /// Descriptors do not record seniority. When a subshell J occurs under
+    /// several seniorities (e.g. f7/2^4 with J = 2 or 4) the lowest one is used.
+    ///
+    /// # Returns
+    /// * `Ok(Csf)` - The decoded CSF
+    /// * `Err(String)` - If the descriptor is not a physically valid coupling
+    pub fn descriptor_to_csf(&self, descriptor: &[i32]) -> Result<Csf, String> {
+        if descriptor.len() != 3 * self.orbital_count {
+            return Err(format!(
+                "Descriptor length {} does not match {} peel subshells (expected {})",
+                descriptor.len(),
+                self.orbital_count,
+                3 * self.orbital_count
+            ));
+        }
+        let orbitals = self
+            .peel_subshells
+            .iter()
+            .map(|label| label.parse::<Orbital>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let occupied: Vec<usize> = (0..self.orbital_count)
+            .filter(|&i| descriptor[3 * i] != 0)
+            .collect();
+        let &last = occupied.last().ok_or("Descriptor has no occupied subshells")?;
+        let final_double_j = descriptor[3 * last + 2];
+        if final_double_j < 0 {
+            return Err(format!("Final 2J must be non-negative, found {}", final_double_j));
+        }
+
+        for i in (0..self.orbital_count).filter(|i| descriptor[3 * i] == 0) {
+            let (middle, coupling) = (descriptor[3 * i + 1], descriptor[3 * i + 2]);
+            if middle != 0 || coupling != final_double_j {
+                return Err(format!(
+                    "Unoccupied subshell {} must be [0, 0, {}], found [0, {}, {}]",
+                    self.peel_subshells[i], final_double_j, middle, coupling
+                ));
             }
         }
 
-        // Step 6: Fill unoccupied orbitals with final J value (position 2)
-        let all_orbitals: std::collections::HashSet<_> = (0..self.orbital_count).collect();
-        let occupied: std::collections::HashSet<_> = occupied_orbitals.iter().cloned().collect();
-        let remaining: Vec<_> = all_orbitals.difference(&occupied).cloned().collect();
+        let mut subshells = Vec::with_capacity(occupied.len());
+        let mut running: Option<i32> = None;
+        let mut odd_electrons = 0u32;
+
+        for (pos, &i) in occupied.iter().enumerate() {
+            let orbital = orbitals[i];
+            let label = &self.peel_subshells[i];
+            let (occupation, middle, coupling) =
+                (descriptor[3 * i], descriptor[3 * i + 1], descriptor[3 * i + 2]);
+
+            if occupation < 0 || occupation as u32 > orbital.max_occupancy() {
+                return Err(format!(
+                    "{} holds at most {} electrons, found {}",
+                    label,
+                    orbital.max_occupancy(),
+                    occupation
+                ));
+            }
+            let occupation = occupation as u32;
+            let multiplicity = subshell_j_multiplicity(orbital.two_j(), occupation, middle);
+            if multiplicity == 0 {
+                return Err(format!(
+                    "J = {} is not allowed for {}({})",
+                    double_j_to_string(middle),
+                    label,
+                    occupation
+                ));
+            }
+            let seniorities = subshell_seniorities(orbital.two_j(), occupation, middle);
+            if (multiplicity as usize) > seniorities.len().max(1) {
+                return Err(format!(
+                    "J = {} of {}({}) is not unique even with seniority",
+                    double_j_to_string(middle),
+                    label,
+                    occupation
+                ));
+            }
+            let seniority = (seniorities.len() > 1).then(|| seniorities[0]);
+
+            let open = occupation < orbital.max_occupancy();
+            let is_last = pos + 1 == occupied.len();
+            let coupled_double_j = if is_last {
+                if !triangle(running.unwrap_or(0), middle, final_double_j) {
+                    return Err(format!(
+                        "Final J = {} cannot be coupled from J = {} and {} J = {}",
+                        double_j_to_string(final_double_j),
+                        double_j_to_string(running.unwrap_or(0)),
+                        label,
+                        double_j_to_string(middle)
+                    ));
+                }
+                None
+            } else if !open {
+                // Closed subshells leave the coupling unchanged and print nothing
+                if coupling != 0 {
+                    return Err(format!(
+                        "Closed subshell {} must have coupled 2J 0, found {}",
+                        label, coupling
+                    ));
+                }
+                None
+            } else if let Some(previous) = running {
+                if !triangle(previous, middle, coupling) {
+                    return Err(format!(
+                        "Coupled J = {} at {} violates the triangle rule with J = {} and {}",
+                        double_j_to_string(coupling),
+                        label,
+                        double_j_to_string(previous),
+                        double_j_to_string(middle)
+                    ));
+                }
+                running = Some(coupling);
+                Some(coupling)
+            } else {
+                // The first open subshell couples to its own J
+                if coupling != middle {
+                    return Err(format!(
+                        "First open subshell {} must have coupled 2J equal to its 2J {}, found {}",
+                        label, middle, coupling
+                    ));
+                }
+                running = Some(middle);
+                None
+            };
 
-        for idx in remaining {
-            let descriptor_idx = idx * 3 + 2;
-            if descriptor_idx < descriptor.len() {
-                descriptor[descriptor_idx] = final_double_j;
+            if orbital.is_odd() {
+                odd_electrons += occupation;
             }
+
+            subshells.push(CsfSubshell {
+                label: label.clone(),
+                occupation,
+                seniority,
+                intermediate_double_j: open.then_some(middle),
+                coupled_double_j,
+            });
         }
 
-        Ok(descriptor)
+        Ok(Csf {
+            subshells,
+            final_double_j,
+            parity: if odd_electrons % 2 == 1 { Parity::Odd } else { Parity::Even },
+        })
+    }
+
+    /// Rebuild the three GRASP lines of a CSF from a descriptor array
+    ///
+    /// # Arguments
+    /// * `descriptor` - Descriptor of length `3 * orbital_count`, as produced by `parse_csf`
+    ///
+    /// # Returns
+    /// * `Ok([line1, line2, line3])` - GRASP fixed-width CSF lines
+    /// * `Err(String)` - If the descriptor is not a physically valid coupling
+    pub fn decode_descriptor(&self, descriptor: &[i32]) -> Result<[String; 3], String> {
+        self.descriptor_to_csf(descriptor).map(|csf| csf.to_lines())
     }
 }
 
 //////////////////////////////////////////////////////////////////////////////
-/// Python Bindings (PyO3)
+// Python Bindings (PyO3)
 //////////////////////////////////////////////////////////////////////////////
 
 #[cfg(feature = "python")]
@@ -933,13 +1344,19 @@ impl PyCSFDescriptorGenerator {
     ///     line1: First line of CSF (subshell configurations)
     ///     line2: Second line of CSF (intermediate J coupling)
     ///     line3: Third line of CSF (final coupling and total J)
+    ///     validate: Reject CSFs that violate the coupling rules instead of
+    ///         mapping unparseable values to 0
     ///
     /// Returns:
     ///     List of int32 descriptor values
-    fn parse_csf(&self, line1: &str, line2: &str, line3: &str) -> PyResult<Vec<i32>> {
-        self.inner
-            .parse_csf(line1, line2, line3)
-            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e))
+    #[pyo3(signature = (line1, line2, line3, validate=false))]
+    fn parse_csf(&self, line1: &str, line2: &str, line3: &str, validate: bool) -> PyResult<Vec<i32>> {
+        let result = if validate {
+            self.inner.parse_csf_validated(line1, line2, line3)
+        } else {
+            self.inner.parse_csf(line1, line2, line3)
+        };
+        result.map_err(|e| crate::error::format_error(e, None, None))
     }
 
     /// Parse CSF from a list of 3 strings (Python list format)
@@ -951,31 +1368,34 @@ impl PyCSFDescriptorGenerator {
         }
         self.inner
             .parse_csf(&csf_lines[0], &csf_lines[1], &csf_lines[2])
-            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e))
+            .map_err(|e| crate::error::format_error(e, None, None))
     }
 
     /// Batch parse multiple CSFs
     ///
     /// Args:
     ///     csf_list: List of CSF data, each being a list of 3 strings
+    ///     validate: Reject CSFs that violate the coupling rules
     ///
     /// Returns:
     ///     List of descriptor arrays
-    fn batch_parse_csfs(&self, csf_list: Vec<Vec<String>>) -> PyResult<Vec<Vec<i32>>> {
+    #[pyo3(signature = (csf_list, validate=false))]
+    fn batch_parse_csfs(&self, csf_list: Vec<Vec<String>>, validate: bool) -> PyResult<Vec<Vec<i32>>> {
         let mut results = Vec::with_capacity(csf_list.len());
 
         for (idx, csf_lines) in csf_list.into_iter().enumerate() {
-            match self.inner.parse_csf(
-                &csf_lines.get(0).map(|s| s.as_str()).unwrap_or(""),
-                &csf_lines.get(1).map(|s| s.as_str()).unwrap_or(""),
-                &csf_lines.get(2).map(|s| s.as_str()).unwrap_or(""),
-            ) {
+            let line1 = csf_lines.first().map(|s| s.as_str()).unwrap_or("");
+            let line2 = csf_lines.get(1).map(|s| s.as_str()).unwrap_or("");
+            let line3 = csf_lines.get(2).map(|s| s.as_str()).unwrap_or("");
+            let result = if validate {
+                self.inner.parse_csf_validated(line1, line2, line3)
+            } else {
+                self.inner.parse_csf(line1, line2, line3)
+            };
+            match result {
                 Ok(descriptor) => results.push(descriptor),
                 Err(e) => {
-                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
-                        "Error parsing CSF at index {}: {}",
-                        idx, e
-                    )));
+                    return Err(crate::error::CsfError::malformed(idx, e).into());
                 }
             }
         }
@@ -983,6 +1403,46 @@ impl PyCSFDescriptorGenerator {
         Ok(results)
     }
 
+    /// Rebuild the three CSF lines from a descriptor array
+    ///
+    /// Args:
+    ///     descriptor: List of int32 descriptor values (length 3 * orbital_count)
+    ///
+    /// Returns:
+    ///     List of 3 strings [line1, line2, line3]
+    ///
+    /// Raises:
+    ///     CSFFormatError: If the descriptor is not a physically valid coupling
+    fn decode_descriptor(&self, descriptor: Vec<i32>) -> PyResult<Vec<String>> {
+        self.inner
+            .decode_descriptor(&descriptor)
+            .map(|lines| lines.to_vec())
+            .map_err(|e| crate::error::format_error(e, None, None))
+    }
+
+    /// Batch decode multiple descriptors
+    ///
+    /// Args:
+    ///     descriptors: List of descriptor arrays
+    ///
+    /// Returns:
+    ///     List of CSFs, each being a list of 3 strings
+    ///
+    /// Raises:
+    ///     CSFFormatError: For the first invalid descriptor, with its `idx`
+    fn batch_decode_descriptors(&self, descriptors: Vec<Vec<i32>>) -> PyResult<Vec<Vec<String>>> {
+        descriptors
+            .iter()
+            .enumerate()
+            .map(|(idx, descriptor)| {
+                self.inner
+                    .decode_descriptor(descriptor)
+                    .map(|lines| lines.to_vec())
+                    .map_err(|e| crate::error::CsfError::malformed(idx, e).into())
+            })
+            .collect()
+    }
+
     /// Get the configuration as a dictionary
     fn get_config(&self, py: Python) -> PyResult<pyo3::Py<pyo3::PyAny>> {
         let dict = pyo3::types::PyDict::new(py);
@@ -998,42 +1458,87 @@ impl PyCSFDescriptorGenerator {
 /// - Each column corresponds to one position in the descriptor array
 /// - Much faster than List column format for large datasets
 /// - Read with: `df = pl.read_parquet(); descriptors = df[["col_0", "col_1", ...]].to_numpy()`
+/// - `idx` and any `passthrough_columns` come first so rows can be joined back to their CSFs
 ///
 /// This version uses streaming batch processing with 65536 rows/batch for low memory usage
 /// and better I/CPU balance on multi-core systems. Multi-column format avoids ListArray overhead.
+///
+/// `compression`, `compression_level`, `row_group_rows`, `dictionary`, `statistics` and
+/// `data_page_size` set the Parquet layout of the descriptor and rejects files.
 #[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (
     input_parquet,
     output_file,
-    peel_subshells,
-    num_workers=None
+    peel_subshells=None,
+    header_path=None,
+    num_workers=None,
+    passthrough_columns=None,
+    error_policy="zeros",
+    compression=None,
+    compression_level=None,
+    row_group_rows=None,
+    dictionary=true,
+    statistics=true,
+    data_page_size=None
 ))]
+#[allow(clippy::too_many_arguments)]
 fn py_generate_descriptors_from_parquet(
     py: Python,
     input_parquet: String,
     output_file: String,
-    peel_subshells: Vec<String>,
+    peel_subshells: Option<Vec<String>>,
+    header_path: Option<String>,
     num_workers: Option<usize>,
+    passthrough_columns: Option<Vec<String>>,
+    error_policy: &str,
+    compression: Option<&str>,
+    compression_level: Option<u32>,
+    row_group_rows: Option<usize>,
+    dictionary: bool,
+    statistics: bool,
+    data_page_size: Option<usize>,
 ) -> PyResult<pyo3::Py<pyo3::PyAny>> {
     use pyo3::types::PyDict;
     use std::path::Path;
 
     let input_path = Path::new(&input_parquet).to_path_buf();
     let output_path = Path::new(&output_file).to_path_buf();
+    let passthrough_columns = passthrough_columns.unwrap_or_default();
+    let error_policy: parquet_batch::ErrorPolicy = error_policy
+        .parse()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let writer_options = crate::writer_options::writer_options_from_kwargs(
+        compression,
+        compression_level,
+        row_group_rows,
+        dictionary,
+        statistics,
+        data_page_size,
+    )?;
 
     // Release the GIL during the long-running operation
     let stats = py
         .detach(|| {
+            let peel_subshells = match peel_subshells {
+                Some(s) => s,
+                None => parquet_batch::read_peel_subshells_from_parquet(
+                    &input_path,
+                    header_path.as_deref().map(Path::new),
+                )?,
+            };
             parquet_batch::generate_descriptors_from_parquet_parallel(
                 &input_path,
                 &output_path,
                 peel_subshells,
                 num_workers,
+                &passthrough_columns,
+                error_policy,
+                &writer_options,
             )
-        })
-        .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))?;
+        })?;
 
+    let csfs_per_second = stats.csfs_per_second();
     let dict = PyDict::new(py);
     dict.set_item("success", true)?;
     dict.set_item("input_file", stats.input_file)?;
@@ -1042,16 +1547,28 @@ fn py_generate_descriptors_from_parquet(
     dict.set_item("descriptor_count", stats.descriptor_count)?;
     dict.set_item("orbital_count", stats.orbital_count)?;
     dict.set_item("descriptor_size", stats.descriptor_size)?;
+    dict.set_item("failed_count", stats.failed_count)?;
+    dict.set_item("num_workers", stats.num_workers)?;
+    dict.set_item("elapsed_seconds", stats.elapsed_seconds)?;
+    dict.set_item("csfs_per_second", csfs_per_second)?;
+    if let Some(rejects_file) = stats.rejects_file {
+        dict.set_item("rejects_file", rejects_file)?;
+    }
     Ok(dict.into())
 }
 
-/// Python-exposed function to read peel subshells from header file
+/// Python-exposed function to read peel subshells from a header TOML file,
+/// or from the metadata of a converted `.parquet` file
 #[cfg(feature = "python")]
 #[pyfunction]
 fn py_read_peel_subshells(header_path: String) -> PyResult<Vec<String>> {
     use std::path::Path;
-    parquet_batch::read_peel_subshells_from_header(Path::new(&header_path))
-        .map_err(|e| pyo3::exceptions::PyIOError::new_err(e))
+    let path = Path::new(&header_path);
+    if path.extension().is_some_and(|e| e == "parquet") {
+        Ok(parquet_batch::read_peel_subshells_from_parquet(path, None)?)
+    } else {
+        Ok(parquet_batch::read_peel_subshells_from_header(path)?)
+    }
 }
 
 /// Register the Python module functions and classes
@@ -1068,7 +1585,7 @@ pub fn register_descriptor_module(module: &Bound<'_, PyModule>) -> PyResult<()>
 }
 
 //////////////////////////////////////////////////////////////////////////////
-/// Rust Tests
+// Rust Tests
 //////////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
@@ -1102,9 +1619,94 @@ mod tests {
     }
 
     #[test]
-    fn test_chunk_string() {
-        let result = chunk_string("abcdefghi", 3);
-        assert_eq!(result, vec!["abc", "def", "ghi"]);
+    fn test_field() {
+        let line = "  5s ( 2)  4d-( 4)  4d";
+        assert_eq!(field(line, 0), "  5s ( 2)");
+        assert_eq!(field(line, 1), "  4d-( 4)");
+        assert_eq!(field(line, 2), "  4d");
+        assert_eq!(field(line, 3), "");
+        assert_eq!(orbital_code("4d-"), orbital_code("4d-"));
+        assert_ne!(orbital_code("4d-"), orbital_code("4d"));
+        assert_eq!(orbital_code("123456789"), None);
+    }
+
+    #[test]
+    fn test_parse_csf_into_reuses_buffer() {
+        let generator = CSFDescriptorGenerator::new(
+            ["3s", "3p-", "3p", "3d-"].iter().map(|s| s.to_string()).collect(),
+        );
+        let first = ["  3s ( 1)  3p-( 1)  3p ( 1)", "      1/2      1/2      3/2", "                    1    3/2+"];
+        let second = ["  3p ( 2)", "        2", "          2+"];
+
+        let mut buffer = vec![0; 12];
+        generator.parse_csf_into(first[0], first[1], first[2], &mut buffer).unwrap();
+        assert_eq!(buffer, generator.parse_csf(first[0], first[1], first[2]).unwrap());
+        // Values of the previous CSF must not leak into the next one
+        generator.parse_csf_into(second[0], second[1], second[2], &mut buffer).unwrap();
+        assert_eq!(buffer, vec![0, 0, 4, 0, 0, 4, 2, 4, 4, 0, 0, 4]);
+
+        assert!(generator.parse_csf_into(first[0], first[1], first[2], &mut [0; 9]).is_err());
+
+        // A subshell outside the peel list is an error, not a skipped field
+        let error = generator.parse_csf("  4s ( 1)", "      1/2", "         1/2+").unwrap_err();
+        assert_eq!(error, "4s is not a peel subshell");
+    }
+
+    #[test]
+    fn test_descriptor_from_csf_matches_parse_csf() {
+        let subshells: Vec<String> = ["5s", "4d-", "4d", "5p-", "5p", "6s", "4f-", "4f", "5d-", "5d"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let generator = CSFDescriptorGenerator::new(subshells);
+        let line1 = "  5s ( 2)  4d-( 4)  4d ( 6)  5p-( 2)  5p ( 4)  6s ( 2)  4f-( 3)  4f ( 4)  5d-( 1)";
+        let line2 = "                                                            9/2        8      3/2";
+        let line3 = "                                                                        7/2      4-";
+
+        let csf = Csf::parse(line1, line2, line3).unwrap();
+        assert_eq!(
+            generator.descriptor_from_csf(&csf).unwrap(),
+            generator.parse_csf(line1, line2, line3).unwrap()
+        );
+
+        // Both reject a subshell outside the peel list with the same error
+        let lines = ["  4s ( 1)", "      1/2", "         1/2+"];
+        let csf = Csf::parse(lines[0], lines[1], lines[2]).unwrap();
+        assert_eq!(
+            generator.descriptor_from_csf(&csf).unwrap_err(),
+            generator.parse_csf(lines[0], lines[1], lines[2]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_decode_descriptor_round_trip() {
+        let generator = CSFDescriptorGenerator::new(
+            ["3s", "3p-", "3p", "3d-"].iter().map(|s| s.to_string()).collect(),
+        );
+        let lines = [
+            "  3s ( 1)  3p-( 1)  3p ( 1)".to_string(),
+            "      1/2      1/2      3/2".to_string(),
+            "                    1    3/2+".to_string(),
+        ];
+        let descriptor = generator.parse_csf(&lines[0], &lines[1], &lines[2]).unwrap();
+        assert_eq!(generator.decode_descriptor(&descriptor).unwrap(), lines);
+    }
+
+    #[test]
+    fn test_decode_descriptor_invalid() {
+        let generator = CSFDescriptorGenerator::new(
+            ["3s", "3p-", "3p"].iter().map(|s| s.to_string()).collect(),
+        );
+        // Wrong length
+        assert!(generator.decode_descriptor(&[1, 1, 1]).is_err());
+        // Nothing occupied
+        assert!(generator.decode_descriptor(&[0, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+        // 3p-(3) exceeds 2j+1 = 2
+        assert!(generator.decode_descriptor(&[0, 0, 3, 3, 3, 3, 0, 0, 3]).is_err());
+        // 3p(2) with J = 1 is Pauli-forbidden
+        assert!(generator.decode_descriptor(&[0, 0, 2, 0, 0, 2, 2, 2, 2]).is_err());
+        // 3s(1) J=1/2 and 3p-(1) J=1/2 cannot couple to J = 2
+        assert!(generator.decode_descriptor(&[1, 1, 1, 1, 1, 4, 0, 0, 4]).is_err());
     }
 
     #[test]
@@ -0,0 +1,217 @@
+//! Crate Error Type
+//!
+//! File-level operations (conversion, export, descriptor generation,
+//! validation, generation and excitation analysis) return [`CsfError`], so
+//! callers can tell a missing file from a malformed CSF or a Parquet table
+//! without the expected columns. Parsers of a single CSF keep returning
+//! `String`; the file-level code wraps their messages with the CSF index and
+//! line number.
+//!
+//! In Python each variant raises a subclass of `rcsfs.CSFError`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Errors returned by file-level operations
+#[derive(Debug)]
+pub enum CsfError {
+    /// Opening, reading or writing a file failed
+    Io {
+        /// File involved, when known
+        path: Option<PathBuf>,
+        source: std::io::Error,
+    },
+    /// The 5-line CSF header or its `_header.toml` file is missing or malformed
+    Header(String),
+    /// A CSF could not be read or parsed
+    MalformedCsf {
+        /// 0-based index of the CSF, when known
+        idx: Option<usize>,
+        /// 1-based line number in the CSF file, when known
+        line: Option<usize>,
+        message: String,
+    },
+    /// A Parquet table lacks a required column or a column has the wrong type
+    Schema(String),
+    /// Reading or writing Parquet failed
+    Parquet(parquet::errors::ParquetError),
+    /// Building or filtering Arrow arrays failed
+    Arrow(arrow::error::ArrowError),
+    /// An argument is out of range or cannot be parsed
+    InvalidInput(String),
+}
+
+/// Result type of file-level operations
+pub type Result<T> = std::result::Result<T, CsfError>;
+
+impl CsfError {
+    /// I/O error on a known file
+    pub fn io(path: &Path, source: std::io::Error) -> Self {
+        CsfError::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        }
+    }
+
+    /// Malformed CSF at index `idx`
+    pub fn malformed(idx: usize, message: impl Into<String>) -> Self {
+        CsfError::MalformedCsf {
+            idx: Some(idx),
+            line: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CsfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsfError::Io { path: Some(path), source } => write!(f, "{}: {}", path.display(), source),
+            CsfError::Io { path: None, source } => write!(f, "I/O error: {}", source),
+            CsfError::Header(message) => write!(f, "Invalid CSF header: {}", message),
+            CsfError::MalformedCsf { idx, line, message } => {
+                write!(f, "Malformed CSF")?;
+                if let Some(idx) = idx {
+                    write!(f, " at index {}", idx)?;
+                }
+                if let Some(line) = line {
+                    write!(f, " (line {})", line)?;
+                }
+                write!(f, ": {}", message)
+            }
+            CsfError::Schema(message) => write!(f, "Schema mismatch: {}", message),
+            CsfError::Parquet(e) => write!(f, "Parquet error: {}", e),
+            CsfError::Arrow(e) => write!(f, "Arrow error: {}", e),
+            CsfError::InvalidInput(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CsfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CsfError::Io { source, .. } => Some(source),
+            CsfError::Parquet(e) => Some(e),
+            CsfError::Arrow(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for CsfError {
+    fn from(source: std::io::Error) -> Self {
+        CsfError::Io { path: None, source }
+    }
+}
+
+impl From<parquet::errors::ParquetError> for CsfError {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        CsfError::Parquet(e)
+    }
+}
+
+impl From<arrow::error::ArrowError> for CsfError {
+    fn from(e: arrow::error::ArrowError) -> Self {
+        CsfError::Arrow(e)
+    }
+}
+
+impl From<toml::de::Error> for CsfError {
+    fn from(e: toml::de::Error) -> Self {
+        CsfError::Header(e.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for CsfError {
+    fn from(e: toml::ser::Error) -> Self {
+        CsfError::Header(e.to_string())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[cfg(feature = "python")]
+pyo3::create_exception!(_rcsfs, CSFError, pyo3::exceptions::PyException, "Base class of all rCSFs errors.");
+#[cfg(feature = "python")]
+pyo3::create_exception!(_rcsfs, CSFIOError, CSFError, "A file could not be opened, read or written.");
+#[cfg(feature = "python")]
+pyo3::create_exception!(_rcsfs, CSFHeaderError, CSFError, "The CSF header or header TOML file is missing or malformed.");
+#[cfg(feature = "python")]
+pyo3::create_exception!(
+    _rcsfs,
+    CSFFormatError,
+    CSFError,
+    "A CSF could not be parsed; `idx` and `line` locate it when known."
+);
+#[cfg(feature = "python")]
+pyo3::create_exception!(_rcsfs, CSFSchemaError, CSFError, "A Parquet table lacks a required column or has the wrong column type.");
+#[cfg(feature = "python")]
+pyo3::create_exception!(_rcsfs, CSFParquetError, CSFError, "Reading or writing Parquet/Arrow data failed.");
+
+#[cfg(feature = "python")]
+impl From<CsfError> for PyErr {
+    fn from(e: CsfError) -> PyErr {
+        let message = e.to_string();
+        match e {
+            CsfError::Io { .. } => CSFIOError::new_err(message),
+            CsfError::Header(_) => CSFHeaderError::new_err(message),
+            CsfError::MalformedCsf { idx, line, .. } => format_error(message, idx, line),
+            CsfError::Schema(_) => CSFSchemaError::new_err(message),
+            CsfError::Parquet(_) | CsfError::Arrow(_) => CSFParquetError::new_err(message),
+            CsfError::InvalidInput(_) => pyo3::exceptions::PyValueError::new_err(message),
+        }
+    }
+}
+
+/// Build a `CSFFormatError` carrying `idx` and `line` attributes
+#[cfg(feature = "python")]
+pub(crate) fn format_error(message: String, idx: Option<usize>, line: Option<usize>) -> PyErr {
+    let err = CSFFormatError::new_err(message);
+    Python::attach(|py| {
+        let value = err.value(py);
+        // Setting attributes on a fresh exception instance cannot fail
+        let _ = value.setattr("idx", idx);
+        let _ = value.setattr("line", line);
+    });
+    err
+}
+
+/// Register the exception classes
+#[cfg(feature = "python")]
+pub fn register_error_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add("CSFError", py.get_type::<CSFError>())?;
+    module.add("CSFIOError", py.get_type::<CSFIOError>())?;
+    module.add("CSFHeaderError", py.get_type::<CSFHeaderError>())?;
+    module.add("CSFFormatError", py.get_type::<CSFFormatError>())?;
+    module.add("CSFSchemaError", py.get_type::<CSFSchemaError>())?;
+    module.add("CSFParquetError", py.get_type::<CSFParquetError>())?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let e = CsfError::MalformedCsf {
+            idx: Some(3),
+            line: Some(15),
+            message: "bad".to_string(),
+        };
+        assert_eq!(e.to_string(), "Malformed CSF at index 3 (line 15): bad");
+
+        let e = CsfError::io(Path::new("x.csf"), std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(e.to_string().starts_with("x.csf: "));
+    }
+}
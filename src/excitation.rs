@@ -0,0 +1,641 @@
+//! Excitation Analysis
+//!
+//! Measures how far each CSF is from a set of reference configurations. The
+//! relativistic subshells of line1 are folded onto their non-relativistic nl
+//! shells and compared with every reference:
+//!
+//! * `excitation_rank` - electrons moved relative to the closest reference
+//! * `reference_index` - index of that reference in the list given
+//! * `excited_from` - shells that lost electrons, e.g. "3s(1)"
+//! * `excited_to` - shells that gained electrons, e.g. "3p(1)"
+//!
+//! The analysis runs over a converted Parquet table or directly over a `.csf`
+//! file, appends these columns and can drop every CSF above a maximum rank.
+
+use crate::csf::parse_configuration_line;
+use crate::csf_generator::{parse_configuration, shell_label, Shell};
+use crate::csf_reader::CsfInput;
+use crate::csfs_conversion::{
+    append_header_metadata, build_csf_batch, csf_table_schema, header_file_path, load_header_data, string_column,
+    write_header_file, ConsistencyCheck, ConversionStats, HeaderData, HeaderInfo, PartialOutput, RawCsf,
+};
+use crate::error::{CsfError, Result};
+use crate::writer_options::WriterOptions;
+use arrow::array::{Array, BooleanBuilder, StringBuilder, UInt32Array, UInt32Builder};
+use arrow::compute::filter_record_batch;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Number of CSFs per record batch when analysing a `.csf` file
+const ANALYSIS_BATCH_SIZE: usize = 65536;
+
+/// Columns appended by the analysis; existing columns with these names are replaced
+const EXCITATION_COLUMNS: [&str; 4] = ["excitation_rank", "reference_index", "excited_from", "excited_to"];
+
+/// Distance of one CSF from its closest reference configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Excitation {
+    /// Number of electrons moved
+    pub rank: u32,
+    /// Index of the closest reference (the first one on ties)
+    pub reference_index: usize,
+    /// Shells that lost electrons and how many, in (n, l) order
+    pub excited_from: Vec<(Shell, u32)>,
+    /// Shells that gained electrons and how many, in (n, l) order
+    pub excited_to: Vec<(Shell, u32)>,
+}
+
+/// Format a shell list as "3s(1) 3p(2)"
+fn format_shells(shells: &[(Shell, u32)]) -> String {
+    shells
+        .iter()
+        .map(|&(shell, q)| format!("{}({})", shell_label(shell), q))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Excitation {
+    /// Shells that lost electrons, formatted as "3s(1) 3p(2)"
+    pub fn excited_from_label(&self) -> String {
+        format_shells(&self.excited_from)
+    }
+
+    /// Shells that gained electrons, formatted as "3s(1) 3p(2)"
+    pub fn excited_to_label(&self) -> String {
+        format_shells(&self.excited_to)
+    }
+}
+
+/// Statistics of an excitation analysis run
+#[derive(Debug, Clone, Default)]
+pub struct ExcitationStats {
+    /// Number of CSFs analysed
+    pub csf_count: usize,
+    /// Number of CSFs written (all of them without a rank limit)
+    pub kept_count: usize,
+    /// Number of CSFs per excitation rank, indexed by rank
+    pub rank_counts: Vec<usize>,
+    /// CSFs whose electron count no reference shares
+    pub unmatched_count: usize,
+    /// CSFs whose line1 could not be parsed
+    pub unparsed_count: usize,
+    /// Header file written next to the output, if the input header was found
+    pub header_file: Option<std::path::PathBuf>,
+}
+
+/// Compares CSF configurations with a fixed set of references
+pub struct ExcitationAnalyzer {
+    references: Vec<BTreeMap<Shell, u32>>,
+}
+
+impl ExcitationAnalyzer {
+    /// Parse the reference configurations, e.g. `["3s(2) 3p(1)"]`
+    ///
+    /// References use non-relativistic shells; CSF subshells such as `3p-` and
+    /// `3p` are folded onto `3p` before comparing.
+    pub fn new(references: &[String]) -> std::result::Result<Self, String> {
+        if references.is_empty() {
+            return Err("At least one reference configuration is required".to_string());
+        }
+        let references = references
+            .iter()
+            .map(|r| parse_configuration(r).map(|shells| shells.into_iter().collect()))
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { references })
+    }
+
+    /// Compute the excitation of a CSF from its line1
+    ///
+    /// # Returns
+    /// * `Ok(Some(Excitation))` - Distance to the closest reference
+    /// * `Ok(None)` - No reference has the same number of electrons
+    /// * `Err(String)` - If line1 cannot be parsed
+    pub fn analyze_line1(&self, line1: &str) -> std::result::Result<Option<Excitation>, String> {
+        let mut configuration: BTreeMap<Shell, u32> = BTreeMap::new();
+        for (orbital, occupation) in parse_configuration_line(line1)? {
+            *configuration.entry(orbital.nl()).or_default() += occupation;
+        }
+        Ok(self.analyze(&configuration))
+    }
+
+    /// Compare a non-relativistic configuration with every reference
+    fn analyze(&self, configuration: &BTreeMap<Shell, u32>) -> Option<Excitation> {
+        let electrons: u32 = configuration.values().sum();
+        let mut best: Option<Excitation> = None;
+
+        for (reference_index, reference) in self.references.iter().enumerate() {
+            if reference.values().sum::<u32>() != electrons {
+                continue;
+            }
+            let mut excited_from = Vec::new();
+            let mut excited_to = Vec::new();
+            let mut shells: Vec<&Shell> = reference.keys().chain(configuration.keys()).collect();
+            shells.sort();
+            shells.dedup();
+            for shell in shells {
+                let before = reference.get(shell).copied().unwrap_or(0);
+                let after = configuration.get(shell).copied().unwrap_or(0);
+                if before > after {
+                    excited_from.push((*shell, before - after));
+                } else if after > before {
+                    excited_to.push((*shell, after - before));
+                }
+            }
+            let rank = excited_from.iter().map(|(_, q)| q).sum();
+            if best.as_ref().is_none_or(|b| rank < b.rank) {
+                best = Some(Excitation {
+                    rank,
+                    reference_index,
+                    excited_from,
+                    excited_to,
+                });
+            }
+        }
+        best
+    }
+
+    /// Append the excitation columns to a batch and drop rows above `max_rank`
+    fn annotate_batch(
+        &self,
+        batch: &RecordBatch,
+        schema: &Arc<Schema>,
+        max_rank: Option<u32>,
+        stats: &mut ExcitationStats,
+    ) -> Result<RecordBatch> {
+        let line1 = string_column(batch, "line1")?;
+        let n = batch.num_rows();
+        let mut rank_builder = UInt32Builder::with_capacity(n);
+        let mut reference_builder = UInt32Builder::with_capacity(n);
+        let mut from_builder = StringBuilder::with_capacity(n, n * 8);
+        let mut to_builder = StringBuilder::with_capacity(n, n * 8);
+        let mut keep = BooleanBuilder::with_capacity(n);
+
+        for i in 0..n {
+            let excitation = match self.analyze_line1(line1.value(i)) {
+                Ok(excitation) => {
+                    stats.unmatched_count += excitation.is_none() as usize;
+                    excitation
+                }
+                Err(e) => {
+                    stats.unparsed_count += 1;
+                    if stats.unparsed_count <= 5 {
+                        println!("警告: 第 {} 个 CSF 无法解析: {}", stats.csf_count + i, e);
+                    }
+                    None
+                }
+            };
+            match &excitation {
+                Some(e) => {
+                    let rank = e.rank as usize;
+                    if stats.rank_counts.len() <= rank {
+                        stats.rank_counts.resize(rank + 1, 0);
+                    }
+                    stats.rank_counts[rank] += 1;
+                    rank_builder.append_value(e.rank);
+                    reference_builder.append_value(e.reference_index as u32);
+                    from_builder.append_value(e.excited_from_label());
+                    to_builder.append_value(e.excited_to_label());
+                }
+                None => {
+                    rank_builder.append_null();
+                    reference_builder.append_null();
+                    from_builder.append_null();
+                    to_builder.append_null();
+                }
+            }
+            keep.append_value(match max_rank {
+                Some(max) => excitation.is_some_and(|e| e.rank <= max),
+                None => true,
+            });
+        }
+        stats.csf_count += n;
+
+        let mut columns: Vec<Arc<dyn Array>> = batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .filter(|(field, _)| !EXCITATION_COLUMNS.contains(&field.name().as_str()))
+            .map(|(_, column)| column.clone())
+            .collect();
+        columns.push(Arc::new(rank_builder.finish()));
+        columns.push(Arc::new(reference_builder.finish()));
+        columns.push(Arc::new(from_builder.finish()));
+        columns.push(Arc::new(to_builder.finish()));
+
+        let annotated = RecordBatch::try_new(schema.clone(), columns)?;
+        let filtered = filter_record_batch(&annotated, &keep.finish())?;
+        stats.kept_count += filtered.num_rows();
+        Ok(filtered)
+    }
+}
+
+/// Input schema with the excitation columns (re)placed at the end
+fn annotated_schema(input: &Schema) -> Arc<Schema> {
+    let mut fields: Vec<Field> = input
+        .fields()
+        .iter()
+        .filter(|f| !EXCITATION_COLUMNS.contains(&f.name().as_str()))
+        .map(|f| f.as_ref().clone())
+        .collect();
+    fields.push(Field::new("excitation_rank", DataType::UInt32, true));
+    fields.push(Field::new("reference_index", DataType::UInt32, true));
+    fields.push(Field::new("excited_from", DataType::Utf8, true));
+    fields.push(Field::new("excited_to", DataType::Utf8, true));
+    Arc::new(Schema::new(fields))
+}
+
+/// Block sizes and separator count of the rows actually written
+#[derive(Default)]
+struct KeptBlocks {
+    block_csf_counts: Vec<usize>,
+    separators: usize,
+    last_block: Option<u32>,
+}
+
+impl KeptBlocks {
+    fn record(&mut self, batch: &RecordBatch) {
+        let blocks = batch
+            .column_by_name("block")
+            .and_then(|c| c.as_any().downcast_ref::<UInt32Array>());
+        for i in 0..batch.num_rows() {
+            let block = blocks.map_or(0, |b| b.value(i));
+            if self.block_csf_counts.len() <= block as usize {
+                self.block_csf_counts.resize(block as usize + 1, 0);
+            }
+            self.block_csf_counts[block as usize] += 1;
+            if self.last_block.is_some_and(|last| last != block) {
+                self.separators += 1;
+            }
+            self.last_block = Some(block);
+        }
+    }
+}
+
+/// Store the header of the rows that were kept in the output's metadata,
+/// close the writer and write `[output_stem]_header.toml`
+fn finish_with_kept_header(
+    mut writer: ArrowWriter<File>,
+    output_path: &Path,
+    header_lines: Vec<String>,
+    mut conversion_stats: ConversionStats,
+    kept: KeptBlocks,
+    kept_count: usize,
+) -> Result<std::path::PathBuf> {
+    conversion_stats.csf_count = kept_count;
+    conversion_stats.total_lines = 3 * kept_count + kept.separators;
+    conversion_stats.block_csf_counts = kept.block_csf_counts;
+    let header_data = HeaderData {
+        header_info: HeaderInfo { header_lines },
+        conversion_stats,
+    };
+    append_header_metadata(&mut writer, &header_data, None)?;
+    writer.close()?;
+    write_header_file(output_path, output_path, &header_data)
+}
+
+/// Print the summary of an analysis run
+fn print_stats(stats: &ExcitationStats) {
+    println!("\n激发分析完成！");
+    println!("CSF 数量: {}", stats.csf_count);
+    for (rank, count) in stats.rank_counts.iter().enumerate() {
+        println!("  激发阶 {}: {}", rank, count);
+    }
+    if stats.unmatched_count > 0 {
+        println!("警告: {} 个 CSF 的电子数与所有参考组态都不同", stats.unmatched_count);
+    }
+    if stats.unparsed_count > 0 {
+        println!("警告: {} 个 CSF 的第一行无法解析", stats.unparsed_count);
+    }
+    println!("保留 CSF 数量: {}", stats.kept_count);
+}
+
+/// Annotate a converted CSF Parquet table with excitation columns
+///
+/// Every input column is kept. When the input's header can be found, in its
+/// metadata or its `_header.toml`, the header of the kept rows is stored in
+/// the output's metadata and `[output_stem]_header.toml`, so the output can be
+/// passed to `parquet_to_csfs` or the descriptor generator directly. Both
+/// files are removed again if the analysis fails.
+///
+/// # Arguments
+/// * `input_parquet` - Parquet file with a `line1` column
+/// * `output_parquet` - Output Parquet file
+/// * `references` - Reference configurations, e.g. `["3s(2) 3p(1)"]`
+/// * `max_rank` - Drop CSFs excited by more electrons (and unmatched CSFs)
+/// * `writer_options` - Codec, row groups and encodings of the output file
+pub fn analyze_excitations_parquet(
+    input_parquet: &Path,
+    output_parquet: &Path,
+    references: &[String],
+    max_rank: Option<u32>,
+    writer_options: &WriterOptions,
+) -> Result<ExcitationStats> {
+    let analyzer = ExcitationAnalyzer::new(references).map_err(CsfError::InvalidInput)?;
+    let props = writer_options.properties(parquet::basic::Compression::UNCOMPRESSED)?;
+    println!("开始激发分析");
+    println!("输入文件: {:?}", input_parquet);
+    println!("输出文件: {:?}", output_parquet);
+
+    let input = File::open(input_parquet).map_err(|e| CsfError::io(input_parquet, e))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(input)?;
+    let schema = annotated_schema(builder.schema());
+    let reader = builder.build()?;
+
+    let mut partial = PartialOutput::default();
+    let output = File::create(output_parquet).map_err(|e| CsfError::io(output_parquet, e))?;
+    partial.track(output_parquet.to_path_buf());
+    partial.track(header_file_path(output_parquet, output_parquet));
+    let mut writer = ArrowWriter::try_new(output, schema.clone(), Some(props))?;
+
+    let mut stats = ExcitationStats::default();
+    let mut kept = KeptBlocks::default();
+    for batch in reader {
+        let filtered = analyzer.annotate_batch(&batch?, &schema, max_rank, &mut stats)?;
+        kept.record(&filtered);
+        writer.write(&filtered)?;
+    }
+
+    match load_header_data(input_parquet, None)? {
+        Some((header_data, _)) => {
+            stats.header_file = Some(finish_with_kept_header(
+                writer,
+                output_parquet,
+                header_data.header_info.header_lines,
+                header_data.conversion_stats,
+                kept,
+                stats.kept_count,
+            )?);
+        }
+        None => {
+            writer.close()?;
+            println!("警告: 未找到输入文件的 header，输出不含 header");
+        }
+    }
+    partial.keep();
+
+    print_stats(&stats);
+    Ok(stats)
+}
+
+/// Analyse a GRASP `.csf` file and write the annotated CSF table to Parquet
+///
+/// The output has the converter's columns (`idx`, `line1`..`line3`, `block`,
+/// ...) followed by the excitation columns, and `[output_stem]_header.toml`
+/// is written next to it. `idx` keeps the position in the input file even
+/// when rows are dropped by `max_rank`. Both files are removed again if the
+/// analysis fails.
+///
+/// # Arguments
+/// * `csfs_path` - Input CSF file
+/// * `output_parquet` - Output Parquet file
+/// * `references` - Reference configurations, e.g. `["3s(2) 3p(1)"]`
+/// * `max_rank` - Drop CSFs excited by more electrons (and unmatched CSFs)
+/// * `writer_options` - Codec, row groups and encodings of the output file
+pub fn analyze_excitations_csf(
+    csfs_path: &Path,
+    output_parquet: &Path,
+    references: &[String],
+    max_rank: Option<u32>,
+    writer_options: &WriterOptions,
+) -> Result<ExcitationStats> {
+    let analyzer = ExcitationAnalyzer::new(references).map_err(CsfError::InvalidInput)?;
+    let props = writer_options.properties(parquet::basic::Compression::UNCOMPRESSED)?;
+    println!("开始激发分析");
+    println!("输入文件: {:?}", csfs_path);
+    println!("输出文件: {:?}", output_parquet);
+
+    let mut input = CsfInput::open(csfs_path)?;
+    let header_lines = input.header_lines()?;
+    let core_electrons = crate::csf_header::CsfHeader::parse(&header_lines)
+        .map(|h| h.closed_core_electrons())
+        .unwrap_or(0);
+
+    let table_schema = csf_table_schema();
+    let schema = annotated_schema(&table_schema);
+    let mut partial = PartialOutput::default();
+    let output = File::create(output_parquet).map_err(|e| CsfError::io(output_parquet, e))?;
+    partial.track(output_parquet.to_path_buf());
+    partial.track(header_file_path(output_parquet, output_parquet));
+    let mut writer = ArrowWriter::try_new(output, schema.clone(), Some(props))?;
+
+    let mut consistency = ConsistencyCheck::new(core_electrons);
+    let mut stats = ExcitationStats::default();
+    let mut kept = KeptBlocks::default();
+    let mut csf_count = 0;
+    let (total_lines, _) = input.scan(|records| loop {
+        let mut batch_records = Vec::with_capacity(ANALYSIS_BATCH_SIZE);
+        for record in records.by_ref().take(ANALYSIS_BATCH_SIZE) {
+            let record = record?;
+            let lines = record.str_lines(csf_count + batch_records.len())?;
+            batch_records.push(RawCsf { block: record.block, lines });
+        }
+        if batch_records.is_empty() {
+            return Ok(());
+        }
+        let batch = build_csf_batch(&table_schema, csf_count, &batch_records, 256, &mut consistency)?;
+        csf_count += batch_records.len();
+
+        let filtered = analyzer.annotate_batch(&batch, &schema, max_rank, &mut stats)?;
+        kept.record(&filtered);
+        writer.write(&filtered)?;
+    })?;
+
+    let conversion_stats = ConversionStats {
+        csf_count,
+        total_lines,
+        truncated_count: 0,
+        block_csf_counts: Vec::new(),
+        electron_count: consistency.electron_count,
+        electron_count_mismatch_count: consistency.electron_count_mismatch_count,
+        parity_mismatch_count: consistency.parity_mismatch_count,
+        unparsed_count: consistency.unparsed_count,
+    };
+    stats.header_file = Some(finish_with_kept_header(
+        writer,
+        output_parquet,
+        header_lines,
+        conversion_stats,
+        kept,
+        stats.kept_count,
+    )?);
+    partial.keep();
+
+    print_stats(&stats);
+    Ok(stats)
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Compute excitation ranks relative to reference configurations
+///
+/// Args:
+///     input_path: Converted Parquet file (.parquet) or GRASP CSF file
+///     output_path: Output Parquet file with the excitation columns appended
+///     references: Reference configurations, e.g. ["3s(2) 3p(1)"]
+///     max_rank: Keep only CSFs excited by at most this many electrons
+///     compression: Parquet codec (default: None, uncompressed)
+///     compression_level: Level for gzip, brotli and zstd (default: codec default)
+///     row_group_rows: Most rows per row group (default: Parquet's 1M rows)
+///     dictionary: Dictionary-encode the columns (default: True)
+///     statistics: Write column statistics (default: True)
+///     data_page_size: Target data page size in bytes (default: Parquet's 1 MiB)
+///
+/// Returns:
+///     Dictionary with csf_count, kept_count, rank_counts, unmatched_count
+///     (electron count matches no reference) and unparsed_count (line1
+///     could not be parsed)
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (
+    input_path,
+    output_path,
+    references,
+    max_rank=None,
+    compression=None,
+    compression_level=None,
+    row_group_rows=None,
+    dictionary=true,
+    statistics=true,
+    data_page_size=None
+))]
+#[allow(clippy::too_many_arguments)]
+fn analyze_excitations(
+    py: Python,
+    input_path: String,
+    output_path: String,
+    references: Vec<String>,
+    max_rank: Option<u32>,
+    compression: Option<&str>,
+    compression_level: Option<u32>,
+    row_group_rows: Option<usize>,
+    dictionary: bool,
+    statistics: bool,
+    data_page_size: Option<usize>,
+) -> PyResult<Py<PyAny>> {
+    use pyo3::exceptions::PyValueError;
+    use pyo3::types::PyDict;
+
+    if references.is_empty() {
+        return Err(PyValueError::new_err("At least one reference configuration is required"));
+    }
+    ExcitationAnalyzer::new(&references).map_err(|e| crate::error::format_error(e, None, None))?;
+    let writer_options = crate::writer_options::writer_options_from_kwargs(
+        compression,
+        compression_level,
+        row_group_rows,
+        dictionary,
+        statistics,
+        data_page_size,
+    )?;
+
+    let input = Path::new(&input_path);
+    let output = Path::new(&output_path);
+    let is_parquet = input
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"));
+    let stats = py
+        .detach(|| {
+            if is_parquet {
+                analyze_excitations_parquet(input, output, &references, max_rank, &writer_options)
+            } else {
+                analyze_excitations_csf(input, output, &references, max_rank, &writer_options)
+            }
+        })?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("success", true)?;
+    dict.set_item("input_file", &input_path)?;
+    dict.set_item("output_file", &output_path)?;
+    dict.set_item("csf_count", stats.csf_count)?;
+    dict.set_item("kept_count", stats.kept_count)?;
+    dict.set_item("rank_counts", stats.rank_counts)?;
+    dict.set_item("unmatched_count", stats.unmatched_count)?;
+    dict.set_item("unparsed_count", stats.unparsed_count)?;
+    if let Some(header_file) = stats.header_file {
+        dict.set_item("header_file", header_file.to_string_lossy())?;
+    }
+    Ok(dict.into())
+}
+
+/// Register the Python functions of this module
+#[cfg(feature = "python")]
+pub fn register_excitation_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(analyze_excitations, module)?)?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyzer(references: &[&str]) -> ExcitationAnalyzer {
+        let references: Vec<String> = references.iter().map(|s| s.to_string()).collect();
+        ExcitationAnalyzer::new(&references).unwrap()
+    }
+
+    #[test]
+    fn test_reference_has_rank_zero() {
+        let a = analyzer(&["3s(2) 3p(1)"]);
+        let e = a.analyze_line1("  3s ( 2)  3p-( 1)").unwrap().unwrap();
+        assert_eq!(e.rank, 0);
+        assert!(e.excited_from.is_empty() && e.excited_to.is_empty());
+    }
+
+    #[test]
+    fn test_double_excitation() {
+        let a = analyzer(&["3s(2) 3p(1)"]);
+        let e = a.analyze_line1("  3p-( 1)  3p ( 1)  3d-( 1)").unwrap().unwrap();
+        assert_eq!(e.rank, 2);
+        assert_eq!(e.excited_from_label(), "3s(2)");
+        assert_eq!(e.excited_to_label(), "3p(1) 3d(1)");
+    }
+
+    #[test]
+    fn test_closest_reference() {
+        let a = analyzer(&["3s(2) 3p(1)", "3s(1) 3p(2)"]);
+        let e = a.analyze_line1("  3s ( 1)  3p-( 1)  3p ( 1)").unwrap().unwrap();
+        assert_eq!((e.rank, e.reference_index), (0, 1));
+        let e = a.analyze_line1("  3p-( 2)  3p ( 1)").unwrap().unwrap();
+        assert_eq!((e.rank, e.reference_index), (1, 1));
+    }
+
+    #[test]
+    fn test_unmatched_electron_count() {
+        let a = analyzer(&["3s(2) 3p(1)"]);
+        assert_eq!(a.analyze_line1("  3s ( 2)").unwrap(), None);
+        assert!(a.analyze_line1("  3s ( 2").is_err());
+    }
+
+    #[test]
+    fn test_annotate_batch_counts_unparsed_apart_from_unmatched() {
+        use arrow::array::StringArray;
+
+        let a = analyzer(&["3s(2) 3p(1)"]);
+        let line1 = StringArray::from(vec!["  3s ( 2)  3p-( 1)", "  3s ( 2)", "  3s ( 2"]);
+        let batch = RecordBatch::try_from_iter([("line1", Arc::new(line1) as Arc<dyn Array>)]).unwrap();
+        let schema = annotated_schema(&batch.schema());
+
+        let mut stats = ExcitationStats::default();
+        let kept = a.annotate_batch(&batch, &schema, Some(1), &mut stats).unwrap();
+        assert_eq!((stats.csf_count, stats.kept_count), (3, 1));
+        assert_eq!((stats.unmatched_count, stats.unparsed_count), (1, 1));
+        assert_eq!(stats.rank_counts, vec![1]);
+        assert_eq!(kept.num_rows(), 1);
+    }
+}
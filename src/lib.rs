@@ -1,57 +1,122 @@
-use pyo3::prelude::*;
-use pyo3::exceptions::{PyValueError, PyIOError};
-use pyo3::types::{PyDict, PyDictMethods};
-use std::path::Path;
+//! rCSFs: conversion of GRASP CSF lists to Parquet, CSF parsing and
+//! descriptor generation.
+//!
+//! The default `python` feature adds the PyO3 bindings of the `_rcsfs`
+//! extension module. Without it (`default-features = false`) the crate is a
+//! plain Rust library whose functions return Rust types and `CsfError`, and
+//! needs no Python toolchain to build.
 
-// Public modules for integration testing
+// Public modules for integration testing and Rust users
+pub mod coupling;
+pub mod csf;
+pub mod csf_generator;
+pub mod csf_header;
+pub mod csf_reader;
 pub mod csfs_conversion;
 pub mod csfs_descriptor;
+pub mod error;
+pub mod excitation;
+pub mod orbital;
+pub mod parquet_info;
+pub mod validation;
+pub mod writer_options;
 
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyFutureWarning, PyKeyError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::types::{PyDict, PyDictMethods};
+#[cfg(feature = "python")]
+use std::path::Path;
+
+#[cfg(feature = "python")]
 #[pymodule]
 fn _rcsfs(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
     m.add_function(wrap_pyfunction!(convert_csfs, m)?)?;
-    m.add_function(wrap_pyfunction!(get_parquet_info, m)?)?;
+    m.add_function(wrap_pyfunction!(parquet_to_csfs, m)?)?;
     m.add_class::<CSFProcessor>()?;
+    m.add_class::<ConversionResult>()?;
+
+    // Register exception hierarchy
+    error::register_error_module(m)?;
+
+    // Register typed CSF model
+    csf::register_csf_module(m)?;
+
+    // Register CSF file header parser
+    csf_header::register_header_module(m)?;
+
+    // Register relativistic subshell type
+    orbital::register_orbital_module(m)?;
 
     // Register CSF descriptor module
     csfs_descriptor::register_descriptor_module(m)?;
 
-    Ok(())
-}
+    // Register coupling validation
+    validation::register_validation_module(m)?;
 
+    // Register native CSF generator
+    csf_generator::register_generator_module(m)?;
 
-/// Get Parquet file basic information and metadata
-///
-/// Args:
-/// - input_path: Path to Parquet file
-///
-/// Returns:
-/// Dictionary containing file information and metadata
-#[pyfunction]
-fn get_parquet_info(
-    py: Python,
-    input_path: String,
-) -> PyResult<pyo3::Py<pyo3::PyAny>> {
-    py.detach(|| {
-        csfs_conversion::get_parquet_metadata(Path::new(&input_path))
-            .map_err(|e| PyIOError::new_err(format!("Failed to get Parquet file info: {}", e)))
-    })
+    // Register excitation analysis
+    excitation::register_excitation_module(m)?;
+
+    // Register Parquet inspector
+    parquet_info::register_parquet_info_module(m)?;
+
+    Ok(())
 }
 
+
 /// CSF file processor class providing an object-oriented interface
+///
+/// Each processor owns its rayon thread pool, which is reused by every
+/// `convert` call that does not ask for a different worker count.
+#[cfg(feature = "python")]
 #[pyclass]
 struct CSFProcessor {
     max_line_len: usize,
     chunk_size: usize,
+    pool: rayon::ThreadPool,
+    writer_options: writer_options::WriterOptions,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl CSFProcessor {
     /// Create a new CSF processor instance
+    ///
+    /// The Parquet writer options apply to every file the processor converts.
     #[new]
-    #[pyo3(signature = (max_line_len=256, chunk_size=3000000))]
-    fn new(max_line_len: Option<usize>, chunk_size: Option<usize>) -> PyResult<Self> {
+    #[pyo3(signature = (
+        max_line_len=256,
+        chunk_size=3000000,
+        num_workers=None,
+        compression=None,
+        compression_level=None,
+        row_group_rows=None,
+        dictionary=true,
+        statistics=true,
+        data_page_size=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        max_line_len: Option<usize>,
+        chunk_size: Option<usize>,
+        num_workers: Option<usize>,
+        compression: Option<&str>,
+        compression_level: Option<u32>,
+        row_group_rows: Option<usize>,
+        dictionary: bool,
+        statistics: bool,
+        data_page_size: Option<usize>,
+    ) -> PyResult<Self> {
         let max_line_len = max_line_len.unwrap_or(256);
         let chunk_size = chunk_size.unwrap_or(3000000);
 
@@ -65,6 +130,15 @@ impl CSFProcessor {
         Ok(CSFProcessor {
             max_line_len,
             chunk_size,
+            pool: csfs_conversion::build_thread_pool(num_workers)?,
+            writer_options: writer_options::writer_options_from_kwargs(
+                compression,
+                compression_level,
+                row_group_rows,
+                dictionary,
+                statistics,
+                data_page_size,
+            )?,
         })
     }
 
@@ -88,35 +162,150 @@ impl CSFProcessor {
         Ok(())
     }
 
+    /// Set the number of worker threads (rebuilds the processor's pool)
+    #[setter]
+    fn set_num_workers(&mut self, value: usize) -> PyResult<()> {
+        self.pool = csfs_conversion::build_thread_pool(Some(value))?;
+        Ok(())
+    }
+
     /// Get current configuration
     fn get_config(&self, py: Python) -> PyResult<pyo3::Py<pyo3::PyAny>> {
         let config = PyDict::new(py);
         config.set_item("max_line_len", self.max_line_len)?;
         config.set_item("chunk_size", self.chunk_size)?;
+        config.set_item("num_workers", self.pool.current_num_threads())?;
+        let options = &self.writer_options;
+        config.set_item("compression", options.codec.map(|c| c.name()))?;
+        config.set_item("compression_level", options.level)?;
+        config.set_item("row_group_rows", options.row_group_rows)?;
+        config.set_item("dictionary", options.dictionary)?;
+        config.set_item("statistics", options.statistics)?;
+        config.set_item("data_page_size", options.data_page_size)?;
         Ok(config.into())
     }
 
     /// Convert CSF file using parallel processing
+    ///
+    /// `num_workers` runs this call on a separate pool of that size; by default
+    /// the processor's own pool is used. `parallel_read` reads byte ranges of
+    /// the file concurrently instead of through one reader thread.
+    #[pyo3(signature = (input_path, output_path, num_workers=None, parallel_read=false, raise_on_error=false))]
     fn convert(
         &self,
         py: Python,
         input_path: String,
         output_path: String,
         num_workers: Option<usize>,
+        parallel_read: bool,
+        raise_on_error: bool,
     ) -> PyResult<pyo3::Py<pyo3::PyAny>> {
-        convert_csfs(
+        let call_pool = match num_workers {
+            Some(n) => Some(csfs_conversion::build_thread_pool(Some(n))?),
+            None => None,
+        };
+        run_conversion(
             py,
             input_path,
             output_path,
-            Some(self.max_line_len),
-            Some(self.chunk_size),
-            num_workers,
+            self.max_line_len,
+            self.chunk_size,
+            call_pool.as_ref().unwrap_or(&self.pool),
+            parallel_read,
+            &self.writer_options,
+            raise_on_error,
         )
     }
 
-    /// Get Parquet file information
-    fn get_metadata(&self, py: Python, input_path: String) -> PyResult<pyo3::Py<pyo3::PyAny>> {
-        get_parquet_info(py, input_path)
+    /// Get Parquet file information (see `get_parquet_info`)
+    fn get_metadata(&self, py: Python, input_path: String) -> PyResult<parquet_info::PyParquetInfo> {
+        parquet_info::get_parquet_info(py, input_path)
+    }
+}
+
+/// Statistics of a successful CSF to Parquet conversion
+///
+/// Attributes mirror the keys of the legacy result dictionary, and
+/// `result["csf_count"]` keeps working for code written against it.
+#[cfg(feature = "python")]
+#[pyclass(frozen, get_all)]
+struct ConversionResult {
+    input_file: String,
+    output_file: String,
+    header_file: Option<String>,
+    max_line_len: usize,
+    chunk_size: usize,
+    num_workers: usize,
+    csf_count: usize,
+    total_lines: usize,
+    truncated_count: usize,
+    block_count: usize,
+    block_csf_counts: Vec<usize>,
+    electron_count: Option<u32>,
+    electron_count_mismatch_count: usize,
+    parity_mismatch_count: usize,
+    unparsed_count: usize,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ConversionResult {
+    /// Always True; failed conversions raise or return an error dictionary
+    #[getter]
+    fn success(&self) -> bool {
+        true
+    }
+
+    /// Statistics as a dictionary (the legacy return value)
+    fn to_dict(&self, py: Python) -> PyResult<pyo3::Py<pyo3::PyAny>> {
+        let stats = PyDict::new(py);
+        stats.set_item("success", true)?;
+        stats.set_item("input_file", &self.input_file)?;
+        stats.set_item("output_file", &self.output_file)?;
+        stats.set_item("max_line_len", self.max_line_len)?;
+        stats.set_item("chunk_size", self.chunk_size)?;
+        stats.set_item("num_workers", self.num_workers)?;
+        stats.set_item("csf_count", self.csf_count)?;
+        stats.set_item("total_lines", self.total_lines)?;
+        stats.set_item("truncated_count", self.truncated_count)?;
+        stats.set_item("block_count", self.block_count)?;
+        stats.set_item("block_csf_counts", &self.block_csf_counts)?;
+        stats.set_item("electron_count", self.electron_count)?;
+        stats.set_item("electron_count_mismatch_count", self.electron_count_mismatch_count)?;
+        stats.set_item("parity_mismatch_count", self.parity_mismatch_count)?;
+        stats.set_item("unparsed_count", self.unparsed_count)?;
+        if let Some(header_file) = &self.header_file {
+            stats.set_item("header_file", header_file)?;
+        }
+        Ok(stats.into())
+    }
+
+    fn __getitem__(&self, py: Python, key: &str) -> PyResult<pyo3::Py<pyo3::PyAny>> {
+        let stats = self.to_dict(py)?;
+        match stats.bind(py).cast::<PyDict>()?.get_item(key)? {
+            Some(value) => Ok(value.unbind()),
+            None => Err(PyKeyError::new_err(key.to_string())),
+        }
+    }
+
+    /// Dictionary-style lookup returning `default` for unknown keys
+    #[pyo3(signature = (key, default=None))]
+    fn get(
+        &self,
+        py: Python,
+        key: &str,
+        default: Option<pyo3::Py<pyo3::PyAny>>,
+    ) -> PyResult<Option<pyo3::Py<pyo3::PyAny>>> {
+        let stats = self.to_dict(py)?;
+        let value = stats.bind(py).cast::<PyDict>()?.get_item(key)?;
+        Ok(value.map(Bound::unbind).or(default))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConversionResult(output_file={:?}, csf_count={}, block_count={}, truncated_count={})",
+            self.output_file, self.csf_count, self.block_count, self.truncated_count
+        )
     }
 }
 
@@ -127,33 +316,60 @@ impl CSFProcessor {
 /// - output_path: Path to output Parquet file
 /// - max_line_len: Maximum line length (default: 256)
 /// - chunk_size: Batch processing size (default: 3000000, optimized for parallel efficiency)
-/// - num_workers: Number of worker threads (default: CPU core count)
+/// - num_workers: Number of worker threads in the pool built for this call
+///   (default: CPU core count)
+/// - parallel_read: Read byte ranges of the file concurrently on the workers
+///   instead of through one reader thread (default: False). Scales on NVMe
+///   drives and parallel filesystems; `chunk_size` is then unused
+/// - raise_on_error: Raise a `CSFError` subclass on failure instead of
+///   returning `{"success": False, "error": ...}` (default: False; this will
+///   become the default in a future release)
+/// - compression: Parquet codec: "uncompressed", "snappy", "gzip", "brotli",
+///   "lz4" or "zstd" (default: None, uncompressed)
+/// - compression_level: Level for gzip, brotli and zstd (default: codec default)
+/// - row_group_rows: Most rows per row group (default: Parquet's 1M rows)
+/// - dictionary: Dictionary-encode the columns (default: True)
+/// - statistics: Write column statistics (default: True)
+/// - data_page_size: Target data page size in bytes (default: Parquet's 1 MiB)
 ///
 /// Returns:
-/// Dictionary containing conversion statistics:
-/// - success: Whether conversion succeeded
+/// `ConversionResult` with the conversion statistics:
 /// - csf_count: Number of CSFs
 /// - total_lines: Total line count
 /// - truncated_count: Number of truncated lines
+/// - block_count: Number of J/parity blocks
+/// - block_csf_counts: Number of CSFs in each block
 /// - input_file: Input file path
 /// - output_file: Output file path
 /// - header_file: TOML header file path
 /// - max_line_len: Maximum line length used
 /// - chunk_size: Batch processing size used
-/// - error: Error message (only present on failure)
+/// - num_workers: Number of worker threads used
+///
+/// On failure the partial Parquet file and header file are removed.
 ///
 /// Features:
 /// - Multi-threaded parallel processing using rayon (automatically uses all CPU cores)
 /// - Maintains original CSF order for consistent output file ordering
 /// - Memory efficient streaming to handle large files
+#[cfg(feature = "python")]
 #[pyfunction]
 #[pyo3(signature = (
     input_path,
     output_path,
     max_line_len=None,
     chunk_size=None,
-    num_workers=None
+    num_workers=None,
+    parallel_read=false,
+    raise_on_error=false,
+    compression=None,
+    compression_level=None,
+    row_group_rows=None,
+    dictionary=true,
+    statistics=true,
+    data_page_size=None
 ))]
+#[allow(clippy::too_many_arguments)]
 fn convert_csfs(
     py: Python,
     input_path: String,
@@ -161,6 +377,14 @@ fn convert_csfs(
     max_line_len: Option<usize>,
     chunk_size: Option<usize>,
     num_workers: Option<usize>,
+    parallel_read: bool,
+    raise_on_error: bool,
+    compression: Option<&str>,
+    compression_level: Option<u32>,
+    row_group_rows: Option<usize>,
+    dictionary: bool,
+    statistics: bool,
+    data_page_size: Option<usize>,
 ) -> PyResult<pyo3::Py<pyo3::PyAny>> {
     // Set default parameters (optimized for parallel processing)
     let max_line_len = max_line_len.unwrap_or(256);
@@ -174,44 +398,101 @@ fn convert_csfs(
         return Err(PyValueError::new_err("chunk_size must be greater than 0"));
     }
 
+    let writer_options = writer_options::writer_options_from_kwargs(
+        compression,
+        compression_level,
+        row_group_rows,
+        dictionary,
+        statistics,
+        data_page_size,
+    )?;
+
+    let pool = csfs_conversion::build_thread_pool(num_workers)?;
+    run_conversion(
+        py,
+        input_path,
+        output_path,
+        max_line_len,
+        chunk_size,
+        &pool,
+        parallel_read,
+        &writer_options,
+        raise_on_error,
+    )
+}
+
+/// Run a conversion on `pool` and build the Python result
+#[cfg(feature = "python")]
+#[allow(clippy::too_many_arguments)]
+fn run_conversion(
+    py: Python,
+    input_path: String,
+    output_path: String,
+    max_line_len: usize,
+    chunk_size: usize,
+    pool: &rayon::ThreadPool,
+    parallel_read: bool,
+    writer_options: &writer_options::WriterOptions,
+    raise_on_error: bool,
+) -> PyResult<pyo3::Py<pyo3::PyAny>> {
     // Execute parallel conversion
     let result = py.detach(|| {
-        csfs_conversion::convert_csfs_to_parquet_parallel(
-            Path::new(&input_path),
-            Path::new(&output_path),
-            max_line_len,
-            chunk_size,
-            num_workers,
-        )
+        let (input, output) = (Path::new(&input_path), Path::new(&output_path));
+        if parallel_read {
+            csfs_conversion::convert_csfs_to_parquet_by_ranges(
+                input,
+                output,
+                max_line_len,
+                csfs_conversion::DEFAULT_RANGE_BYTES,
+                pool,
+                writer_options,
+            )
+        } else {
+            csfs_conversion::convert_csfs_to_parquet_in_pool(
+                input,
+                output,
+                max_line_len,
+                chunk_size,
+                pool,
+                writer_options,
+            )
+        }
     });
 
     match result {
         Ok(conversion_stats) => {
-            // Create result dictionary
-            let stats = PyDict::new(py);
-            stats.set_item("success", true)?;
-            stats.set_item("input_file", &input_path)?;
-            stats.set_item("output_file", &output_path)?;
-            stats.set_item("max_line_len", max_line_len)?;
-            stats.set_item("chunk_size", chunk_size)?;
-            stats.set_item("csf_count", conversion_stats.csf_count)?;
-            stats.set_item("total_lines", conversion_stats.total_lines)?;
-            stats.set_item("truncated_count", conversion_stats.truncated_count)?;
-
-            // Try to read [input_file_stem]_header.toml file path
-            let output_dir = Path::new(&output_path).parent().unwrap_or_else(|| Path::new("."));
-            let input_file_stem = Path::new(&input_path).file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("csfs");
-            let header_filename = format!("{}_header.toml", input_file_stem);
-            let header_path = output_dir.join(header_filename);
-            if header_path.exists() {
-                stats.set_item("header_file", header_path.to_string_lossy())?;
-            }
-
-            Ok(stats.into())
+            let header_path =
+                csfs_conversion::header_file_path(Path::new(&input_path), Path::new(&output_path));
+            let result = ConversionResult {
+                input_file: input_path,
+                output_file: output_path,
+                header_file: header_path
+                    .exists()
+                    .then(|| header_path.to_string_lossy().into_owned()),
+                max_line_len,
+                chunk_size,
+                num_workers: pool.current_num_threads(),
+                csf_count: conversion_stats.csf_count,
+                total_lines: conversion_stats.total_lines,
+                truncated_count: conversion_stats.truncated_count,
+                block_count: conversion_stats.block_csf_counts.len(),
+                block_csf_counts: conversion_stats.block_csf_counts,
+                electron_count: conversion_stats.electron_count,
+                electron_count_mismatch_count: conversion_stats.electron_count_mismatch_count,
+                parity_mismatch_count: conversion_stats.parity_mismatch_count,
+                unparsed_count: conversion_stats.unparsed_count,
+            };
+            Ok(Py::new(py, result)?.into_any())
         }
+        Err(e) if raise_on_error => Err(e.into()),
         Err(e) => {
+            PyErr::warn(
+                py,
+                &py.get_type::<PyFutureWarning>(),
+                c"convert_csfs will raise on failure in a future release; \
+                  pass raise_on_error=True to opt in now",
+                2,
+            )?;
             // Create error result dictionary
             let stats = PyDict::new(py);
             stats.set_item("success", false)?;
@@ -220,3 +501,46 @@ fn convert_csfs(
         }
     }
 }
+
+/// Write a converted Parquet CSF table back to a GRASP CSF text file
+///
+/// Args:
+/// - input_parquet: Path to Parquet file with line1, line2, line3 (and optional block) columns
+/// - output_path: Path to output CSF file
+/// - header_path: Path to header TOML file (default: auto-detected next to the Parquet file)
+///
+/// Returns:
+/// Dictionary containing:
+/// - success: Always True (errors raise CSFIOError, CSFHeaderError or CSFSchemaError)
+/// - input_file: Input Parquet file path
+/// - output_file: Output CSF file path
+/// - header_file: Header TOML file used
+/// - csf_count: Number of CSFs written
+/// - block_count: Number of J/parity blocks written
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (input_parquet, output_path, header_path=None))]
+fn parquet_to_csfs(
+    py: Python,
+    input_parquet: String,
+    output_path: String,
+    header_path: Option<String>,
+) -> PyResult<pyo3::Py<pyo3::PyAny>> {
+    let export_stats = py
+        .detach(|| {
+            csfs_conversion::parquet_to_csfs(
+                Path::new(&input_parquet),
+                Path::new(&output_path),
+                header_path.as_deref().map(Path::new),
+            )
+        })?;
+
+    let stats = PyDict::new(py);
+    stats.set_item("success", true)?;
+    stats.set_item("input_file", &input_parquet)?;
+    stats.set_item("output_file", &output_path)?;
+    stats.set_item("header_file", export_stats.header_file.to_string_lossy())?;
+    stats.set_item("csf_count", export_stats.csf_count)?;
+    stats.set_item("block_count", export_stats.block_count)?;
+    Ok(stats.into())
+}
@@ -0,0 +1,330 @@
+//! Relativistic Subshell Labels
+//!
+//! GRASP labels relativistic subshells as `<n><l>[-]`, e.g. `2p-` for
+//! 2p<sub>1/2</sub> and `2p` for 2p<sub>3/2</sub>. This module parses those
+//! labels into quantum numbers so occupations can be checked against 2j+1,
+//! orbitals can be sorted in GRASP order and relativistic subshells can be
+//! folded onto their non-relativistic nl shell.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Orbital angular momentum symbols in GRASP order (l = 0, 1, 2, ...)
+const L_SYMBOLS: &[char] = &['s', 'p', 'd', 'f', 'g', 'h', 'i', 'k', 'l', 'm', 'n', 'o', 'q', 'r', 't', 'u', 'v'];
+
+/// Convert an orbital angular momentum symbol to l
+pub fn l_from_symbol(symbol: char) -> Option<u32> {
+    L_SYMBOLS
+        .iter()
+        .position(|&c| c == symbol.to_ascii_lowercase())
+        .map(|l| l as u32)
+}
+
+/// Convert l to its orbital angular momentum symbol
+pub fn l_symbol(l: u32) -> Option<char> {
+    L_SYMBOLS.get(l as usize).copied()
+}
+
+/// A relativistic subshell n l j
+///
+/// The Dirac quantum number kappa encodes both l and j:
+/// * `kappa = -(l + 1)` for j = l + 1/2 (label without `-`)
+/// * `kappa = l` for j = l - 1/2 (label with `-`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Orbital {
+    n: u32,
+    kappa: i32,
+}
+
+impl Orbital {
+    /// Create an orbital from its principal quantum number and kappa
+    ///
+    /// # Returns
+    /// * `Ok(Orbital)` - A physically valid subshell
+    /// * `Err(String)` - If kappa is 0 or l >= n
+    pub fn new(n: u32, kappa: i32) -> Result<Self, String> {
+        if kappa == 0 {
+            return Err("kappa must be non-zero".to_string());
+        }
+        let orbital = Orbital { n, kappa };
+        if n == 0 || orbital.l() >= n {
+            return Err(format!("Invalid subshell: l = {} is not allowed for n = {}", orbital.l(), n));
+        }
+        Ok(orbital)
+    }
+
+    /// Create an orbital from n, l and whether j = l - 1/2
+    pub fn from_nlj(n: u32, l: u32, j_minus: bool) -> Result<Self, String> {
+        if j_minus && l == 0 {
+            return Err(format!("Invalid subshell: {}s- does not exist", n));
+        }
+        let kappa = if j_minus { l as i32 } else { -(l as i32 + 1) };
+        Self::new(n, kappa)
+    }
+
+    /// Principal quantum number
+    pub fn n(&self) -> u32 {
+        self.n
+    }
+
+    /// Dirac quantum number kappa
+    pub fn kappa(&self) -> i32 {
+        self.kappa
+    }
+
+    /// Orbital angular momentum l
+    pub fn l(&self) -> u32 {
+        if self.kappa > 0 {
+            self.kappa as u32
+        } else {
+            (-self.kappa - 1) as u32
+        }
+    }
+
+    /// Doubled total angular momentum 2j
+    pub fn two_j(&self) -> u32 {
+        2 * self.kappa.unsigned_abs() - 1
+    }
+
+    /// True for j = l - 1/2 subshells (labels ending in `-`)
+    pub fn is_j_minus(&self) -> bool {
+        self.kappa > 0
+    }
+
+    /// Maximum number of electrons in the subshell (2j + 1)
+    pub fn max_occupancy(&self) -> u32 {
+        self.two_j() + 1
+    }
+
+    /// Parity contribution of one electron in this subshell, (-1)^l
+    pub fn is_odd(&self) -> bool {
+        self.l() % 2 == 1
+    }
+
+    /// The non-relativistic nl shell this subshell belongs to, e.g. `4d` for `4d-`
+    pub fn nl(&self) -> (u32, u32) {
+        (self.n, self.l())
+    }
+
+    /// Label of the non-relativistic nl shell, e.g. "4d" for `4d-`
+    pub fn nl_label(&self) -> String {
+        format!("{}{}", self.n, l_symbol(self.l()).unwrap_or('?'))
+    }
+
+    /// Check that `occupation` electrons fit into the subshell
+    pub fn check_occupation(&self, occupation: u32) -> Result<(), String> {
+        if occupation > self.max_occupancy() {
+            return Err(format!(
+                "{} holds at most {} electrons, found {}",
+                self,
+                self.max_occupancy(),
+                occupation
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Orbital {
+    type Err = String;
+
+    /// Parse a GRASP subshell label such as `5s`, `4d-` or `10f`
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        let label = label.trim();
+        let (body, j_minus) = match label.strip_suffix('-') {
+            Some(body) => (body, true),
+            None => (label, false),
+        };
+
+        let symbol_pos = body
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| format!("Invalid subshell label: {:?}", label))?;
+        let (n_str, rest) = body.split_at(symbol_pos);
+        let mut chars = rest.chars();
+        let symbol = chars.next();
+        if n_str.is_empty() || chars.next().is_some() {
+            return Err(format!("Invalid subshell label: {:?}", label));
+        }
+
+        let n: u32 = n_str
+            .parse()
+            .map_err(|_| format!("Invalid principal quantum number in label: {:?}", label))?;
+        let l = symbol
+            .and_then(l_from_symbol)
+            .ok_or_else(|| format!("Unknown orbital symbol in label: {:?}", label))?;
+
+        Self::from_nlj(n, l, j_minus)
+    }
+}
+
+impl fmt::Display for Orbital {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.nl_label())?;
+        if self.is_j_minus() {
+            write!(f, "-")?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Orbital {
+    /// GRASP order: by n, then l, then j (`2p-` before `2p`)
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.n, self.l(), self.two_j()).cmp(&(other.n, other.l(), other.two_j()))
+    }
+}
+
+impl PartialOrd for Orbital {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Python-exposed relativistic subshell
+#[cfg(feature = "python")]
+#[pyclass(name = "Orbital", eq, ord, hash, frozen)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PyOrbital {
+    inner: Orbital,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyOrbital {
+    /// Parse a GRASP subshell label (e.g. "4d-")
+    #[new]
+    fn new(label: &str) -> PyResult<Self> {
+        label
+            .parse()
+            .map(|inner| Self { inner })
+            .map_err(pyo3::exceptions::PyValueError::new_err)
+    }
+
+    #[getter]
+    fn n(&self) -> u32 {
+        self.inner.n()
+    }
+
+    #[getter]
+    fn l(&self) -> u32 {
+        self.inner.l()
+    }
+
+    #[getter]
+    fn kappa(&self) -> i32 {
+        self.inner.kappa()
+    }
+
+    #[getter]
+    fn two_j(&self) -> u32 {
+        self.inner.two_j()
+    }
+
+    #[getter]
+    fn max_occupancy(&self) -> u32 {
+        self.inner.max_occupancy()
+    }
+
+    /// Label of the non-relativistic nl shell (e.g. "4d")
+    fn nl_label(&self) -> String {
+        self.inner.nl_label()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Orbital('{}')", self.inner)
+    }
+}
+
+/// Sort subshell labels in GRASP order
+#[cfg(feature = "python")]
+#[pyfunction]
+fn sort_orbitals(labels: Vec<String>) -> PyResult<Vec<String>> {
+    let mut orbitals = labels
+        .iter()
+        .map(|l| l.parse::<Orbital>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    orbitals.sort();
+    Ok(orbitals.iter().map(|o| o.to_string()).collect())
+}
+
+/// Register the Python classes and functions of this module
+#[cfg(feature = "python")]
+pub fn register_orbital_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyOrbital>()?;
+    module.add_function(wrap_pyfunction!(sort_orbitals, module)?)?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_labels() {
+        let d_minus: Orbital = "4d-".parse().unwrap();
+        assert_eq!((d_minus.n(), d_minus.l(), d_minus.kappa(), d_minus.two_j()), (4, 2, 2, 3));
+        assert_eq!(d_minus.max_occupancy(), 4);
+
+        let f: Orbital = "5f".parse().unwrap();
+        assert_eq!((f.n(), f.l(), f.kappa(), f.two_j()), (5, 3, -4, 7));
+        assert_eq!(f.max_occupancy(), 8);
+
+        let s: Orbital = "10s".parse().unwrap();
+        assert_eq!((s.n(), s.kappa(), s.two_j()), (10, -1, 1));
+    }
+
+    #[test]
+    fn test_invalid_labels() {
+        assert!("1p".parse::<Orbital>().is_err());
+        assert!("2s-".parse::<Orbital>().is_err());
+        assert!("3x".parse::<Orbital>().is_err());
+        assert!("d".parse::<Orbital>().is_err());
+        assert!("3dd".parse::<Orbital>().is_err());
+        assert!("".parse::<Orbital>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for label in ["1s", "2p-", "2p", "4f-", "10g"] {
+            assert_eq!(label.parse::<Orbital>().unwrap().to_string(), label);
+        }
+    }
+
+    #[test]
+    fn test_grasp_order() {
+        let mut orbitals: Vec<Orbital> = ["3s", "2p", "1s", "2p-", "3d-", "2s"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        orbitals.sort();
+        let labels: Vec<String> = orbitals.iter().map(|o| o.to_string()).collect();
+        assert_eq!(labels, ["1s", "2s", "2p-", "2p", "3s", "3d-"]);
+    }
+
+    #[test]
+    fn test_nl_mapping_and_occupation() {
+        let p_minus: Orbital = "2p-".parse().unwrap();
+        let p: Orbital = "2p".parse().unwrap();
+        assert_eq!(p_minus.nl(), p.nl());
+        assert_eq!(p.nl_label(), "2p");
+        assert!(p_minus.check_occupation(2).is_ok());
+        assert!(p_minus.check_occupation(3).is_err());
+    }
+}
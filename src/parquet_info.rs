@@ -0,0 +1,923 @@
+//! Parquet File Inspector
+//!
+//! Reads the footer of a Parquet file, without touching its data pages, and
+//! describes it: the Arrow schema, every row group with the codec, encodings,
+//! sizes and min/max statistics of its column chunks, and the CSF header and
+//! conversion parameters rCSFs stores in the key-value metadata.
+//!
+//! Files are recognised as a CSF table (`line1`..`line3` text columns, as
+//! written by the converters, the generator and the excitation analysis) or a
+//! descriptor table (`col_0..col_N` Int32 columns).
+
+use crate::csf_header::CsfHeader;
+use crate::csfs_conversion::{
+    parse_header_metadata, ConversionParameters, ConversionStats, METADATA_CONVERSION_KEY, METADATA_HEADER_KEY,
+    METADATA_VERSION_KEY,
+};
+use crate::error::{CsfError, Result};
+use arrow::datatypes::{DataType, Schema};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::{Compression, LogicalType};
+use parquet::file::metadata::ColumnChunkMetaData;
+use parquet::file::statistics::Statistics;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// What a Parquet file holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableKind {
+    /// CSF table with `line1`, `line2` and `line3` text columns
+    Csf,
+    /// Descriptor table with `col_0..col_N` Int32 columns
+    Descriptor,
+    /// Any other table
+    Other,
+}
+
+impl TableKind {
+    /// Recognise a table from its Arrow schema
+    pub fn of_schema(schema: &Schema) -> Self {
+        let is_text = |name: &str| {
+            schema
+                .field_with_name(name)
+                .is_ok_and(|f| matches!(f.data_type(), DataType::Utf8 | DataType::LargeUtf8))
+        };
+        if ["line1", "line2", "line3"].iter().all(|name| is_text(name)) {
+            return TableKind::Csf;
+        }
+        let descriptor_columns: Vec<_> = schema
+            .fields()
+            .iter()
+            .filter(|f| f.name().starts_with("col_"))
+            .collect();
+        let is_descriptor = schema.field_with_name("col_0").is_ok()
+            && descriptor_columns.len() % 3 == 0
+            && descriptor_columns.iter().all(|f| f.data_type() == &DataType::Int32);
+        if is_descriptor {
+            TableKind::Descriptor
+        } else {
+            TableKind::Other
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TableKind::Csf => "csf",
+            TableKind::Descriptor => "descriptor",
+            TableKind::Other => "other",
+        }
+    }
+}
+
+/// Minimum or maximum value from the statistics of a column chunk
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatValue {
+    Bool(bool),
+    Int(i64),
+    /// Integers with an unsigned logical type, e.g. `idx` and `block`
+    UInt(u64),
+    Float(f64),
+    Str(String),
+    /// Byte arrays that are not valid UTF-8
+    Bytes(Vec<u8>),
+}
+
+/// Column of the Arrow schema, with its storage summed over all row groups
+#[derive(Debug, Clone)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// Arrow data type, e.g. `UInt64` or `Utf8`
+    pub data_type: String,
+    pub nullable: bool,
+    /// Codec of the column in the first row group, `None` for a file without row groups
+    pub codec: Option<String>,
+    /// Encodings used in any row group
+    pub encodings: Vec<String>,
+    pub compressed_size: i64,
+    pub uncompressed_size: i64,
+}
+
+/// One column chunk of a row group
+#[derive(Debug, Clone)]
+pub struct ColumnChunkInfo {
+    pub name: String,
+    pub codec: String,
+    pub encodings: Vec<String>,
+    pub compressed_size: i64,
+    pub uncompressed_size: i64,
+    /// `None` when the writer stored no statistics
+    pub null_count: Option<u64>,
+    pub min: Option<StatValue>,
+    pub max: Option<StatValue>,
+}
+
+/// One row group
+#[derive(Debug, Clone)]
+pub struct RowGroupInfo {
+    pub num_rows: i64,
+    /// Uncompressed size of all column chunks in bytes
+    pub total_byte_size: i64,
+    /// Compressed size of all column chunks in bytes
+    pub compressed_size: i64,
+    pub columns: Vec<ColumnChunkInfo>,
+}
+
+/// CSF header and provenance stored in the metadata by rCSFs
+#[derive(Debug, Clone)]
+pub struct CsfMetadata {
+    /// rCSFs version that wrote the file
+    pub rcsfs_version: Option<String>,
+    pub header_lines: Vec<String>,
+    /// Empty when the header does not follow the GRASP layout
+    pub core_subshells: Vec<String>,
+    /// Empty when the header does not follow the GRASP layout
+    pub peel_subshells: Vec<String>,
+    pub conversion_stats: ConversionStats,
+    /// Parameters of the conversion; `None` for generated or filtered tables
+    pub conversion: Option<ConversionParameters>,
+}
+
+/// Description of a Parquet file, read from its footer
+#[derive(Debug, Clone)]
+pub struct ParquetInfo {
+    pub file_path: PathBuf,
+    pub file_size: u64,
+    pub num_rows: i64,
+    /// Library that wrote the file, e.g. `parquet-rs version 57.0.0`
+    pub created_by: Option<String>,
+    pub kind: TableKind,
+    pub schema: Vec<ColumnInfo>,
+    pub row_groups: Vec<RowGroupInfo>,
+    /// Header of a table written by rCSFs, `None` if the metadata has none
+    pub csf_metadata: Option<CsfMetadata>,
+    /// Every key-value metadata entry, including the `rcsfs.*` keys
+    pub key_value_metadata: BTreeMap<String, String>,
+}
+
+impl ParquetInfo {
+    pub fn num_columns(&self) -> usize {
+        self.schema.len()
+    }
+
+    /// Distinct codecs of the column chunks, in order of first use
+    pub fn codecs(&self) -> Vec<String> {
+        let mut codecs: Vec<String> = Vec::new();
+        for chunk in self.row_groups.iter().flat_map(|rg| &rg.columns) {
+            if !codecs.contains(&chunk.codec) {
+                codecs.push(chunk.codec.clone());
+            }
+        }
+        codecs
+    }
+
+    /// Length of each descriptor (number of `col_*` columns) of a descriptor table
+    pub fn descriptor_size(&self) -> Option<usize> {
+        (self.kind == TableKind::Descriptor)
+            .then(|| self.schema.iter().filter(|c| c.name.starts_with("col_")).count())
+    }
+}
+
+/// Codec name as accepted by the `compression` writer option
+fn codec_name(compression: Compression) -> &'static str {
+    match compression {
+        Compression::UNCOMPRESSED => "uncompressed",
+        Compression::SNAPPY => "snappy",
+        Compression::GZIP(_) => "gzip",
+        Compression::LZO => "lzo",
+        Compression::BROTLI(_) => "brotli",
+        Compression::LZ4 => "lz4_hadoop",
+        Compression::ZSTD(_) => "zstd",
+        Compression::LZ4_RAW => "lz4",
+    }
+}
+
+/// Min and max of a column chunk's statistics
+fn min_max(column: &ColumnChunkMetaData) -> (Option<StatValue>, Option<StatValue>) {
+    let unsigned = matches!(
+        column.column_descr().logical_type_ref(),
+        Some(LogicalType::Integer { is_signed: false, .. })
+    );
+    let bytes = |b: &[u8]| match std::str::from_utf8(b) {
+        Ok(s) => StatValue::Str(s.to_string()),
+        Err(_) => StatValue::Bytes(b.to_vec()),
+    };
+    match column.statistics() {
+        Some(Statistics::Boolean(s)) => (s.min_opt().map(|v| StatValue::Bool(*v)), s.max_opt().map(|v| StatValue::Bool(*v))),
+        Some(Statistics::Int32(s)) => {
+            let value = |v: &i32| if unsigned { StatValue::UInt(*v as u32 as u64) } else { StatValue::Int(*v as i64) };
+            (s.min_opt().map(value), s.max_opt().map(value))
+        }
+        Some(Statistics::Int64(s)) => {
+            let value = |v: &i64| if unsigned { StatValue::UInt(*v as u64) } else { StatValue::Int(*v) };
+            (s.min_opt().map(value), s.max_opt().map(value))
+        }
+        Some(Statistics::Float(s)) => (
+            s.min_opt().map(|v| StatValue::Float(*v as f64)),
+            s.max_opt().map(|v| StatValue::Float(*v as f64)),
+        ),
+        Some(Statistics::Double(s)) => (s.min_opt().map(|v| StatValue::Float(*v)), s.max_opt().map(|v| StatValue::Float(*v))),
+        Some(Statistics::ByteArray(s)) => (s.min_opt().map(|v| bytes(v.data())), s.max_opt().map(|v| bytes(v.data()))),
+        Some(Statistics::FixedLenByteArray(s)) => (s.min_opt().map(|v| bytes(v.data())), s.max_opt().map(|v| bytes(v.data()))),
+        // INT96 only holds legacy timestamps
+        Some(Statistics::Int96(_)) | None => (None, None),
+    }
+}
+
+/// Read the CSF header and conversion parameters from the key-value metadata
+fn csf_metadata(parquet_path: &Path, kvs: &BTreeMap<String, String>) -> Result<Option<CsfMetadata>> {
+    let Some(text) = kvs.get(METADATA_HEADER_KEY) else {
+        return Ok(None);
+    };
+    let header_data = parse_header_metadata(text, parquet_path)?;
+    let conversion = kvs
+        .get(METADATA_CONVERSION_KEY)
+        .map(|text| toml::from_str(text))
+        .transpose()
+        .map_err(|e| {
+            CsfError::Header(format!("Failed to parse conversion metadata of {:?}: {}", parquet_path, e))
+        })?;
+    let header_lines = header_data.header_info.header_lines;
+    let (core_subshells, peel_subshells) = match CsfHeader::parse(&header_lines) {
+        Ok(header) => (header.core_labels(), header.peel_labels()),
+        Err(_) => (Vec::new(), Vec::new()),
+    };
+    Ok(Some(CsfMetadata {
+        rcsfs_version: kvs.get(METADATA_VERSION_KEY).cloned(),
+        header_lines,
+        core_subshells,
+        peel_subshells,
+        conversion_stats: header_data.conversion_stats,
+        conversion,
+    }))
+}
+
+/// Describe a Parquet file from its footer, without reading the data.
+///
+/// # Arguments
+///
+/// * `parquet_path` - Path to any Parquet file
+///
+/// # Errors
+///
+/// Returns `CsfError::Io` if the file cannot be opened, `CsfError::Parquet` if
+/// it is not a valid Parquet file and `CsfError::Header` if its `rcsfs.*`
+/// metadata cannot be parsed.
+pub fn inspect_parquet(parquet_path: &Path) -> Result<ParquetInfo> {
+    let file = File::open(parquet_path).map_err(|e| CsfError::io(parquet_path, e))?;
+    let file_size = file.metadata().map_err(|e| CsfError::io(parquet_path, e))?.len();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let arrow_schema = builder.schema().clone();
+    let metadata = builder.metadata();
+    let file_metadata = metadata.file_metadata();
+
+    let key_value_metadata: BTreeMap<String, String> = file_metadata
+        .key_value_metadata()
+        .into_iter()
+        .flatten()
+        .filter_map(|kv| Some((kv.key.clone(), kv.value.clone()?)))
+        .collect();
+
+    let row_groups: Vec<RowGroupInfo> = metadata
+        .row_groups()
+        .iter()
+        .map(|rg| RowGroupInfo {
+            num_rows: rg.num_rows(),
+            total_byte_size: rg.total_byte_size(),
+            compressed_size: rg.compressed_size(),
+            columns: rg
+                .columns()
+                .iter()
+                .map(|column| {
+                    let (min, max) = min_max(column);
+                    ColumnChunkInfo {
+                        name: column.column_path().string(),
+                        codec: codec_name(column.compression()).to_string(),
+                        encodings: column.encodings().map(|e| e.to_string()).collect(),
+                        compressed_size: column.compressed_size(),
+                        uncompressed_size: column.uncompressed_size(),
+                        null_count: column.statistics().and_then(|s| s.null_count_opt()),
+                        min,
+                        max,
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    let schema = arrow_schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let chunks = row_groups
+                .iter()
+                .flat_map(|rg| rg.columns.iter().filter(|c| &c.name == field.name()));
+            let mut column = ColumnInfo {
+                name: field.name().clone(),
+                data_type: field.data_type().to_string(),
+                nullable: field.is_nullable(),
+                codec: None,
+                encodings: Vec::new(),
+                compressed_size: 0,
+                uncompressed_size: 0,
+            };
+            for chunk in chunks {
+                column.codec.get_or_insert_with(|| chunk.codec.clone());
+                for encoding in &chunk.encodings {
+                    if !column.encodings.contains(encoding) {
+                        column.encodings.push(encoding.clone());
+                    }
+                }
+                column.compressed_size += chunk.compressed_size;
+                column.uncompressed_size += chunk.uncompressed_size;
+            }
+            column
+        })
+        .collect();
+
+    Ok(ParquetInfo {
+        file_path: parquet_path.to_path_buf(),
+        file_size,
+        num_rows: file_metadata.num_rows(),
+        created_by: file_metadata.created_by().map(str::to_string),
+        kind: TableKind::of_schema(&arrow_schema),
+        schema,
+        row_groups,
+        csf_metadata: csf_metadata(parquet_path, &key_value_metadata)?,
+        key_value_metadata,
+    })
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::IntoPyObjectExt;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyDict, PyIterator, PyList};
+
+#[cfg(feature = "python")]
+fn stat_value_to_py(py: Python, value: &Option<StatValue>) -> PyResult<Py<PyAny>> {
+    Ok(match value {
+        None => py.None(),
+        Some(StatValue::Bool(v)) => v.into_pyobject(py)?.to_owned().into_any().unbind(),
+        Some(StatValue::Int(v)) => v.into_pyobject(py)?.into_any().unbind(),
+        Some(StatValue::UInt(v)) => v.into_pyobject(py)?.into_any().unbind(),
+        Some(StatValue::Float(v)) => v.into_pyobject(py)?.into_any().unbind(),
+        Some(StatValue::Str(v)) => v.into_pyobject(py)?.into_any().unbind(),
+        Some(StatValue::Bytes(v)) => PyBytes::new(py, v).into_any().unbind(),
+    })
+}
+
+#[cfg(feature = "python")]
+fn csf_metadata_to_dict<'py>(py: Python<'py>, metadata: &CsfMetadata) -> PyResult<Bound<'py, PyDict>> {
+    let stats = &metadata.conversion_stats;
+    let conversion_stats = PyDict::new(py);
+    conversion_stats.set_item("csf_count", stats.csf_count)?;
+    conversion_stats.set_item("total_lines", stats.total_lines)?;
+    conversion_stats.set_item("truncated_count", stats.truncated_count)?;
+    conversion_stats.set_item("block_csf_counts", &stats.block_csf_counts)?;
+    conversion_stats.set_item("electron_count", stats.electron_count)?;
+    conversion_stats.set_item("electron_count_mismatch_count", stats.electron_count_mismatch_count)?;
+    conversion_stats.set_item("parity_mismatch_count", stats.parity_mismatch_count)?;
+    conversion_stats.set_item("unparsed_count", stats.unparsed_count)?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("rcsfs_version", &metadata.rcsfs_version)?;
+    dict.set_item("header_lines", &metadata.header_lines)?;
+    dict.set_item("core_subshells", &metadata.core_subshells)?;
+    dict.set_item("peel_subshells", &metadata.peel_subshells)?;
+    dict.set_item("conversion_stats", conversion_stats)?;
+    match &metadata.conversion {
+        Some(parameters) => {
+            let conversion = PyDict::new(py);
+            conversion.set_item("input_file", &parameters.input_file)?;
+            conversion.set_item("input_compression", &parameters.input_compression)?;
+            conversion.set_item("mode", &parameters.mode)?;
+            conversion.set_item("max_line_len", parameters.max_line_len)?;
+            conversion.set_item("chunk_size", parameters.chunk_size)?;
+            conversion.set_item("range_bytes", parameters.range_bytes)?;
+            conversion.set_item("num_workers", parameters.num_workers)?;
+            conversion.set_item("compression", &parameters.compression)?;
+            conversion.set_item("row_group_rows", parameters.row_group_rows)?;
+            conversion.set_item("dictionary", parameters.dictionary)?;
+            conversion.set_item("statistics", parameters.statistics)?;
+            conversion.set_item("data_page_size", parameters.data_page_size)?;
+            dict.set_item("conversion", conversion)?;
+        }
+        None => dict.set_item("conversion", py.None())?,
+    }
+    Ok(dict)
+}
+
+#[cfg(feature = "python")]
+fn column_info_to_dict<'py>(py: Python<'py>, column: &ColumnInfo) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &column.name)?;
+    dict.set_item("data_type", &column.data_type)?;
+    dict.set_item("nullable", column.nullable)?;
+    dict.set_item("codec", &column.codec)?;
+    dict.set_item("encodings", &column.encodings)?;
+    dict.set_item("compressed_size", column.compressed_size)?;
+    dict.set_item("uncompressed_size", column.uncompressed_size)?;
+    Ok(dict)
+}
+
+#[cfg(feature = "python")]
+fn column_chunk_info_to_dict<'py>(py: Python<'py>, chunk: &ColumnChunkInfo) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &chunk.name)?;
+    dict.set_item("codec", &chunk.codec)?;
+    dict.set_item("encodings", &chunk.encodings)?;
+    dict.set_item("compressed_size", chunk.compressed_size)?;
+    dict.set_item("uncompressed_size", chunk.uncompressed_size)?;
+    dict.set_item("null_count", chunk.null_count)?;
+    dict.set_item("min", stat_value_to_py(py, &chunk.min)?)?;
+    dict.set_item("max", stat_value_to_py(py, &chunk.max)?)?;
+    Ok(dict)
+}
+
+#[cfg(feature = "python")]
+fn row_group_info_to_dict<'py>(py: Python<'py>, row_group: &RowGroupInfo) -> PyResult<Bound<'py, PyDict>> {
+    let columns = PyList::empty(py);
+    for chunk in &row_group.columns {
+        columns.append(column_chunk_info_to_dict(py, chunk)?)?;
+    }
+    let dict = PyDict::new(py);
+    dict.set_item("num_rows", row_group.num_rows)?;
+    dict.set_item("total_byte_size", row_group.total_byte_size)?;
+    dict.set_item("compressed_size", row_group.compressed_size)?;
+    dict.set_item("columns", columns)?;
+    Ok(dict)
+}
+
+/// Keys of the dictionary `get_parquet_info` used to return, in order
+#[cfg(feature = "python")]
+const PARQUET_INFO_KEYS: [&str; 13] = [
+    "file_path",
+    "file_size",
+    "num_rows",
+    "num_columns",
+    "num_row_groups",
+    "compression",
+    "created_by",
+    "table_kind",
+    "descriptor_size",
+    "schema",
+    "row_groups",
+    "csf_metadata",
+    "key_value_metadata",
+];
+
+/// Value of `key` in the legacy dictionary, built without the other entries
+#[cfg(feature = "python")]
+fn parquet_info_item<'py>(py: Python<'py>, info: &ParquetInfo, key: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let value = match key {
+        "file_path" => info.file_path.to_string_lossy().into_bound_py_any(py)?,
+        "file_size" => info.file_size.into_bound_py_any(py)?,
+        "num_rows" => info.num_rows.into_bound_py_any(py)?,
+        "num_columns" => info.num_columns().into_bound_py_any(py)?,
+        "num_row_groups" => info.row_groups.len().into_bound_py_any(py)?,
+        "compression" => codecs_text(info).into_bound_py_any(py)?,
+        "created_by" => info.created_by.as_deref().into_bound_py_any(py)?,
+        "table_kind" => info.kind.name().into_bound_py_any(py)?,
+        "descriptor_size" => info.descriptor_size().into_bound_py_any(py)?,
+        "schema" => {
+            let schema = PyList::empty(py);
+            for column in &info.schema {
+                schema.append(column_info_to_dict(py, column)?)?;
+            }
+            schema.into_any()
+        }
+        "row_groups" => {
+            let row_groups = PyList::empty(py);
+            for row_group in &info.row_groups {
+                row_groups.append(row_group_info_to_dict(py, row_group)?)?;
+            }
+            row_groups.into_any()
+        }
+        "csf_metadata" => match &info.csf_metadata {
+            Some(metadata) => csf_metadata_to_dict(py, metadata)?.into_any(),
+            None => py.None().into_bound(py),
+        },
+        "key_value_metadata" => info.key_value_metadata.clone().into_bound_py_any(py)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+/// Convert a [`ParquetInfo`] into the dictionary `get_parquet_info` used to return
+#[cfg(feature = "python")]
+fn parquet_info_to_dict<'py>(py: Python<'py>, info: &ParquetInfo) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for key in PARQUET_INFO_KEYS {
+        dict.set_item(key, parquet_info_item(py, info, key)?)?;
+    }
+    Ok(dict)
+}
+
+/// Codecs of the column chunks, comma-separated when columns differ
+#[cfg(feature = "python")]
+fn codecs_text(info: &ParquetInfo) -> Option<String> {
+    let codecs = info.codecs();
+    (!codecs.is_empty()).then(|| codecs.join(", "))
+}
+
+/// Value of `key` in `dict`, raising `KeyError` like a dictionary
+#[cfg(feature = "python")]
+fn dict_item(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<Py<PyAny>> {
+    match dict.get_item(key)? {
+        Some(value) => Ok(value.unbind()),
+        None => Err(pyo3::exceptions::PyKeyError::new_err(key.to_string())),
+    }
+}
+
+/// Column of the Arrow schema, with its storage summed over all row groups
+#[cfg(feature = "python")]
+#[pyclass(name = "ColumnInfo", frozen)]
+#[derive(Clone)]
+pub struct PyColumnInfo {
+    inner: ColumnInfo,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyColumnInfo {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Arrow data type, e.g. "UInt64" or "Utf8"
+    #[getter]
+    fn data_type(&self) -> &str {
+        &self.inner.data_type
+    }
+
+    #[getter]
+    fn nullable(&self) -> bool {
+        self.inner.nullable
+    }
+
+    /// Codec of the column in the first row group, None without row groups
+    #[getter]
+    fn codec(&self) -> Option<&str> {
+        self.inner.codec.as_deref()
+    }
+
+    /// Encodings used in any row group
+    #[getter]
+    fn encodings(&self) -> Vec<String> {
+        self.inner.encodings.clone()
+    }
+
+    #[getter]
+    fn compressed_size(&self) -> i64 {
+        self.inner.compressed_size
+    }
+
+    #[getter]
+    fn uncompressed_size(&self) -> i64 {
+        self.inner.uncompressed_size
+    }
+
+    /// The column as a dictionary (the legacy return value)
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        column_info_to_dict(py, &self.inner)
+    }
+
+    fn __getitem__(&self, py: Python, key: &str) -> PyResult<Py<PyAny>> {
+        dict_item(&self.to_dict(py)?, key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ColumnInfo(name={:?}, data_type={:?}, compressed_size={})",
+            self.inner.name, self.inner.data_type, self.inner.compressed_size
+        )
+    }
+}
+
+/// One column chunk of a row group, with its min/max statistics
+#[cfg(feature = "python")]
+#[pyclass(name = "ColumnChunkInfo", frozen)]
+#[derive(Clone)]
+pub struct PyColumnChunkInfo {
+    inner: ColumnChunkInfo,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyColumnChunkInfo {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[getter]
+    fn codec(&self) -> &str {
+        &self.inner.codec
+    }
+
+    #[getter]
+    fn encodings(&self) -> Vec<String> {
+        self.inner.encodings.clone()
+    }
+
+    #[getter]
+    fn compressed_size(&self) -> i64 {
+        self.inner.compressed_size
+    }
+
+    #[getter]
+    fn uncompressed_size(&self) -> i64 {
+        self.inner.uncompressed_size
+    }
+
+    /// None when the writer stored no statistics
+    #[getter]
+    fn null_count(&self) -> Option<u64> {
+        self.inner.null_count
+    }
+
+    #[getter]
+    fn min(&self, py: Python) -> PyResult<Py<PyAny>> {
+        stat_value_to_py(py, &self.inner.min)
+    }
+
+    #[getter]
+    fn max(&self, py: Python) -> PyResult<Py<PyAny>> {
+        stat_value_to_py(py, &self.inner.max)
+    }
+
+    /// The column chunk as a dictionary (the legacy return value)
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        column_chunk_info_to_dict(py, &self.inner)
+    }
+
+    fn __getitem__(&self, py: Python, key: &str) -> PyResult<Py<PyAny>> {
+        dict_item(&self.to_dict(py)?, key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ColumnChunkInfo(name={:?}, codec={:?}, compressed_size={})",
+            self.inner.name, self.inner.codec, self.inner.compressed_size
+        )
+    }
+}
+
+/// One row group of a Parquet file
+#[cfg(feature = "python")]
+#[pyclass(name = "RowGroupInfo", frozen)]
+#[derive(Clone)]
+pub struct PyRowGroupInfo {
+    inner: RowGroupInfo,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyRowGroupInfo {
+    #[getter]
+    fn num_rows(&self) -> i64 {
+        self.inner.num_rows
+    }
+
+    /// Uncompressed size of all column chunks in bytes
+    #[getter]
+    fn total_byte_size(&self) -> i64 {
+        self.inner.total_byte_size
+    }
+
+    /// Compressed size of all column chunks in bytes
+    #[getter]
+    fn compressed_size(&self) -> i64 {
+        self.inner.compressed_size
+    }
+
+    #[getter]
+    fn columns(&self) -> Vec<PyColumnChunkInfo> {
+        self.inner.columns.iter().cloned().map(|inner| PyColumnChunkInfo { inner }).collect()
+    }
+
+    /// The row group as a dictionary (the legacy return value)
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        row_group_info_to_dict(py, &self.inner)
+    }
+
+    fn __getitem__(&self, py: Python, key: &str) -> PyResult<Py<PyAny>> {
+        dict_item(&self.to_dict(py)?, key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RowGroupInfo(num_rows={}, compressed_size={}, columns={})",
+            self.inner.num_rows,
+            self.inner.compressed_size,
+            self.inner.columns.len()
+        )
+    }
+}
+
+/// Description of a Parquet file, read from its footer
+///
+/// Also behaves as a read-only mapping with the keys of the dictionary
+/// `get_parquet_info` used to return: `info["num_rows"]`, `"num_rows" in info`,
+/// `info.keys()` and `dict(info)` keep working. `json.dumps` needs
+/// `info.to_dict()`, which returns that plain dictionary.
+#[cfg(feature = "python")]
+#[pyclass(name = "ParquetInfo", frozen)]
+pub struct PyParquetInfo {
+    inner: ParquetInfo,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PyParquetInfo {
+    #[getter]
+    fn file_path(&self) -> String {
+        self.inner.file_path.to_string_lossy().into_owned()
+    }
+
+    #[getter]
+    fn file_size(&self) -> u64 {
+        self.inner.file_size
+    }
+
+    #[getter]
+    fn num_rows(&self) -> i64 {
+        self.inner.num_rows
+    }
+
+    #[getter]
+    fn num_columns(&self) -> usize {
+        self.inner.num_columns()
+    }
+
+    #[getter]
+    fn num_row_groups(&self) -> usize {
+        self.inner.row_groups.len()
+    }
+
+    /// Codec of the column chunks, e.g. "zstd"; comma-separated when columns differ
+    #[getter]
+    fn compression(&self) -> Option<String> {
+        codecs_text(&self.inner)
+    }
+
+    /// Library that wrote the file
+    #[getter]
+    fn created_by(&self) -> Option<&str> {
+        self.inner.created_by.as_deref()
+    }
+
+    /// "csf", "descriptor" or "other"
+    #[getter]
+    fn table_kind(&self) -> &'static str {
+        self.inner.kind.name()
+    }
+
+    /// Number of `col_*` columns of a descriptor table
+    #[getter]
+    fn descriptor_size(&self) -> Option<usize> {
+        self.inner.descriptor_size()
+    }
+
+    #[getter]
+    fn schema(&self) -> Vec<PyColumnInfo> {
+        self.inner.schema.iter().cloned().map(|inner| PyColumnInfo { inner }).collect()
+    }
+
+    #[getter]
+    fn row_groups(&self) -> Vec<PyRowGroupInfo> {
+        self.inner.row_groups.iter().cloned().map(|inner| PyRowGroupInfo { inner }).collect()
+    }
+
+    /// Header lines, subshells, conversion statistics and parameters stored
+    /// by rCSFs, or None
+    #[getter]
+    fn csf_metadata(&self, py: Python) -> PyResult<Py<PyAny>> {
+        match &self.inner.csf_metadata {
+            Some(metadata) => Ok(csf_metadata_to_dict(py, metadata)?.into_any().unbind()),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Every key-value metadata entry, including the `rcsfs.*` keys
+    #[getter]
+    fn key_value_metadata(&self) -> BTreeMap<String, String> {
+        self.inner.key_value_metadata.clone()
+    }
+
+    /// The description as a dictionary (the legacy return value)
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        parquet_info_to_dict(py, &self.inner)
+    }
+
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        parquet_info_item(py, &self.inner, key)?
+            .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err(key.to_string()))
+    }
+
+    /// Dictionary-style lookup returning `default` for unknown keys
+    #[pyo3(signature = (key, default=None))]
+    fn get(&self, py: Python, key: &str, default: Option<Py<PyAny>>) -> PyResult<Option<Py<PyAny>>> {
+        let value = parquet_info_item(py, &self.inner, key)?;
+        Ok(value.map(Bound::unbind).or(default))
+    }
+
+    fn __contains__(&self, key: &Bound<'_, PyAny>) -> bool {
+        key.extract::<String>().is_ok_and(|key| PARQUET_INFO_KEYS.contains(&key.as_str()))
+    }
+
+    fn __len__(&self) -> usize {
+        PARQUET_INFO_KEYS.len()
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, PARQUET_INFO_KEYS)?.try_iter()
+    }
+
+    /// Keys of the legacy dictionary
+    fn keys(&self) -> Vec<&'static str> {
+        PARQUET_INFO_KEYS.to_vec()
+    }
+
+    /// (key, value) pairs of the legacy dictionary
+    fn items<'py>(&self, py: Python<'py>) -> PyResult<Vec<(&'static str, Bound<'py, PyAny>)>> {
+        PARQUET_INFO_KEYS
+            .into_iter()
+            .map(|key| Ok((key, self.__getitem__(py, key)?)))
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ParquetInfo(file_path={:?}, table_kind={:?}, num_rows={}, num_row_groups={})",
+            self.inner.file_path,
+            self.inner.kind.name(),
+            self.inner.num_rows,
+            self.inner.row_groups.len()
+        )
+    }
+}
+
+/// Describe a Parquet file from its footer, without reading the data
+///
+/// Args:
+///     input_path: Path to a Parquet file
+///
+/// Returns:
+///     `ParquetInfo` with the file size, row and column counts, codecs,
+///     schema (`ColumnInfo`), row groups (`RowGroupInfo` with the codec,
+///     encodings, sizes and min/max of each column chunk), table kind
+///     ("csf", "descriptor" or "other") and the embedded CSF header metadata
+#[cfg(feature = "python")]
+#[pyfunction]
+pub(crate) fn get_parquet_info(py: Python, input_path: String) -> PyResult<PyParquetInfo> {
+    let inner = py.detach(|| inspect_parquet(Path::new(&input_path)))?;
+    Ok(PyParquetInfo { inner })
+}
+
+/// Register the Parquet inspector
+#[cfg(feature = "python")]
+pub fn register_parquet_info_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(get_parquet_info, module)?)?;
+    module.add_class::<PyParquetInfo>()?;
+    module.add_class::<PyRowGroupInfo>()?;
+    module.add_class::<PyColumnChunkInfo>()?;
+    module.add_class::<PyColumnInfo>()?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::Field;
+
+    #[test]
+    fn test_table_kind_of_schema() {
+        let csf = crate::csfs_conversion::csf_table_schema();
+        assert_eq!(TableKind::of_schema(&csf), TableKind::Csf);
+
+        let descriptor = Schema::new(
+            std::iter::once(Field::new("idx", DataType::UInt64, false))
+                .chain((0..6).map(|i| Field::new(format!("col_{}", i), DataType::Int32, true)))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(TableKind::of_schema(&descriptor), TableKind::Descriptor);
+
+        let other = Schema::new(vec![
+            Field::new("line1", DataType::Utf8, false),
+            Field::new("col_0", DataType::Float64, false),
+        ]);
+        assert_eq!(TableKind::of_schema(&other), TableKind::Other);
+    }
+}
@@ -0,0 +1,327 @@
+//! CSF Coupling Validation
+//!
+//! `parse_csf` is deliberately lenient: blank or unparseable J values become 0
+//! and nothing checks whether the numbers describe a physical state. This module
+//! checks each CSF against the jj-coupling rules so corrupt or hand-edited lists
+//! can be caught before they reach GRASP:
+//!
+//! * occupation does not exceed 2j+1
+//! * every subshell J (and seniority, if written) is allowed for its occupancy
+//! * each coupled J on line3 satisfies the triangle rule with the previous
+//!   coupling and the subshell J
+//! * the final J closes the coupling chain
+//! * the declared parity sign matches the occupations
+
+use crate::coupling::{subshell_j_multiplicity, subshell_seniorities, triangle};
+use crate::csf::{double_j_to_string, Csf};
+use crate::csf_reader::CsfInput;
+use crate::error::Result;
+use std::path::Path;
+
+/// A CSF that failed validation, with every rule it violates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsfValidationError {
+    /// 0-based index of the CSF in the list
+    pub index: usize,
+    /// J/parity block the CSF belongs to
+    pub block: u32,
+    /// Human-readable description of each problem
+    pub errors: Vec<String>,
+}
+
+/// Result of validating a whole CSF list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of CSFs checked
+    pub csf_count: usize,
+    /// CSFs with at least one problem, in file order
+    pub invalid: Vec<CsfValidationError>,
+}
+
+impl ValidationReport {
+    /// True when every CSF passed
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty()
+    }
+
+    /// Number of CSFs that passed
+    pub fn valid_count(&self) -> usize {
+        self.csf_count - self.invalid.len()
+    }
+}
+
+/// Check the angular momentum coupling of a parsed CSF
+///
+/// # Returns
+/// Every violated rule; an empty list means the CSF is valid.
+pub fn check_coupling(csf: &Csf) -> Vec<String> {
+    let mut errors = Vec::new();
+    let count = csf.subshells.len();
+    // Coupled J of the subshells seen so far, None until the first open subshell
+    let mut running: Option<i32> = None;
+
+    for (pos, subshell) in csf.subshells.iter().enumerate() {
+        let name = format!("{}({})", subshell.label, subshell.occupation);
+        let middle = subshell.intermediate_double_j;
+        let mut open = middle.is_some();
+
+        match subshell.orbital() {
+            Err(e) => errors.push(e),
+            Ok(orbital) => {
+                if let Err(e) = orbital.check_occupation(subshell.occupation) {
+                    errors.push(e);
+                } else {
+                    open = subshell.occupation < orbital.max_occupancy();
+                    match middle {
+                        None if open => errors.push(format!("{} is open but has no J on line2", name)),
+                        None => {}
+                        Some(j) => {
+                            if subshell_j_multiplicity(orbital.two_j(), subshell.occupation, j) == 0 {
+                                errors.push(format!(
+                                    "J = {} is not allowed for {}",
+                                    double_j_to_string(j),
+                                    name
+                                ));
+                            } else if let Some(v) = subshell.seniority {
+                                let allowed = subshell_seniorities(orbital.two_j(), subshell.occupation, j);
+                                if !allowed.contains(&v) {
+                                    errors.push(format!(
+                                        "Seniority {} is not allowed for {} with J = {}",
+                                        v,
+                                        name,
+                                        double_j_to_string(j)
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let j = middle.unwrap_or(0);
+        if pos + 1 == count {
+            let previous = running.unwrap_or(0);
+            if !triangle(previous, j, csf.final_double_j) {
+                errors.push(format!(
+                    "Final J = {} cannot be coupled from J = {} and {} J = {}",
+                    double_j_to_string(csf.final_double_j),
+                    double_j_to_string(previous),
+                    name,
+                    double_j_to_string(j)
+                ));
+            }
+        } else {
+            match (running, subshell.coupled_double_j) {
+                (previous, Some(coupled)) => {
+                    if !triangle(previous.unwrap_or(0), j, coupled) {
+                        errors.push(format!(
+                            "Coupled J = {} at {} violates the triangle rule with J = {} and {}",
+                            double_j_to_string(coupled),
+                            name,
+                            double_j_to_string(previous.unwrap_or(0)),
+                            double_j_to_string(j)
+                        ));
+                    }
+                    running = Some(coupled);
+                }
+                // The first open subshell couples to its own J
+                (None, None) if open => running = Some(j),
+                (Some(_), None) if open => {
+                    errors.push(format!("{} is open but has no coupled J on line3", name))
+                }
+                // Closed subshells leave the coupling unchanged
+                _ => {}
+            }
+        }
+    }
+
+    errors
+}
+
+/// Parse and check a single CSF given as its three lines
+///
+/// Runs `check_coupling` and `Csf::check_parity`.
+///
+/// # Returns
+/// Every problem found; an empty list means the CSF is valid. A CSF that
+/// cannot be parsed yields a single entry describing the format error.
+pub fn validate_csf_lines(line1: &str, line2: &str, line3: &str) -> Vec<String> {
+    match Csf::parse(line1, line2, line3) {
+        Ok(csf) => {
+            let mut errors = check_coupling(&csf);
+            errors.extend(csf.check_parity().err());
+            errors
+        }
+        Err(e) => vec![e],
+    }
+}
+
+/// Validate every CSF in a GRASP `.csf` file
+///
+/// # Arguments
+/// * `csfs_path` - Path to the CSF file (5 header lines followed by CSF triples)
+///
+/// # Returns
+/// * `Ok(ValidationReport)` - Per-CSF verdicts; the file itself was readable
+/// * `Err(CsfError)` - If the file cannot be read or its block structure is broken
+pub fn validate_csfs_file(csfs_path: &Path) -> Result<ValidationReport> {
+    let mut input = CsfInput::open(csfs_path)?;
+
+    let mut report = ValidationReport::default();
+    input.scan(|records| {
+        for record in records {
+            let record = record?;
+            let [line1, line2, line3] = record.str_lines(report.csf_count)?;
+            let errors = validate_csf_lines(line1, line2, line3);
+            if !errors.is_empty() {
+                report.invalid.push(CsfValidationError {
+                    index: report.csf_count,
+                    block: record.block,
+                    errors,
+                });
+            }
+            report.csf_count += 1;
+        }
+        Ok(())
+    })?;
+
+    Ok(report)
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Check a single CSF against the coupling rules
+///
+/// Args:
+///     line1: First line of CSF (subshell configurations)
+///     line2: Second line of CSF (intermediate J coupling)
+///     line3: Third line of CSF (final coupling and total J)
+///
+/// Returns:
+///     List of problems, empty when the CSF is valid
+#[cfg(feature = "python")]
+#[pyfunction]
+fn validate_csf(line1: &str, line2: &str, line3: &str) -> Vec<String> {
+    validate_csf_lines(line1, line2, line3)
+}
+
+/// Validate every CSF in a GRASP .csf file
+///
+/// Args:
+///     input_path: Path to the CSF file
+///
+/// Returns:
+///     Dictionary with csf_count, valid_count, invalid_count, is_valid and
+///     invalid (list of dicts with index, block and errors)
+#[cfg(feature = "python")]
+#[pyfunction]
+fn validate_csfs(py: Python, input_path: String) -> PyResult<Py<PyAny>> {
+    use pyo3::types::{PyDict, PyList};
+
+    let report = validate_csfs_file(Path::new(&input_path))?;
+
+    let invalid = PyList::empty(py);
+    for entry in &report.invalid {
+        let item = PyDict::new(py);
+        item.set_item("index", entry.index)?;
+        item.set_item("block", entry.block)?;
+        item.set_item("errors", &entry.errors)?;
+        invalid.append(item)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("input_file", &input_path)?;
+    dict.set_item("csf_count", report.csf_count)?;
+    dict.set_item("valid_count", report.valid_count())?;
+    dict.set_item("invalid_count", report.invalid.len())?;
+    dict.set_item("is_valid", report.is_valid())?;
+    dict.set_item("invalid", invalid)?;
+    Ok(dict.into())
+}
+
+/// Register the Python functions of this module
+#[cfg(feature = "python")]
+pub fn register_validation_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(validate_csf, module)?)?;
+    module.add_function(wrap_pyfunction!(validate_csfs, module)?)?;
+    Ok(())
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_csf() {
+        let errors = validate_csf_lines(
+            "  3s ( 1)  3p-( 1)  3p ( 1)",
+            "      1/2      1/2      3/2",
+            "                    1    3/2+",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_closed_and_single_open_subshell() {
+        let errors = validate_csf_lines("  5s ( 2)  4d-( 4)  4d ( 5)", "                       5/2", "     5/2+");
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_over_occupied_subshell() {
+        let errors = validate_csf_lines("  3p-( 3)", "      3/2", "   3/2-");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].contains("at most 2"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_forbidden_subshell_j() {
+        // p3/2^2 only has J = 0 and 2
+        let errors = validate_csf_lines("  3p ( 2)", "        1", "     1+");
+        assert!(errors.iter().any(|e| e.contains("not allowed")), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_triangle_violation() {
+        let errors = validate_csf_lines(
+            "  3s ( 1)  3p-( 1)  3p ( 1)",
+            "      1/2      1/2      3/2",
+            "                    2    3/2+",
+        );
+        assert!(errors.iter().any(|e| e.contains("triangle")), "{:?}", errors);
+
+        let errors = validate_csf_lines("  3s ( 1)  3p-( 1)", "      1/2      1/2", "              2-");
+        assert!(errors.iter().any(|e| e.contains("Final J")), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_seniority_check() {
+        let ok = validate_csf_lines("  4f ( 4)", "   2;   4", "      4+");
+        assert!(ok.is_empty(), "{:?}", ok);
+        let bad = validate_csf_lines("  4f ( 4)", "   0;   4", "      4+");
+        assert!(bad.iter().any(|e| e.contains("Seniority")), "{:?}", bad);
+    }
+
+    #[test]
+    fn test_parity_mismatch() {
+        let errors = validate_csf_lines("  3s ( 1)  3p-( 1)", "      1/2      1/2", "              1+");
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].contains("parity"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_unparseable_csf() {
+        let errors = validate_csf_lines("  3s ( 1", "", "  1/2+");
+        assert_eq!(errors.len(), 1);
+    }
+}
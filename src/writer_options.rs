@@ -0,0 +1,244 @@
+//! Parquet Writer Options
+//!
+//! The CSF table written by the converters and the descriptor table are read
+//! very differently: a CSF table is usually scanned once and filtered, while
+//! descriptor tables are kept around and loaded many times. [`WriterOptions`]
+//! lets each dataset choose its own trade-off between file size and scan speed:
+//! compression codec and level, rows per row group, dictionary encoding, column
+//! statistics and data page size.
+//!
+//! Leaving `codec` unset keeps each writer's historical default: uncompressed
+//! CSF tables and ZSTD (level 3) descriptor tables.
+
+use crate::error::{CsfError, Result};
+use parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::{EnabledStatistics, WriterProperties, WriterPropertiesBuilder};
+
+/// Parquet compression codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Uncompressed,
+    Snappy,
+    Gzip,
+    Brotli,
+    /// LZ4 raw blocks (the `LZ4_RAW` Parquet codec)
+    Lz4,
+    Zstd,
+}
+
+impl std::str::FromStr for Codec {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uncompressed" | "none" => Ok(Codec::Uncompressed),
+            "snappy" => Ok(Codec::Snappy),
+            "gzip" => Ok(Codec::Gzip),
+            "brotli" => Ok(Codec::Brotli),
+            "lz4" => Ok(Codec::Lz4),
+            "zstd" => Ok(Codec::Zstd),
+            _ => Err(format!(
+                "Invalid compression '{}': expected 'uncompressed', 'snappy', 'gzip', 'brotli', 'lz4' or 'zstd'",
+                s
+            )),
+        }
+    }
+}
+
+impl Codec {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::Uncompressed => "uncompressed",
+            Codec::Snappy => "snappy",
+            Codec::Gzip => "gzip",
+            Codec::Brotli => "brotli",
+            Codec::Lz4 => "lz4",
+            Codec::Zstd => "zstd",
+        }
+    }
+
+    /// Parquet compression of this codec at `level`, or the codec's default level
+    fn compression(self, level: Option<u32>) -> Result<Compression> {
+        let invalid = |e: parquet::errors::ParquetError| CsfError::InvalidInput(e.to_string());
+        match (self, level) {
+            (Codec::Gzip, level) => Ok(Compression::GZIP(match level {
+                Some(level) => GzipLevel::try_new(level).map_err(invalid)?,
+                None => GzipLevel::default(),
+            })),
+            (Codec::Brotli, level) => Ok(Compression::BROTLI(match level {
+                Some(level) => BrotliLevel::try_new(level).map_err(invalid)?,
+                None => BrotliLevel::default(),
+            })),
+            (Codec::Zstd, level) => Ok(Compression::ZSTD(match level {
+                Some(level) => ZstdLevel::try_new(level as i32).map_err(invalid)?,
+                None => ZstdLevel::default(),
+            })),
+            (codec, Some(_)) => Err(CsfError::InvalidInput(format!(
+                "{:?} compression does not take a level",
+                codec
+            ))),
+            (Codec::Uncompressed, None) => Ok(Compression::UNCOMPRESSED),
+            (Codec::Snappy, None) => Ok(Compression::SNAPPY),
+            (Codec::Lz4, None) => Ok(Compression::LZ4_RAW),
+        }
+    }
+}
+
+/// Layout and encoding of a Parquet file written by rCSFs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriterOptions {
+    /// Compression codec, `None` for the writer's default
+    pub codec: Option<Codec>,
+    /// Compression level for gzip (0-10), brotli (0-11) and zstd (1-22),
+    /// `None` for the codec's default
+    pub level: Option<u32>,
+    /// Most rows per row group, `None` for the Parquet default (1M rows)
+    pub row_group_rows: Option<usize>,
+    /// Dictionary-encode columns; line2 and line3 repeat a lot in CSF lists
+    pub dictionary: bool,
+    /// Write min/max statistics for each column chunk and page
+    pub statistics: bool,
+    /// Target size of a data page in bytes, `None` for the Parquet default (1 MiB)
+    pub data_page_size: Option<usize>,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            codec: None,
+            level: None,
+            row_group_rows: None,
+            dictionary: true,
+            statistics: true,
+            data_page_size: None,
+        }
+    }
+}
+
+impl WriterOptions {
+    /// Writer properties builder, using `default_codec` when `codec` is unset
+    pub fn builder(&self, default_codec: Compression) -> Result<WriterPropertiesBuilder> {
+        let compression = match self.codec {
+            Some(codec) => codec.compression(self.level)?,
+            None if self.level.is_some() => {
+                return Err(CsfError::InvalidInput(
+                    "A compression level needs a compression codec".to_string(),
+                ));
+            }
+            None => default_codec,
+        };
+        let statistics = if self.statistics {
+            EnabledStatistics::Page
+        } else {
+            EnabledStatistics::None
+        };
+
+        let mut builder = WriterProperties::builder()
+            .set_compression(compression)
+            .set_dictionary_enabled(self.dictionary)
+            .set_statistics_enabled(statistics);
+        if let Some(rows) = self.row_group_rows {
+            if rows == 0 {
+                return Err(CsfError::InvalidInput("row_group_rows must be greater than 0".to_string()));
+            }
+            builder = builder.set_max_row_group_size(rows);
+        }
+        if let Some(size) = self.data_page_size {
+            if size == 0 {
+                return Err(CsfError::InvalidInput("data_page_size must be greater than 0".to_string()));
+            }
+            builder = builder.set_data_page_size_limit(size);
+        }
+        Ok(builder)
+    }
+
+    /// Writer properties, using `default_codec` when `codec` is unset
+    pub fn properties(&self, default_codec: Compression) -> Result<WriterProperties> {
+        Ok(self.builder(default_codec)?.build())
+    }
+}
+
+/// Compression of `properties` for progress messages, e.g. `ZSTD(ZstdLevel(3))`
+pub(crate) fn compression_name(properties: &WriterProperties) -> String {
+    properties
+        .compression(&parquet::schema::types::ColumnPath::from("idx"))
+        .to_string()
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Python Bindings (PyO3)
+//////////////////////////////////////////////////////////////////////////////
+
+/// Build [`WriterOptions`] from the keyword arguments shared by the Python writers
+#[cfg(feature = "python")]
+pub(crate) fn writer_options_from_kwargs(
+    compression: Option<&str>,
+    compression_level: Option<u32>,
+    row_group_rows: Option<usize>,
+    dictionary: bool,
+    statistics: bool,
+    data_page_size: Option<usize>,
+) -> pyo3::PyResult<WriterOptions> {
+    let codec = compression
+        .map(str::parse)
+        .transpose()
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    let options = WriterOptions {
+        codec,
+        level: compression_level,
+        row_group_rows,
+        dictionary,
+        statistics,
+        data_page_size,
+    };
+    // Report bad combinations before any file is created
+    options.builder(Compression::UNCOMPRESSED)?;
+    Ok(options)
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Rust Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_from_str() {
+        assert_eq!("ZSTD".parse::<Codec>(), Ok(Codec::Zstd));
+        assert_eq!("none".parse::<Codec>(), Ok(Codec::Uncompressed));
+        assert!("lzo".parse::<Codec>().is_err());
+    }
+
+    #[test]
+    fn test_properties() {
+        let path = parquet::schema::types::ColumnPath::from("line1");
+
+        let props = WriterOptions::default().properties(Compression::SNAPPY).unwrap();
+        assert_eq!(props.compression(&path), Compression::SNAPPY);
+        assert!(props.dictionary_enabled(&path));
+
+        let options = WriterOptions {
+            codec: Some(Codec::Zstd),
+            level: Some(9),
+            row_group_rows: Some(1000),
+            dictionary: false,
+            statistics: false,
+            data_page_size: Some(4096),
+        };
+        let props = options.properties(Compression::UNCOMPRESSED).unwrap();
+        assert_eq!(props.compression(&path), Compression::ZSTD(ZstdLevel::try_new(9).unwrap()));
+        assert_eq!(props.max_row_group_size(), 1000);
+        assert!(!props.dictionary_enabled(&path));
+        assert_eq!(props.statistics_enabled(&path), EnabledStatistics::None);
+        assert_eq!(props.data_page_size_limit(), 4096);
+
+        let bad_level = WriterOptions { codec: Some(Codec::Zstd), level: Some(99), ..Default::default() };
+        assert!(matches!(bad_level.properties(Compression::UNCOMPRESSED), Err(CsfError::InvalidInput(_))));
+        let level_without_codec = WriterOptions { level: Some(3), ..Default::default() };
+        assert!(level_without_codec.properties(Compression::UNCOMPRESSED).is_err());
+        let snappy_level = WriterOptions { codec: Some(Codec::Snappy), level: Some(1), ..Default::default() };
+        assert!(snappy_level.properties(Compression::UNCOMPRESSED).is_err());
+    }
+}
@@ -6,6 +6,8 @@
 //! - Error recovery scenarios
 //! - Large file handling
 
+use _rcsfs::error::CsfError;
+use _rcsfs::writer_options::WriterOptions;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -88,6 +90,47 @@ fn create_large_csf(path: &Path, csf_count: usize) {
     fs::write(path, content).expect("Failed to create large CSF file");
 }
 
+/// Create a CSF file with two J/parity blocks separated by ` *`
+fn create_multi_block_csf(path: &Path) {
+    let content = "Core subshells:\n\
+                   \x20 1s   2s   2p-  2p\n\
+                   Peel subshells:\n\
+                   \x20 3s   3p-  3p\n\
+                   CSF(s):\n\
+                   \x20 3s ( 1)  3p-( 2)\n\
+                   \x20              1/2\n\
+                   \x20                1/2+\n\
+                   \x20*\n\
+                   \x20 3s ( 2)  3p-( 1)\n\
+                   \x20              1/2\n\
+                   \x20                1/2-\n\
+                   \x20 3s ( 2)  3p ( 1)\n\
+                   \x20              3/2\n\
+                   \x20                3/2-\n";
+    fs::write(path, content).expect("Failed to create multi-block CSF file");
+}
+
+/// Read the `block` column of a converted CSF Parquet file
+fn read_block_column(path: &Path) -> Vec<u32> {
+    use arrow::array::UInt32Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let file = fs::File::open(path).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap();
+    let mut blocks = Vec::new();
+    for batch in reader {
+        let batch = batch.unwrap();
+        let col = batch
+            .column_by_name("block")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap();
+        blocks.extend(col.values().iter().copied());
+    }
+    blocks
+}
+
 //////////////////////////////////////////////////////////////////////////////
 // Parquet I/O Tests
 //////////////////////////////////////////////////////////////////////////////
@@ -101,7 +144,13 @@ fn test_parquet_io_basic() {
 
     create_minimal_csf(&input_path);
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -122,7 +171,13 @@ fn test_parquet_io_creates_header_file() {
 
     create_minimal_csf(&input_path);
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
 
     assert!(result.is_ok(), "Conversion should succeed");
     assert!(header_path.exists(), "Header file should be created");
@@ -144,7 +199,13 @@ fn test_parquet_io_invalid_input_path() {
     let input_path = temp_dir().join("nonexistent.csf");
     let output_path = temp_dir().join("output.parquet");
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
 
     assert!(result.is_err(), "Conversion should fail for non-existent input file");
 }
@@ -158,13 +219,90 @@ fn test_parquet_io_invalid_output_directory() {
 
     create_minimal_csf(&input_path);
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
 
     assert!(result.is_err(), "Conversion should fail for invalid output directory");
 }
 
+#[test]
+fn test_writer_options_set_codec_row_groups_and_encodings() {
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, convert_csfs_to_parquet_parallel};
+    use _rcsfs::csfs_descriptor::parquet_batch::{generate_descriptors_from_parquet, ErrorPolicy};
+    use _rcsfs::writer_options::Codec;
+    use parquet::basic::{Compression, Encoding};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let input_path = temp_dir().join("test_writer_options.csf");
+    let output_path = temp_dir().join("test_writer_options.parquet");
+    let descriptor_path = temp_dir().join("test_writer_options_desc.parquet");
+    create_large_csf(&input_path, 10);
+    let metadata = |path: &Path| SerializedFileReader::new(fs::File::open(path).unwrap()).unwrap().metadata().clone();
+
+    // Defaults: uncompressed, dictionary encoded, with statistics
+    convert_csfs_to_parquet(&input_path, &output_path, 256, 1000, &WriterOptions::default()).unwrap();
+    let defaults = metadata(&output_path);
+    let column = defaults.row_group(0).column(1);
+    assert_eq!(column.compression(), Compression::UNCOMPRESSED);
+    assert!(column.encodings().any(|e| e == Encoding::RLE_DICTIONARY));
+    assert!(column.statistics().is_some());
+
+    let options = WriterOptions {
+        codec: Some(Codec::Zstd),
+        level: Some(9),
+        row_group_rows: Some(4),
+        dictionary: false,
+        statistics: false,
+        data_page_size: None,
+    };
+    convert_csfs_to_parquet_parallel(&input_path, &output_path, 256, 1000, Some(2), &options).unwrap();
+    let tuned = metadata(&output_path);
+    assert_eq!(tuned.num_row_groups(), 3);
+    let column = tuned.row_group(0).column(1);
+    // The file records the codec but not its level
+    assert!(matches!(column.compression(), Compression::ZSTD(_)));
+    assert!(!column.encodings().any(|e| e == Encoding::RLE_DICTIONARY));
+    assert!(column.statistics().is_none());
+    assert_eq!(read_parquet_batch(&output_path).num_rows(), 10);
+
+    // Descriptors stay ZSTD by default and follow an explicit codec
+    let peel: Vec<String> = ["5s", "4d-", "4d", "5p-", "5p", "6s"].iter().map(|s| s.to_string()).collect();
+    let descriptor_codec = |options: &WriterOptions| {
+        generate_descriptors_from_parquet(
+            &output_path,
+            &descriptor_path,
+            Some(peel.clone()),
+            None,
+            &[],
+            ErrorPolicy::Fail,
+            options,
+        )
+        .unwrap();
+        metadata(&descriptor_path).row_group(0).column(1).compression()
+    };
+    assert!(matches!(descriptor_codec(&WriterOptions::default()), Compression::ZSTD(_)));
+    let snappy = WriterOptions { codec: Some(Codec::Snappy), ..Default::default() };
+    assert_eq!(descriptor_codec(&snappy), Compression::SNAPPY);
+
+    // Invalid options fail before the output file is created
+    cleanup_test_file(&output_path);
+    let bad_level = WriterOptions { codec: Some(Codec::Gzip), level: Some(42), ..Default::default() };
+    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000, &bad_level);
+    assert!(matches!(result, Err(CsfError::InvalidInput(_))));
+    assert!(!output_path.exists());
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&descriptor_path);
+    cleanup_test_file(&temp_dir().join("test_writer_options_header.toml"));
+}
+
 //////////////////////////////////////////////////////////////////////////////
 // Line Truncation Tests
 //////////////////////////////////////////////////////////////////////////////
@@ -178,7 +316,13 @@ fn test_line_truncation() {
 
     create_long_line_csf(&input_path, 500); // Lines longer than max_line_len=256
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -202,7 +346,14 @@ fn test_parallel_processing_basic() {
 
     create_minimal_csf(&input_path);
 
-    let result = convert_csfs_to_parquet_parallel(&input_path, &output_path, 256, 1000, Some(2));
+    let result = convert_csfs_to_parquet_parallel(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        Some(2),
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -221,7 +372,14 @@ fn test_parallel_processing_single_worker() {
 
     create_minimal_csf(&input_path);
 
-    let result = convert_csfs_to_parquet_parallel(&input_path, &output_path, 256, 1000, Some(1));
+    let result = convert_csfs_to_parquet_parallel(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        Some(1),
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -238,7 +396,14 @@ fn test_parallel_processing_many_workers() {
 
     create_minimal_csf(&input_path);
 
-    let result = convert_csfs_to_parquet_parallel(&input_path, &output_path, 256, 1000, Some(8));
+    let result = convert_csfs_to_parquet_parallel(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        Some(8),
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -246,6 +411,208 @@ fn test_parallel_processing_many_workers() {
     assert!(result.is_ok(), "Many workers conversion should succeed");
 }
 
+#[test]
+fn test_parallel_processing_repeated_worker_counts() {
+    use _rcsfs::csfs_conversion::{
+        build_thread_pool, convert_csfs_to_parquet_in_pool, convert_csfs_to_parquet_parallel,
+    };
+
+    let input_path = temp_dir().join("test_parallel_repeated.csf");
+    let output_path = temp_dir().join("test_parallel_repeated.parquet");
+    create_minimal_csf(&input_path);
+
+    // Each call owns its pool, so the worker count can change between calls
+    for n in [3, 1, 5] {
+        let stats = convert_csfs_to_parquet_parallel(
+            &input_path,
+            &output_path,
+            256,
+            1000,
+            Some(n),
+            &WriterOptions::default(),
+        ).unwrap();
+        assert_eq!(stats.csf_count, 2);
+    }
+
+    // A caller-owned pool is reused as is
+    let pool = build_thread_pool(Some(2)).unwrap();
+    assert_eq!(pool.current_num_threads(), 2);
+    for _ in 0..2 {
+        let stats = convert_csfs_to_parquet_in_pool(
+            &input_path,
+            &output_path,
+            256,
+            1000,
+            &pool,
+            &WriterOptions::default(),
+        ).unwrap();
+        assert_eq!(stats.csf_count, 2);
+    }
+    assert!(build_thread_pool(Some(0)).is_err());
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+    cleanup_test_file(&temp_dir().join("test_parallel_repeated_header.toml"));
+}
+
+#[test]
+fn test_parallel_pipeline_matches_sequential() {
+    use _rcsfs::csfs_conversion::{
+        build_thread_pool, convert_csfs_to_parquet, convert_csfs_to_parquet_by_ranges,
+        convert_csfs_to_parquet_parallel, DEFAULT_RANGE_BYTES,
+    };
+
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let input_path = temp_dir().join("test_pipeline.csf");
+    let seq_path = temp_dir().join("test_pipeline_seq.parquet");
+    let par_path = temp_dir().join("test_pipeline_par.parquet");
+    // Split the sample into blocks of 4 CSFs, so ranges also start and end around separators
+    let text = fs::read_to_string(&fixture).unwrap();
+    let mut content = String::new();
+    for (i, line) in text.lines().enumerate() {
+        if i > 5 && (i - 5) % 12 == 0 {
+            content.push_str(" *\n");
+        }
+        content.push_str(line);
+        content.push('\n');
+    }
+    fs::write(&input_path, content).unwrap();
+
+    let stats_seq = convert_csfs_to_parquet(
+        &input_path,
+        &seq_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+    assert!(stats_seq.block_csf_counts.len() > 1);
+    let expected = read_parquet_batch(&seq_path);
+
+    // One CSF per batch, so batches finish out of order and must be reordered
+    for n in [1, 4] {
+        let stats_par = convert_csfs_to_parquet_parallel(
+            &input_path,
+            &par_path,
+            256,
+            3,
+            Some(n),
+            &WriterOptions::default(),
+        ).unwrap();
+        assert_eq!(stats_par.csf_count, stats_seq.csf_count);
+        assert_eq!(stats_par.total_lines, stats_seq.total_lines);
+        assert_eq!(stats_par.block_csf_counts, stats_seq.block_csf_counts);
+        assert_eq!(stats_par.electron_count, stats_seq.electron_count);
+        assert_eq!(read_parquet_batch(&par_path), expected);
+    }
+
+    // Byte ranges from one byte (mostly empty ranges) to the whole file
+    let pool = build_thread_pool(Some(3)).unwrap();
+    for range_bytes in [1, 97, 1000, DEFAULT_RANGE_BYTES] {
+        let stats_par = convert_csfs_to_parquet_by_ranges(
+            &input_path,
+            &par_path,
+            256,
+            range_bytes,
+            &pool,
+            &WriterOptions::default(),
+        ).unwrap();
+        assert_eq!(stats_par.csf_count, stats_seq.csf_count);
+        assert_eq!(stats_par.total_lines, stats_seq.total_lines);
+        assert_eq!(stats_par.block_csf_counts, stats_seq.block_csf_counts);
+        assert_eq!(read_parquet_batch(&par_path), expected, "range_bytes = {}", range_bytes);
+    }
+    assert!(convert_csfs_to_parquet_by_ranges(
+        &input_path,
+        &par_path,
+        256,
+        0,
+        &pool,
+        &WriterOptions::default(),
+    ).is_err());
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&seq_path);
+    cleanup_test_file(&par_path);
+    cleanup_test_file(&temp_dir().join("test_pipeline_header.toml"));
+}
+
+#[test]
+fn test_parallel_pipeline_read_error_removes_output() {
+    use _rcsfs::csfs_conversion::{
+        build_thread_pool, convert_csfs_to_parquet_by_ranges, convert_csfs_to_parquet_parallel,
+    };
+
+    let input_path = temp_dir().join("test_pipeline_bad.csf");
+    let output_path = temp_dir().join("test_pipeline_bad.parquet");
+
+    // Many good CSFs, then a separator splitting a CSF
+    let mut content = String::from("h1\nh2\nh3\nh4\nh5\n");
+    for _ in 0..50 {
+        content.push_str("  3s ( 1)\n      1/2\n        1/2+\n");
+    }
+    content.push_str("  3s ( 1)\n *\n      1/2\n        1/2+\n");
+    fs::write(&input_path, content).unwrap();
+
+    let result = convert_csfs_to_parquet_parallel(
+        &input_path,
+        &output_path,
+        256,
+        6,
+        Some(3),
+        &WriterOptions::default(),
+    );
+    assert!(
+        matches!(result, Err(CsfError::MalformedCsf { idx: Some(50), .. })),
+        "{:?}",
+        result.err()
+    );
+    assert!(!output_path.exists());
+    assert!(!temp_dir().join("test_pipeline_bad_header.toml").exists());
+
+    // Byte ranges report the same CSF and its line in the whole file
+    let pool = build_thread_pool(Some(3)).unwrap();
+    let result = convert_csfs_to_parquet_by_ranges(
+        &input_path,
+        &output_path,
+        256,
+        100,
+        &pool,
+        &WriterOptions::default(),
+    );
+    assert!(
+        matches!(result, Err(CsfError::MalformedCsf { idx: Some(50), line: Some(157), .. })),
+        "{:?}",
+        result.err()
+    );
+    assert!(!output_path.exists());
+
+    // A CSF missing its last line is reported before the next CSF
+    let mut content = String::from("h1\nh2\nh3\nh4\nh5\n");
+    for i in 0..50 {
+        content.push_str("  3s ( 1)\n      1/2\n");
+        if i != 20 {
+            content.push_str("        1/2+\n");
+        }
+    }
+    fs::write(&input_path, content).unwrap();
+    let result = convert_csfs_to_parquet_by_ranges(
+        &input_path,
+        &output_path,
+        256,
+        100,
+        &pool,
+        &WriterOptions::default(),
+    );
+    assert!(
+        matches!(result, Err(CsfError::MalformedCsf { idx: Some(20), .. })),
+        "{:?}",
+        result.err()
+    );
+
+    cleanup_test_file(&input_path);
+    assert!(!output_path.exists());
+}
+
 //////////////////////////////////////////////////////////////////////////////
 // Edge Cases Tests
 //////////////////////////////////////////////////////////////////////////////
@@ -265,7 +632,13 @@ fn test_empty_csf() {
                      Header line 5\n";
     fs::write(&input_path, content).expect("Failed to create empty CSF file");
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -292,7 +665,13 @@ fn test_incomplete_csf() {
                                           3/2               2\n";
     fs::write(&input_path, content).expect("Failed to create incomplete CSF file");
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -311,7 +690,13 @@ fn test_very_small_max_line_len() {
 
     create_minimal_csf(&input_path);
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 10, 1000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        10,
+        1000,
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -335,7 +720,13 @@ fn test_large_file_sequential() {
     // Create a file with 1000 CSFs
     create_large_csf(&input_path, 1000);
 
-    let result = convert_csfs_to_parquet(&input_path, &output_path, 256, 10000);
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        10000,
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -355,7 +746,14 @@ fn test_large_file_parallel() {
     // Create a file with 1000 CSFs
     create_large_csf(&input_path, 1000);
 
-    let result = convert_csfs_to_parquet_parallel(&input_path, &output_path, 256, 5000, Some(4));
+    let result = convert_csfs_to_parquet_parallel(
+        &input_path,
+        &output_path,
+        256,
+        5000,
+        Some(4),
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path);
     cleanup_test_file(&output_path);
@@ -378,8 +776,21 @@ fn test_large_file_integrity() {
     create_large_csf(&input_path1, 100);
     create_large_csf(&input_path2, 100);
 
-    let result_seq = convert_csfs_to_parquet(&input_path1, &output_path1, 256, 10000);
-    let result_par = convert_csfs_to_parquet_parallel(&input_path2, &output_path2, 256, 5000, Some(4));
+    let result_seq = convert_csfs_to_parquet(
+        &input_path1,
+        &output_path1,
+        256,
+        10000,
+        &WriterOptions::default(),
+    );
+    let result_par = convert_csfs_to_parquet_parallel(
+        &input_path2,
+        &output_path2,
+        256,
+        5000,
+        Some(4),
+        &WriterOptions::default(),
+    );
 
     cleanup_test_file(&input_path1);
     cleanup_test_file(&input_path2);
@@ -397,3 +808,1849 @@ fn test_large_file_integrity() {
     assert_eq!(stats_seq.total_lines, stats_par.total_lines, "Total lines should match");
     assert_eq!(stats_seq.truncated_count, stats_par.truncated_count, "Truncated count should match");
 }
+
+//////////////////////////////////////////////////////////////////////////////
+// Multi-block Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_multi_block_sequential() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+
+    let input_path = temp_dir().join("test_multi_block_seq.csf");
+    let output_path = temp_dir().join("test_multi_block_seq.parquet");
+    let header_path = temp_dir().join("test_multi_block_seq_header.toml");
+
+    create_multi_block_csf(&input_path);
+
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
+    assert!(result.is_ok(), "Multi-block conversion should succeed");
+    let stats = result.unwrap();
+    assert_eq!(stats.csf_count, 3, "Separator must not be counted as CSF data");
+    assert_eq!(stats.block_csf_counts, vec![1, 2]);
+    assert_eq!(read_block_column(&output_path), vec![0, 1, 1]);
+
+    let header_content = fs::read_to_string(&header_path).unwrap();
+    assert!(header_content.contains("block_csf_counts"), "Header file should record block sizes");
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+    cleanup_test_file(&header_path);
+}
+
+#[test]
+fn test_multi_block_small_chunks() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+
+    let input_path = temp_dir().join("test_multi_block_chunks.csf");
+    let output_path = temp_dir().join("test_multi_block_chunks.parquet");
+
+    create_multi_block_csf(&input_path);
+
+    // A chunk size of 4 lines puts the separator in the middle of a read batch
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        4,
+        &WriterOptions::default(),
+    );
+    let blocks = read_block_column(&output_path);
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+
+    let stats = result.unwrap();
+    assert_eq!(stats.csf_count, 3);
+    assert_eq!(blocks, vec![0, 1, 1]);
+}
+
+#[test]
+fn test_multi_block_parallel() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet_parallel;
+
+    let input_path = temp_dir().join("test_multi_block_par.csf");
+    let output_path = temp_dir().join("test_multi_block_par.parquet");
+
+    create_multi_block_csf(&input_path);
+
+    let result = convert_csfs_to_parquet_parallel(
+        &input_path,
+        &output_path,
+        256,
+        3,
+        None,
+        &WriterOptions::default(),
+    );
+    let blocks = read_block_column(&output_path);
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+
+    let stats = result.unwrap();
+    assert_eq!(stats.csf_count, 3);
+    assert_eq!(stats.total_lines, 10, "Separator line is still counted as read");
+    assert_eq!(stats.block_csf_counts, vec![1, 2]);
+    assert_eq!(blocks, vec![0, 1, 1]);
+}
+
+#[test]
+fn test_single_block_has_one_block_count() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+
+    let input_path = temp_dir().join("test_single_block.csf");
+    let output_path = temp_dir().join("test_single_block.parquet");
+
+    create_large_csf(&input_path, 10);
+
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
+    let blocks = read_block_column(&output_path);
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+
+    assert_eq!(result.unwrap().block_csf_counts, vec![10]);
+    assert!(blocks.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn test_separator_inside_csf_is_rejected() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+
+    let input_path = temp_dir().join("test_bad_separator.csf");
+    let output_path = temp_dir().join("test_bad_separator.parquet");
+
+    let content = "h1\nh2\nh3\nh4\nh5\n\
+                   \x20 3s ( 1)\n\
+                   \x20*\n\
+                   \x20    1/2\n\
+                   \x20      1/2+\n";
+    fs::write(&input_path, content).unwrap();
+
+    let result = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+
+    assert!(result.is_err(), "A separator splitting a CSF should be reported");
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Typed CSF Tests
+//////////////////////////////////////////////////////////////////////////////
+
+/// Read the CSF triples of the bundled sample file
+fn sample_csf_triples() -> Vec<[String; 3]> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let content = fs::read_to_string(path).unwrap();
+    let lines: Vec<&str> = content.lines().skip(5).collect();
+    lines
+        .chunks(3)
+        .map(|c| [c[0].to_string(), c[1].to_string(), c[2].to_string()])
+        .collect()
+}
+
+#[test]
+fn test_typed_csf_round_trip_sample() {
+    use _rcsfs::csf::Csf;
+
+    let triples = sample_csf_triples();
+    assert!(!triples.is_empty());
+    for [line1, line2, line3] in &triples {
+        let csf = Csf::parse(line1, line2, line3).unwrap();
+        assert_eq!(&csf.to_lines(), &[line1.clone(), line2.clone(), line3.clone()]);
+    }
+}
+
+#[test]
+fn test_typed_csf_descriptor_matches_text_parser() {
+    use _rcsfs::csf::Csf;
+    use _rcsfs::csfs_descriptor::CSFDescriptorGenerator;
+
+    let peel: Vec<String> = "5s 4d- 4d 5p- 5p 6s 4f- 4f 5d- 5d"
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    let generator = CSFDescriptorGenerator::new(peel);
+
+    for [line1, line2, line3] in sample_csf_triples() {
+        let csf = Csf::parse(&line1, &line2, &line3).unwrap();
+        assert_eq!(
+            generator.descriptor_from_csf(&csf).unwrap(),
+            generator.parse_csf(&line1, &line2, &line3).unwrap()
+        );
+    }
+}
+
+/// The descriptor parser as it was before `parse_csf_into`: padded copies of
+/// line2 and line3, a `String` per subshell name, a `HashMap` lookup and
+/// `HashSet`s for the unoccupied orbitals
+///
+/// Kept as the reference `parse_csf_into` is checked and timed against.
+fn legacy_parse_csf(
+    orbital_index_map: &std::collections::HashMap<String, usize>,
+    line1: &str,
+    line2: &str,
+    line3: &str,
+) -> Result<Vec<i32>, String> {
+    use _rcsfs::csfs_descriptor::j_to_double_j;
+
+    fn chunk_string(s: &str, chunk_size: usize) -> Vec<&str> {
+        s.as_bytes()
+            .chunks(chunk_size)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap_or(""))
+            .collect()
+    }
+
+    let orbital_count = orbital_index_map.len();
+    let mut descriptor = vec![0i32; 3 * orbital_count];
+    let mut occupied_orbitals = Vec::new();
+
+    let subshells_line = line1.trim_end();
+    let line_length = subshells_line.len();
+    let middle_line = format!("{:<width$}", line2.trim_end(), width = line_length);
+    let coupling_line_raw = line3.trim_end();
+    let coupling_trimmed = coupling_line_raw
+        .get(4..coupling_line_raw.len().saturating_sub(5))
+        .unwrap_or(coupling_line_raw);
+    let coupling_line = format!("{:<width$}", coupling_trimmed, width = line_length);
+    let final_j_str = coupling_line_raw
+        .get(coupling_line_raw.len().saturating_sub(5)..coupling_line_raw.len().saturating_sub(1))
+        .unwrap_or("");
+    let final_double_j = j_to_double_j(final_j_str)?;
+
+    let subshell_list = chunk_string(subshells_line, 9);
+    let middle_list = chunk_string(&middle_line, 9);
+    let coupling_list = chunk_string(&coupling_line, 9);
+
+    for (i, ((subshell_charges, middle_item), coupling_item)) in subshell_list
+        .iter()
+        .zip(middle_list.iter())
+        .zip(coupling_list.iter())
+        .enumerate()
+    {
+        let subshell = subshell_charges.get(0..5).map(|s| s.trim()).unwrap_or("").to_string();
+        let subshell_electron_num: i32 = if subshell_charges.len() >= 8 {
+            subshell_charges[6..8].trim().parse().unwrap_or(0)
+        } else {
+            0
+        };
+
+        let mut temp_middle_item: i32 = 0;
+        if !middle_item.trim().is_empty() {
+            let middle_value = match middle_item.find(';') {
+                Some(semi_pos) => &middle_item[semi_pos + 1..],
+                None => middle_item,
+            };
+            temp_middle_item = j_to_double_j(middle_value).unwrap_or(0);
+        }
+        let mut temp_coupling_item: i32 = 0;
+        if !coupling_item.trim().is_empty() {
+            temp_coupling_item = j_to_double_j(coupling_item).unwrap_or(0);
+        } else if !middle_item.trim().is_empty() {
+            temp_coupling_item = temp_middle_item;
+        }
+        if i == subshell_list.len() - 1 {
+            temp_coupling_item = final_double_j;
+        }
+
+        if let Some(&orbs_idx) = orbital_index_map.get(&subshell) {
+            let descriptor_idx = orbs_idx * 3;
+            descriptor[descriptor_idx] = subshell_electron_num;
+            descriptor[descriptor_idx + 1] = temp_middle_item;
+            descriptor[descriptor_idx + 2] = temp_coupling_item;
+            occupied_orbitals.push(orbs_idx);
+        }
+    }
+
+    let all_orbitals: std::collections::HashSet<_> = (0..orbital_count).collect();
+    let occupied: std::collections::HashSet<_> = occupied_orbitals.iter().cloned().collect();
+    for idx in all_orbitals.difference(&occupied) {
+        descriptor[idx * 3 + 2] = final_double_j;
+    }
+
+    Ok(descriptor)
+}
+
+/// Generator and legacy lookup table for the peel subshells of the sample file
+fn sample_descriptor_parsers() -> (
+    _rcsfs::csfs_descriptor::CSFDescriptorGenerator,
+    std::collections::HashMap<String, usize>,
+) {
+    use _rcsfs::csf_header::CsfHeader;
+    use _rcsfs::csfs_descriptor::CSFDescriptorGenerator;
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let peel = CsfHeader::from_csf_file(&path).unwrap().peel_labels();
+    let orbital_index_map = peel.iter().enumerate().map(|(i, name)| (name.clone(), i)).collect();
+    (CSFDescriptorGenerator::new(peel), orbital_index_map)
+}
+
+#[test]
+fn test_parse_csf_into_matches_legacy_parser() {
+    let (generator, orbital_index_map) = sample_descriptor_parsers();
+    let mut buffer = vec![0; 3 * generator.orbital_count()];
+
+    let triples = sample_csf_triples();
+    assert!(!triples.is_empty());
+    for (i, [line1, line2, line3]) in triples.iter().enumerate() {
+        let expected = legacy_parse_csf(&orbital_index_map, line1, line2, line3).unwrap();
+        generator.parse_csf_into(line1, line2, line3, &mut buffer).unwrap();
+        assert_eq!(buffer, expected, "CSF {}", i);
+        assert_eq!(generator.parse_csf(line1, line2, line3).unwrap(), expected, "CSF {}", i);
+    }
+}
+
+/// Throughput of `parse_csf_into` against the legacy parser
+///
+/// Run with `cargo test --release --test integration_test bench_parse_csf -- --ignored --nocapture`;
+/// results are recorded in `docs/performance_optimization_log.md`.
+#[test]
+#[ignore]
+fn bench_parse_csf_into_vs_legacy() {
+    use std::time::Instant;
+
+    let (generator, orbital_index_map) = sample_descriptor_parsers();
+    let triples = sample_csf_triples();
+    let rounds = 2_000_000 / triples.len();
+    let csf_count = (rounds * triples.len()) as f64;
+
+    let start = Instant::now();
+    let mut checksum = 0i64;
+    for _ in 0..rounds {
+        for [line1, line2, line3] in &triples {
+            let descriptor = legacy_parse_csf(&orbital_index_map, line1, line2, line3).unwrap();
+            checksum += descriptor[2] as i64;
+        }
+    }
+    let legacy = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let mut buffer = vec![0; 3 * generator.orbital_count()];
+    let mut checksum_into = 0i64;
+    for _ in 0..rounds {
+        for [line1, line2, line3] in &triples {
+            generator.parse_csf_into(line1, line2, line3, &mut buffer).unwrap();
+            checksum_into += buffer[2] as i64;
+        }
+    }
+    let into = start.elapsed().as_secs_f64();
+    assert_eq!(checksum, checksum_into);
+
+    println!("{} CSFs, {} peel orbitals", csf_count, generator.orbital_count());
+    println!("legacy parse_csf: {:.3} s, {:.2} M CSF/s", legacy, csf_count / legacy / 1e6);
+    println!("parse_csf_into:   {:.3} s, {:.2} M CSF/s", into, csf_count / into / 1e6);
+    println!("speedup:          {:.2}x", legacy / into);
+    assert!(legacy / into >= 2.0, "parse_csf_into is only {:.2}x faster", legacy / into);
+}
+
+/// Convert the multi-block sample with an unparsable final J in its second CSF
+///
+/// Returns the paths of the CSF file, the Parquet table and its header.
+fn convert_csf_with_bad_row(stem: &str) -> (PathBuf, PathBuf, PathBuf) {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+
+    let input_path = temp_dir().join(format!("{}.csf", stem));
+    let parquet_path = temp_dir().join(format!("{}.parquet", stem));
+    let header_path = temp_dir().join(format!("{}_header.toml", stem));
+    create_multi_block_csf(&input_path);
+    let content = fs::read_to_string(&input_path).unwrap().replacen("1/2-", "x/y-", 1);
+    fs::write(&input_path, content).unwrap();
+    convert_csfs_to_parquet(
+        &input_path,
+        &parquet_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+    (input_path, parquet_path, header_path)
+}
+
+/// Read a whole Parquet file into one record batch
+fn read_parquet_batch(path: &Path) -> arrow::record_batch::RecordBatch {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let file = fs::File::open(path).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap();
+    let batches: Vec<_> = reader.map(|b| b.unwrap()).collect();
+    arrow::compute::concat_batches(&batches[0].schema(), &batches).unwrap()
+}
+
+#[test]
+fn test_descriptor_parquet_keeps_idx_and_passthrough_columns() {
+    use _rcsfs::csfs_descriptor::parquet_batch::{
+        generate_descriptors_from_parquet, generate_descriptors_from_parquet_parallel, ErrorPolicy,
+    };
+    use arrow::array::{Int32Array, UInt32Array, UInt64Array};
+
+    let (input_path, parquet_path, header_path) = convert_csf_with_bad_row("test_descriptor_passthrough");
+    let serial_path = temp_dir().join("test_descriptor_passthrough_serial.parquet");
+    let parallel_path = temp_dir().join("test_descriptor_passthrough_parallel.parquet");
+
+    let passthrough = vec!["block".to_string()];
+    let serial = generate_descriptors_from_parquet(
+        &parquet_path,
+        &serial_path,
+        None,
+        None,
+        &passthrough,
+        ErrorPolicy::Zeros,
+        &WriterOptions::default(),
+    )
+    .unwrap();
+    let parallel = generate_descriptors_from_parquet_parallel(
+        &parquet_path,
+        &parallel_path,
+        vec!["3s".into(), "3p-".into(), "3p".into()],
+        Some(2),
+        &passthrough,
+        ErrorPolicy::Zeros,
+        &WriterOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(serial.csf_count, 3);
+    assert_eq!(serial.descriptor_count, 3, "failed rows are kept");
+    assert_eq!(parallel.descriptor_count, 3);
+
+    let serial_batch = read_parquet_batch(&serial_path);
+    let parallel_batch = read_parquet_batch(&parallel_path);
+    assert_eq!(serial_batch, parallel_batch);
+
+    let names: Vec<String> = serial_batch.schema().fields().iter().map(|f| f.name().clone()).collect();
+    assert_eq!(&names[..3], ["idx", "block", "col_0"]);
+    assert_eq!(names.len(), 2 + 9);
+
+    let idx = serial_batch.column(0).as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!(idx.values().to_vec(), vec![0, 1, 2]);
+    let block = serial_batch.column(1).as_any().downcast_ref::<UInt32Array>().unwrap();
+    assert_eq!(block.values().to_vec(), read_block_column(&parquet_path));
+    // The unparsable CSF is written as zeros instead of being dropped
+    for c in 2..names.len() {
+        let col = serial_batch.column(c).as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!(col.value(1), 0);
+    }
+
+    // Unknown passthrough columns are rejected
+    let missing = vec!["J".to_string()];
+    let err = generate_descriptors_from_parquet(
+        &parquet_path,
+        &serial_path,
+        None,
+        None,
+        &missing,
+        ErrorPolicy::Zeros,
+        &WriterOptions::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, CsfError::Schema(_)), "{}", err);
+    assert!(err.to_string().contains("'J'"), "{}", err);
+
+    let rejects_path = temp_dir().join("test_descriptor_passthrough_serial_rejects.parquet");
+    let parallel_rejects_path = temp_dir().join("test_descriptor_passthrough_parallel_rejects.parquet");
+    for path in [
+        &input_path,
+        &parquet_path,
+        &header_path,
+        &serial_path,
+        &parallel_path,
+        &rejects_path,
+        &parallel_rejects_path,
+    ] {
+        cleanup_test_file(path);
+    }
+}
+
+/// Generate descriptors with `ErrorPolicy::Skip` on one thread
+fn run_descriptor_policy_skip(
+    parquet_path: &Path,
+    output_path: &Path,
+    peel: &[String],
+) -> _rcsfs::csfs_descriptor::parquet_batch::BatchDescriptorStats {
+    use _rcsfs::csfs_descriptor::parquet_batch::{generate_descriptors_from_parquet, ErrorPolicy};
+
+    generate_descriptors_from_parquet(
+        parquet_path,
+        output_path,
+        Some(peel.to_vec()),
+        None,
+        &[],
+        ErrorPolicy::Skip,
+        &WriterOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_descriptor_error_policies_and_rejects_file() {
+    use _rcsfs::csfs_descriptor::parquet_batch::{
+        generate_descriptors_from_parquet, generate_descriptors_from_parquet_parallel, ErrorPolicy,
+    };
+    use arrow::array::{Array, Int32Array, StringArray, UInt64Array};
+
+    let (input_path, parquet_path, header_path) = convert_csf_with_bad_row("test_descriptor_policy");
+    let output_path = temp_dir().join("test_descriptor_policy_out.parquet");
+    let rejects_path = temp_dir().join("test_descriptor_policy_out_rejects.parquet");
+    let peel: Vec<String> = vec!["3s".into(), "3p-".into(), "3p".into()];
+
+    for parallel in [false, true] {
+        let run = |policy: ErrorPolicy| {
+            if parallel {
+                generate_descriptors_from_parquet_parallel(
+                    &parquet_path,
+                    &output_path,
+                    peel.clone(),
+                    Some(2),
+                    &[],
+                    policy,
+                    &WriterOptions::default(),
+                )
+            } else {
+                generate_descriptors_from_parquet(
+                    &parquet_path,
+                    &output_path,
+                    Some(peel.clone()),
+                    None,
+                    &[],
+                    policy,
+                    &WriterOptions::default(),
+                )
+            }
+        };
+
+        // Skip: the failed row is left out and recorded in the rejects file
+        let stats = run(ErrorPolicy::Skip).unwrap();
+        assert_eq!((stats.csf_count, stats.descriptor_count, stats.failed_count), (3, 2, 1));
+        assert_eq!(stats.rejects_file.as_deref(), Some(rejects_path.to_str().unwrap()));
+        let batch = read_parquet_batch(&output_path);
+        let idx = batch.column(0).as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(idx.values().to_vec(), vec![0, 2]);
+
+        let rejects = read_parquet_batch(&rejects_path);
+        assert_eq!(rejects.num_rows(), 1);
+        let reject_idx = rejects.column_by_name("idx").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(reject_idx.value(0), 1);
+        let line3 = rejects.column_by_name("line3").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert!(line3.value(0).contains("x/y"));
+        let error = rejects.column_by_name("error").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert!(!error.value(0).is_empty());
+
+        // Nulls: the failed row is kept with null descriptor values
+        let stats = run(ErrorPolicy::Nulls).unwrap();
+        assert_eq!((stats.descriptor_count, stats.failed_count), (3, 1));
+        let batch = read_parquet_batch(&output_path);
+        let col = batch.column_by_name("col_0").unwrap().as_any().downcast_ref::<Int32Array>().unwrap();
+        assert_eq!((col.is_null(0), col.is_null(1), col.is_null(2)), (false, true, false));
+
+        // Fail: the first failure is an error, recorded in the rejects file,
+        // and no unreadable descriptor file is left behind
+        let err = run(ErrorPolicy::Fail).unwrap_err();
+        assert!(matches!(err, CsfError::MalformedCsf { idx: Some(1), .. }), "{}", err);
+        assert!(!output_path.exists());
+        let rejects = read_parquet_batch(&rejects_path);
+        let reject_idx = rejects.column_by_name("idx").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(reject_idx.values().to_vec(), vec![1]);
+        cleanup_test_file(&rejects_path);
+    }
+
+    // A run that fails after the output was created removes it and keeps the
+    // rejects file of the previous run
+    run_descriptor_policy_skip(&parquet_path, &output_path, &peel);
+    let bad_idx_path = temp_dir().join("test_descriptor_policy_bad_idx.parquet");
+    let batch = read_parquet_batch(&parquet_path);
+    let columns = batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, column)| {
+            let column = if field.name() == "idx" {
+                arrow::compute::cast(column, &arrow::datatypes::DataType::Int64).unwrap()
+            } else {
+                column.clone()
+            };
+            (field.name().clone(), column)
+        })
+        .collect::<Vec<_>>();
+    let batch = arrow::record_batch::RecordBatch::try_from_iter(columns).unwrap();
+    let file = fs::File::create(&bad_idx_path).unwrap();
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    for parallel in [false, true] {
+        let result = if parallel {
+            generate_descriptors_from_parquet_parallel(
+                &bad_idx_path,
+                &output_path,
+                peel.clone(),
+                Some(2),
+                &[],
+                ErrorPolicy::Skip,
+                &WriterOptions::default(),
+            )
+        } else {
+            generate_descriptors_from_parquet(
+                &bad_idx_path,
+                &output_path,
+                Some(peel.clone()),
+                None,
+                &[],
+                ErrorPolicy::Skip,
+                &WriterOptions::default(),
+            )
+        };
+        assert!(matches!(result, Err(CsfError::Schema(_))), "{:?}", result.err());
+        assert!(!output_path.exists());
+        assert_eq!(read_parquet_batch(&rejects_path).num_rows(), 1);
+        assert!(!temp_dir().join("test_descriptor_policy_out_rejects.parquet.tmp").exists());
+    }
+    cleanup_test_file(&bad_idx_path);
+
+    // A clean run leaves no rejects file behind
+    let stats = run_descriptor_policy_skip(&parquet_path, &output_path, &peel);
+    assert!(stats.rejects_file.is_some());
+    fs::write(
+        &input_path,
+        fs::read_to_string(&input_path).unwrap().replace("x/y-", "1/2-"),
+    )
+    .unwrap();
+    _rcsfs::csfs_conversion::convert_csfs_to_parquet(
+        &input_path,
+        &parquet_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+    let stats =
+        generate_descriptors_from_parquet(
+            &parquet_path,
+            &output_path,
+            Some(peel),
+            None,
+            &[],
+            ErrorPolicy::Fail,
+            &WriterOptions::default(),
+        )
+            .unwrap();
+    assert_eq!((stats.failed_count, stats.rejects_file), (0, None));
+    assert!(!rejects_path.exists());
+
+    for path in [&input_path, &parquet_path, &header_path, &output_path] {
+        cleanup_test_file(path);
+    }
+}
+
+#[test]
+fn test_descriptor_parallel_splits_batches_in_order() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+    use _rcsfs::csfs_descriptor::parquet_batch::{
+        generate_descriptors_from_parquet, generate_descriptors_from_parquet_parallel, ErrorPolicy,
+    };
+    use arrow::array::UInt64Array;
+
+    // More CSFs than one work item, so several workers share the input
+    let input_path = temp_dir().join("test_descriptor_pool.csf");
+    let parquet_path = temp_dir().join("test_descriptor_pool.parquet");
+    let header_path = temp_dir().join("test_descriptor_pool_header.toml");
+    let serial_path = temp_dir().join("test_descriptor_pool_serial.parquet");
+    let parallel_path = temp_dir().join("test_descriptor_pool_parallel.parquet");
+    create_large_csf(&input_path, 20_000);
+    convert_csfs_to_parquet(
+        &input_path,
+        &parquet_path,
+        256,
+        30_000,
+        &WriterOptions::default(),
+    ).unwrap();
+
+    let peel: Vec<String> = ["5s", "4d-", "4d", "5p-", "5p", "6s"].iter().map(|s| s.to_string()).collect();
+    generate_descriptors_from_parquet(
+        &parquet_path,
+        &serial_path,
+        Some(peel.clone()),
+        None,
+        &[],
+        ErrorPolicy::Zeros,
+        &WriterOptions::default(),
+    )
+        .unwrap();
+    let stats =
+        generate_descriptors_from_parquet_parallel(
+            &parquet_path,
+            &parallel_path,
+            peel.clone(),
+            Some(3),
+            &[],
+            ErrorPolicy::Zeros,
+            &WriterOptions::default(),
+        )
+            .unwrap();
+    assert_eq!((stats.csf_count, stats.descriptor_count, stats.num_workers), (20_000, 20_000, 3));
+    assert!(stats.elapsed_seconds > 0.0);
+
+    let serial = read_parquet_batch(&serial_path);
+    let parallel = read_parquet_batch(&parallel_path);
+    assert_eq!(serial, parallel);
+    let idx = parallel.column(0).as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert!(idx.values().iter().enumerate().all(|(i, &v)| v == i as u64));
+
+    let err = generate_descriptors_from_parquet_parallel(
+        &parquet_path,
+        &parallel_path,
+        peel,
+        Some(0),
+        &[],
+        ErrorPolicy::Zeros,
+        &WriterOptions::default(),
+    )
+        .unwrap_err();
+    assert!(matches!(err, CsfError::InvalidInput(_)), "{}", err);
+
+    for path in [&input_path, &parquet_path, &header_path, &serial_path, &parallel_path] {
+        cleanup_test_file(path);
+    }
+}
+
+#[test]
+fn test_decode_descriptor_sample() {
+    use _rcsfs::csfs_descriptor::CSFDescriptorGenerator;
+
+    let peel: Vec<String> = "5s 4d- 4d 5p- 5p 6s 4f- 4f 5d- 5d"
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    let generator = CSFDescriptorGenerator::new(peel);
+
+    for lines in sample_csf_triples() {
+        let descriptor = generator.parse_csf(&lines[0], &lines[1], &lines[2]).unwrap();
+        let decoded = generator.decode_descriptor(&descriptor).unwrap();
+        if lines[1].contains(';') {
+            // Seniority is not part of the descriptor, only the coupling must survive
+            let again = generator.parse_csf(&decoded[0], &decoded[1], &decoded[2]).unwrap();
+            assert_eq!(again, descriptor);
+        } else {
+            assert_eq!(decoded, lines);
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Parity and Electron Count Tests
+//////////////////////////////////////////////////////////////////////////////
+
+/// Read the `electron_count` and `parity` columns of a converted CSF Parquet file
+fn read_electron_and_parity_columns(path: &Path) -> Vec<(Option<u32>, Option<i8>)> {
+    use arrow::array::{Array, Int8Array, UInt32Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let file = fs::File::open(path).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap();
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.unwrap();
+        let electrons = batch
+            .column_by_name("electron_count")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap();
+        let parity = batch
+            .column_by_name("parity")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int8Array>()
+            .unwrap();
+        for i in 0..batch.num_rows() {
+            rows.push((
+                electrons.is_valid(i).then(|| electrons.value(i)),
+                parity.is_valid(i).then(|| parity.value(i)),
+            ));
+        }
+    }
+    rows
+}
+
+#[test]
+fn test_electron_count_and_parity_columns() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+
+    let input_path = temp_dir().join("test_parity_columns.csf");
+    let output_path = temp_dir().join("test_parity_columns.parquet");
+    let header_path = temp_dir().join("test_parity_columns_header.toml");
+
+    create_multi_block_csf(&input_path);
+
+    let stats = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+    // 10 core electrons (1s 2s 2p- 2p) + 3 peel electrons
+    assert_eq!(stats.electron_count, Some(13));
+    assert_eq!(stats.electron_count_mismatch_count, 0);
+    assert_eq!(stats.parity_mismatch_count, 0);
+    assert_eq!(stats.unparsed_count, 0);
+    assert_eq!(
+        read_electron_and_parity_columns(&output_path),
+        vec![(Some(13), Some(1)), (Some(13), Some(-1)), (Some(13), Some(-1))]
+    );
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+    cleanup_test_file(&header_path);
+}
+
+#[test]
+fn test_inconsistent_parity_and_electron_count_reported() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+
+    let input_path = temp_dir().join("test_parity_mismatch.csf");
+    let output_path = temp_dir().join("test_parity_mismatch.parquet");
+    let header_path = temp_dir().join("test_parity_mismatch_header.toml");
+
+    let content = "Core subshells:\n\
+                   \x20 1s\n\
+                   Peel subshells:\n\
+                   \x20 2s   2p-  2p\n\
+                   CSF(s):\n\
+                   \x20 2s ( 1)  2p-( 1)\n\
+                   \x20     1/2      1/2\n\
+                   \x20             1-\n\
+                   \x20 2s ( 2)  2p-( 1)\n\
+                   \x20              1/2\n\
+                   \x20                1/2+\n\
+                   \x20 2s ( 1)  2x-( 1)\n\
+                   \x20     1/2      1/2\n\
+                   \x20             1-\n";
+    fs::write(&input_path, content).unwrap();
+
+    let stats = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+    assert_eq!(stats.electron_count, Some(4));
+    assert_eq!(stats.electron_count_mismatch_count, 1);
+    assert_eq!(stats.parity_mismatch_count, 1);
+    assert_eq!(stats.unparsed_count, 1);
+    assert_eq!(
+        read_electron_and_parity_columns(&output_path),
+        vec![(Some(4), Some(-1)), (Some(5), Some(-1)), (None, None)]
+    );
+
+    let header_content = fs::read_to_string(&header_path).unwrap();
+    assert!(header_content.contains("parity_mismatch_count = 1"));
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+    cleanup_test_file(&header_path);
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Coupling Validation Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_validate_sample_file() {
+    use _rcsfs::csfs_descriptor::CSFDescriptorGenerator;
+    use _rcsfs::validation::validate_csfs_file;
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let report = validate_csfs_file(&path).unwrap();
+    assert_eq!(report.csf_count, sample_csf_triples().len());
+    assert!(report.is_valid(), "{:?}", report.invalid);
+
+    let peel: Vec<String> = "5s 4d- 4d 5p- 5p 6s 4f- 4f 5d- 5d"
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    let generator = CSFDescriptorGenerator::new(peel);
+    for [line1, line2, line3] in sample_csf_triples() {
+        assert_eq!(
+            generator.parse_csf_validated(&line1, &line2, &line3).unwrap(),
+            generator.parse_csf(&line1, &line2, &line3).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_validate_reports_corrupt_csfs() {
+    use _rcsfs::validation::validate_csfs_file;
+
+    let path = temp_dir().join("test_validate_corrupt.csf");
+    let content = "Core subshells:\n\
+                   \x20 1s\n\
+                   Peel subshells:\n\
+                   \x20 2s   2p-  2p\n\
+                   CSF(s):\n\
+                   \x20 2s ( 1)  2p-( 1)\n\
+                   \x20     1/2      1/2\n\
+                   \x20             1-\n\
+                   \x20*\n\
+                   \x20 2s ( 1)  2p-( 3)\n\
+                   \x20     1/2      3/2\n\
+                   \x20             1-\n\
+                   \x20 2s ( 1)  2p ( 1)\n\
+                   \x20     1/2      3/2\n\
+                   \x20             3-\n";
+    fs::write(&path, content).unwrap();
+
+    let report = validate_csfs_file(&path).unwrap();
+    assert_eq!(report.csf_count, 3);
+    assert_eq!(report.valid_count(), 1);
+    assert_eq!(report.invalid[0].index, 1);
+    assert_eq!(report.invalid[0].block, 1);
+    assert!(report.invalid[0].errors[0].contains("at most 2"));
+    assert_eq!(report.invalid[1].index, 2);
+    assert!(report.invalid[1].errors[0].contains("Final J"));
+
+    cleanup_test_file(&path);
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// CSF Generator Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_generate_csfs_to_parquet_round_trip() {
+    use _rcsfs::csf_generator::{CsfGenerator, CsfGeneratorConfig};
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, parquet_to_csfs};
+    use _rcsfs::validation::validate_csfs_file;
+
+    let parquet_path = temp_dir().join("test_generated.parquet");
+    let header_path = temp_dir().join("test_generated_header.toml");
+    let csf_path = temp_dir().join("test_generated.csf");
+    let reconverted_path = temp_dir().join("test_generated_reconverted.parquet");
+
+    let config = CsfGeneratorConfig {
+        core_shells: vec!["1s".into(), "2s".into(), "2p".into()],
+        references: vec!["3s(2) 3p(1)".into()],
+        active_shells: vec!["3s".into(), "3p".into(), "3d".into()],
+        excitation_rank: 2,
+        double_j_range: Some((1, 5)),
+        parity: Some(_rcsfs::csf::Parity::Odd),
+    };
+    let generator = CsfGenerator::new(&config).unwrap();
+    assert_eq!(generator.header().peel_labels(), ["3s", "3p-", "3p", "3d-", "3d"]);
+
+    let stats = generator.write_parquet(&parquet_path, 50, &WriterOptions::default()).unwrap();
+    assert!(stats.csf_count > 0);
+    assert_eq!(stats.block_csf_counts.len(), 3, "J = 1/2, 3/2, 5/2 odd blocks");
+    assert_eq!(stats.block_csf_counts.iter().sum::<usize>(), stats.csf_count);
+    assert_eq!(stats.electron_count, Some(13));
+    assert_eq!(stats.parity_mismatch_count, 0);
+    assert!(header_path.exists());
+
+    // Back to text: valid GRASP CSF list with ` *` between blocks
+    let export = parquet_to_csfs(&parquet_path, &csf_path, None).unwrap();
+    assert_eq!(export.csf_count, stats.csf_count);
+    assert_eq!(export.block_count, 3);
+    let report = validate_csfs_file(&csf_path).unwrap();
+    assert_eq!(report.csf_count, stats.csf_count);
+    assert!(report.is_valid(), "{:?}", report.invalid);
+
+    // Converting the text again reproduces the block structure (and rewrites the same header file)
+    let reconverted = convert_csfs_to_parquet(
+        &csf_path,
+        &reconverted_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+    assert_eq!(reconverted.block_csf_counts, stats.block_csf_counts);
+
+    cleanup_test_file(&parquet_path);
+    cleanup_test_file(&header_path);
+    cleanup_test_file(&csf_path);
+    cleanup_test_file(&reconverted_path);
+}
+
+#[test]
+fn test_generate_csfs_writer_options_and_cleanup() {
+    use _rcsfs::csf_generator::{CsfGenerator, CsfGeneratorConfig};
+    use _rcsfs::writer_options::Codec;
+    use parquet::basic::Compression;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let parquet_path = temp_dir().join("test_generated_options.parquet");
+    let header_path = temp_dir().join("test_generated_options_header.toml");
+    let generator = CsfGenerator::new(&CsfGeneratorConfig {
+        references: vec!["3s(2) 3p(1)".into()],
+        active_shells: vec!["3s".into(), "3p".into(), "3d".into()],
+        excitation_rank: 1,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let options = WriterOptions { codec: Some(Codec::Snappy), ..Default::default() };
+    generator.write_parquet(&parquet_path, 50, &options).unwrap();
+    let metadata = SerializedFileReader::new(fs::File::open(&parquet_path).unwrap()).unwrap().metadata().clone();
+    assert_eq!(metadata.row_group(0).column(1).compression(), Compression::SNAPPY);
+
+    // A header file that cannot be written removes the Parquet file as well
+    cleanup_test_file(&header_path);
+    fs::create_dir_all(&header_path).unwrap();
+    let result = generator.write_parquet(&parquet_path, 50, &WriterOptions::default());
+    assert!(matches!(result, Err(CsfError::Io { .. })), "{:?}", result.err());
+    assert!(!parquet_path.exists());
+
+    fs::remove_dir(&header_path).unwrap();
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Excitation Analysis Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_excitation_analysis_parquet_and_text_agree() {
+    use _rcsfs::csf_generator::{CsfGenerator, CsfGeneratorConfig};
+    use _rcsfs::csfs_conversion::parquet_to_csfs;
+    use _rcsfs::excitation::{analyze_excitations_csf, analyze_excitations_parquet};
+    use arrow::array::UInt32Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let generated_path = temp_dir().join("test_excitation_generated.parquet");
+    let generated_header = temp_dir().join("test_excitation_generated_header.toml");
+    let csf_path = temp_dir().join("test_excitation_generated.csf");
+    let filtered_path = temp_dir().join("test_excitation_filtered.parquet");
+    let filtered_header = temp_dir().join("test_excitation_filtered_header.toml");
+    let text_path = temp_dir().join("test_excitation_text.parquet");
+    let text_header = temp_dir().join("test_excitation_text_header.toml");
+    let filtered_csf_path = temp_dir().join("test_excitation_filtered.csf");
+
+    let config = CsfGeneratorConfig {
+        core_shells: vec!["1s".into(), "2s".into(), "2p".into()],
+        references: vec!["3s(2) 3p(1)".into()],
+        active_shells: vec!["3s".into(), "3p".into(), "3d".into()],
+        excitation_rank: 2,
+        double_j_range: Some((1, 5)),
+        parity: Some(_rcsfs::csf::Parity::Odd),
+    };
+    let generated = CsfGenerator::new(&config)
+        .unwrap()
+        .write_parquet(&generated_path, 50, &WriterOptions::default())
+        .unwrap();
+    parquet_to_csfs(&generated_path, &csf_path, None).unwrap();
+
+    let references = vec!["3s(2) 3p(1)".to_string()];
+    let filtered = analyze_excitations_parquet(
+        &generated_path,
+        &filtered_path,
+        &references,
+        Some(1),
+        &WriterOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(filtered.csf_count, generated.csf_count);
+    assert_eq!((filtered.unmatched_count, filtered.unparsed_count), (0, 0));
+    assert_eq!(filtered.rank_counts.len(), 3, "generated with excitation rank 2");
+    assert_eq!(filtered.rank_counts.iter().sum::<usize>(), generated.csf_count);
+    assert_eq!(filtered.kept_count, filtered.rank_counts[0] + filtered.rank_counts[1]);
+
+    // Every kept row is at most singly excited and keeps its original idx
+    let file = fs::File::open(&filtered_path).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap();
+    let mut rows = 0;
+    for batch in reader {
+        let batch = batch.unwrap();
+        let ranks = batch
+            .column_by_name("excitation_rank")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap();
+        assert!(ranks.iter().all(|r| r.is_some_and(|r| r <= 1)));
+        assert!(batch.column_by_name("idx").is_some());
+        rows += batch.num_rows();
+    }
+    assert_eq!(rows, filtered.kept_count);
+
+    // The filtered table carries its own header and exports as a valid CSF list
+    assert!(filtered_header.exists());
+    let export = parquet_to_csfs(&filtered_path, &filtered_csf_path, None).unwrap();
+    assert_eq!(export.csf_count, filtered.kept_count);
+
+    // Analysing the text file directly gives the same ranks
+    let text = analyze_excitations_csf(&csf_path, &text_path, &references, Some(1), &WriterOptions::default()).unwrap();
+    assert_eq!(text.rank_counts, filtered.rank_counts);
+    assert_eq!(text.kept_count, filtered.kept_count);
+    assert!(text_header.exists());
+
+    // A CSF list that breaks off mid-stream leaves no output behind
+    fs::write(&csf_path, "h1\nh2\nh3\nh4\nh5\n  3s ( 2)  3p-( 1)\n *\n            1/2\n           1/2-\n").unwrap();
+    let result = analyze_excitations_csf(&csf_path, &text_path, &references, None, &WriterOptions::default());
+    assert!(matches!(result, Err(CsfError::MalformedCsf { .. })), "{:?}", result.err());
+    assert!(!text_path.exists());
+    assert!(!text_header.exists());
+
+    for path in [
+        &generated_path,
+        &generated_header,
+        &csf_path,
+        &filtered_path,
+        &filtered_header,
+        &text_path,
+        &text_header,
+        &filtered_csf_path,
+    ] {
+        cleanup_test_file(path);
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Memory-Mapped Reader Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_csf_file_reads_header_and_borrowed_triples() {
+    use _rcsfs::csf_reader::CsfFile;
+
+    let input_path = temp_dir().join("test_mmap_records.csf");
+    create_multi_block_csf(&input_path);
+    let csf_file = CsfFile::open(&input_path).unwrap();
+
+    let header = csf_file.header_lines().unwrap();
+    assert_eq!(header.len(), 5);
+    assert_eq!(header[0], "Core subshells:");
+
+    let mut records = csf_file.records();
+    let triples: Vec<_> = records.by_ref().map(|r| r.unwrap()).collect();
+    assert_eq!(records.block_csf_counts, vec![1, 2]);
+    assert_eq!(records.total_lines, 10);
+    assert_eq!(triples.iter().map(|r| (r.block, r.line)).collect::<Vec<_>>(), vec![(0, 6), (1, 10), (1, 13)]);
+    assert_eq!(triples[1].lines[0], b"  3s ( 2)  3p-( 1)");
+
+    drop(csf_file);
+    cleanup_test_file(&input_path);
+}
+
+#[test]
+fn test_crlf_and_invalid_utf8_input() {
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, convert_csfs_to_parquet_parallel};
+
+    let lf_path = temp_dir().join("test_mmap_lf.csf");
+    let crlf_path = temp_dir().join("test_mmap_crlf.csf");
+    let lf_output = temp_dir().join("test_mmap_lf.parquet");
+    let crlf_output = temp_dir().join("test_mmap_crlf.parquet");
+    create_multi_block_csf(&lf_path);
+    fs::write(&crlf_path, fs::read_to_string(&lf_path).unwrap().replace('\n', "\r\n")).unwrap();
+
+    // Windows line endings are dropped like by `BufRead::lines`
+    convert_csfs_to_parquet(&lf_path, &lf_output, 256, 1000, &WriterOptions::default()).unwrap();
+    convert_csfs_to_parquet_parallel(
+        &crlf_path,
+        &crlf_output,
+        256,
+        1000,
+        Some(2),
+        &WriterOptions::default(),
+    ).unwrap();
+    assert_eq!(read_parquet_batch(&lf_output), read_parquet_batch(&crlf_output));
+
+    // A line that is not UTF-8 is located by CSF index and line number
+    let mut bytes = fs::read(&lf_path).unwrap();
+    let pos = bytes.windows(3).position(|w| w == b"3/2").unwrap();
+    bytes[pos] = 0xff;
+    fs::write(&lf_path, bytes).unwrap();
+    let result = convert_csfs_to_parquet(
+        &lf_path,
+        &lf_output,
+        256,
+        1000,
+        &WriterOptions::default(),
+    );
+    assert!(
+        matches!(result, Err(CsfError::MalformedCsf { idx: Some(2), line: Some(14), .. })),
+        "{:?}",
+        result.err()
+    );
+
+    cleanup_test_file(&lf_path);
+    cleanup_test_file(&crlf_path);
+    cleanup_test_file(&crlf_output);
+    cleanup_test_file(&temp_dir().join("test_mmap_crlf_header.toml"));
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Compressed Input Tests
+//////////////////////////////////////////////////////////////////////////////
+
+/// Compress `data` as gzip, zstd and xz
+fn compress_all(data: &[u8]) -> Vec<(&'static str, Vec<u8>)> {
+    use std::io::Write;
+
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    gz.write_all(data).unwrap();
+    let mut xz = liblzma::write::XzEncoder::new(Vec::new(), 1);
+    xz.write_all(data).unwrap();
+    vec![
+        ("gz", gz.finish().unwrap()),
+        ("zst", zstd::encode_all(data, 1).unwrap()),
+        ("xz", xz.finish().unwrap()),
+    ]
+}
+
+#[test]
+fn test_compressed_input_matches_plain() {
+    use _rcsfs::csf_header::CsfHeader;
+    use _rcsfs::csfs_conversion::{
+        build_thread_pool, convert_csfs_to_parquet, convert_csfs_to_parquet_by_ranges,
+        convert_csfs_to_parquet_parallel,
+    };
+    use _rcsfs::validation::validate_csfs_file;
+
+    // Several blocks, larger than one 1 MiB decompression chunk
+    let multi_block_path = temp_dir().join("test_compressed_blocks.csf");
+    create_multi_block_csf(&multi_block_path);
+    let block = fs::read_to_string(&multi_block_path).unwrap();
+    let (header, body) = block.split_at(block.match_indices('\n').nth(4).unwrap().0 + 1);
+    let mut content = header.to_string();
+    for i in 0..8000 {
+        if i > 0 {
+            content.push_str(" *\n");
+        }
+        content.push_str(body);
+    }
+
+    let plain_path = temp_dir().join("test_compressed_plain.csf");
+    let plain_output = temp_dir().join("test_compressed_plain.parquet");
+    fs::write(&plain_path, &content).unwrap();
+    let plain_stats = convert_csfs_to_parquet(
+        &plain_path,
+        &plain_output,
+        256,
+        30000,
+        &WriterOptions::default(),
+    ).unwrap();
+    let plain_batch = read_parquet_batch(&plain_output);
+    assert_eq!(plain_stats.block_csf_counts.len(), 16000);
+    assert!(content.len() > 1 << 20);
+
+    let pool = build_thread_pool(Some(3)).unwrap();
+    for (extension, bytes) in compress_all(content.as_bytes()) {
+        let input_path = temp_dir().join(format!("test_compressed_{}.csf.{}", extension, extension));
+        let output_path = temp_dir().join(format!("test_compressed_{}.parquet", extension));
+        fs::write(&input_path, bytes).unwrap();
+
+        let header = CsfHeader::from_csf_file(&input_path).unwrap();
+        assert_eq!(header.closed_core_electrons(), 10);
+        assert_eq!(validate_csfs_file(&input_path).unwrap().csf_count, plain_stats.csf_count);
+
+        let stats = [
+            convert_csfs_to_parquet(
+                &input_path,
+                &output_path,
+                256,
+                30000,
+                &WriterOptions::default(),
+            ),
+            convert_csfs_to_parquet_parallel(
+                &input_path,
+                &output_path,
+                256,
+                30000,
+                Some(3),
+                &WriterOptions::default(),
+            ),
+            convert_csfs_to_parquet_by_ranges(
+                &input_path,
+                &output_path,
+                256,
+                4096,
+                &pool,
+                &WriterOptions::default(),
+            ),
+        ];
+        for stats in stats {
+            let stats = stats.unwrap();
+            assert_eq!(stats.csf_count, plain_stats.csf_count, "{}", extension);
+            assert_eq!(stats.total_lines, plain_stats.total_lines, "{}", extension);
+            assert_eq!(stats.block_csf_counts, plain_stats.block_csf_counts, "{}", extension);
+        }
+        assert_eq!(read_parquet_batch(&output_path), plain_batch, "{}", extension);
+
+        // `a.csf.gz` shares the header file name of `a.csf`
+        let header_path = temp_dir().join(format!("test_compressed_{}_header.toml", extension));
+        assert!(header_path.exists());
+
+        cleanup_test_file(&input_path);
+        cleanup_test_file(&output_path);
+        cleanup_test_file(&header_path);
+    }
+
+    cleanup_test_file(&multi_block_path);
+    cleanup_test_file(&plain_path);
+    cleanup_test_file(&plain_output);
+    cleanup_test_file(&temp_dir().join("test_compressed_plain_header.toml"));
+}
+
+#[test]
+fn test_truncated_csf_same_error_plain_compressed_and_ranges() {
+    use _rcsfs::csfs_conversion::{
+        build_thread_pool, convert_csfs_to_parquet, convert_csfs_to_parquet_by_ranges,
+        convert_csfs_to_parquet_parallel,
+    };
+    use _rcsfs::validation::validate_csfs_file;
+
+    // CSF 20 is missing its last line, in the middle of the file
+    let mut content = String::from("h1\nh2\nh3\nh4\nh5\n");
+    for i in 0..50 {
+        content.push_str("  3s ( 1)\n      1/2\n");
+        if i != 20 {
+            content.push_str("        1/2+\n");
+        }
+    }
+    let plain_path = temp_dir().join("test_truncated_plain.csf");
+    let gz_path = temp_dir().join("test_truncated_gz.csf.gz");
+    let output_path = temp_dir().join("test_truncated.parquet");
+    fs::write(&plain_path, &content).unwrap();
+    let (_, gz) = compress_all(content.as_bytes()).swap_remove(0);
+    fs::write(&gz_path, gz).unwrap();
+
+    let pool = build_thread_pool(Some(3)).unwrap();
+    for input_path in [&plain_path, &gz_path] {
+        let results = [
+            convert_csfs_to_parquet(input_path, &output_path, 256, 30, &WriterOptions::default())
+                .map(|stats| stats.csf_count),
+            convert_csfs_to_parquet_parallel(
+                input_path,
+                &output_path,
+                256,
+                30,
+                Some(3),
+                &WriterOptions::default(),
+            )
+            .map(|stats| stats.csf_count),
+            convert_csfs_to_parquet_by_ranges(input_path, &output_path, 256, 100, &pool, &WriterOptions::default())
+                .map(|stats| stats.csf_count),
+            validate_csfs_file(input_path).map(|report| report.csf_count),
+        ];
+        for result in results {
+            match result {
+                Err(CsfError::MalformedCsf { idx, line, message }) => {
+                    assert_eq!(idx, Some(20), "{:?}", input_path);
+                    assert_eq!(line, Some(68), "{:?}", input_path);
+                    assert_eq!(message, "Incomplete CSF (2 of 3 lines) before the next CSF");
+                }
+                other => panic!("{:?}: expected MalformedCsf, got {:?}", input_path, other),
+            }
+            assert!(!output_path.exists());
+        }
+    }
+
+    cleanup_test_file(&plain_path);
+    cleanup_test_file(&gz_path);
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Header Parsing Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_header_from_csf_and_toml_agree() {
+    use _rcsfs::csf_header::CsfHeader;
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+    use _rcsfs::csfs_descriptor::parquet_batch::read_peel_subshells_from_header;
+
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let input_path = temp_dir().join("test_header_sample.csf");
+    let output_path = temp_dir().join("test_header_sample.parquet");
+    let header_path = temp_dir().join("test_header_sample_header.toml");
+    fs::copy(&fixture, &input_path).unwrap();
+
+    convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+
+    let from_csf = CsfHeader::from_csf_file(&input_path).unwrap();
+    let from_toml = CsfHeader::from_header_toml(&header_path).unwrap();
+    let peel = read_peel_subshells_from_header(&header_path).unwrap();
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+    cleanup_test_file(&header_path);
+
+    assert_eq!(from_csf, from_toml);
+    assert_eq!(from_csf.core_subshells.len(), 12);
+    assert_eq!(from_csf.closed_core_electrons(), 36);
+    assert_eq!(peel.first().map(String::as_str), Some("5s"));
+    assert_eq!(peel.len(), from_csf.peel_subshells.len());
+}
+
+#[test]
+fn test_header_malformed_reports_line() {
+    use _rcsfs::csf_header::CsfHeader;
+
+    let input_path = temp_dir().join("test_header_malformed.csf");
+    create_minimal_csf(&input_path);
+
+    let result = CsfHeader::from_csf_file(&input_path);
+    cleanup_test_file(&input_path);
+
+    let err = result.unwrap_err();
+    assert!(err.contains("line 1"), "Error should name the offending line: {}", err);
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Parquet to CSF Round-trip Tests
+//////////////////////////////////////////////////////////////////////////////
+
+/// Convert a CSF file to Parquet and back, returning the rebuilt bytes
+fn round_trip_bytes(input_path: &Path, name: &str) -> Vec<u8> {
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, parquet_to_csfs};
+
+    let parquet_path = temp_dir().join(format!("{}.parquet", name));
+    let output_path = temp_dir().join(format!("{}_rebuilt.csf", name));
+    let stem = input_path.file_stem().unwrap().to_str().unwrap();
+    let header_path = temp_dir().join(format!("{}_header.toml", stem));
+
+    convert_csfs_to_parquet(
+        input_path,
+        &parquet_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+    parquet_to_csfs(&parquet_path, &output_path, Some(&header_path)).unwrap();
+    let bytes = fs::read(&output_path).unwrap();
+
+    cleanup_test_file(&parquet_path);
+    cleanup_test_file(&output_path);
+    cleanup_test_file(&header_path);
+    bytes
+}
+
+#[test]
+fn test_round_trip_sample_is_byte_exact() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let input_path = temp_dir().join("test_rt_sample.csf");
+    fs::copy(&fixture, &input_path).unwrap();
+
+    let rebuilt = round_trip_bytes(&input_path, "test_rt_sample");
+    let original = fs::read(&input_path).unwrap();
+    cleanup_test_file(&input_path);
+
+    assert_eq!(rebuilt, original);
+}
+
+#[test]
+fn test_round_trip_multi_block_is_byte_exact() {
+    let input_path = temp_dir().join("test_rt_multi.csf");
+    create_multi_block_csf(&input_path);
+
+    let rebuilt = round_trip_bytes(&input_path, "test_rt_multi");
+    let original = fs::read(&input_path).unwrap();
+    cleanup_test_file(&input_path);
+
+    assert_eq!(String::from_utf8(rebuilt).unwrap(), String::from_utf8(original).unwrap());
+}
+
+#[test]
+fn test_parquet_to_csfs_reads_header_from_metadata() {
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, parquet_to_csfs};
+
+    let input_path = temp_dir().join("test_rt_metadata.csf");
+    let parquet_path = temp_dir().join("test_rt_metadata_other.parquet");
+    let header_path = temp_dir().join("test_rt_metadata_header.toml");
+    let output_path = temp_dir().join("test_rt_metadata_rebuilt.csf");
+    create_multi_block_csf(&input_path);
+    convert_csfs_to_parquet(
+        &input_path,
+        &parquet_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap();
+
+    // Parquet stem differs from the input stem and the header file is gone,
+    // so the header can only come from the Parquet metadata
+    fs::remove_file(&header_path).unwrap();
+    let result = parquet_to_csfs(&parquet_path, &output_path, None);
+    let rebuilt = fs::read(&output_path).unwrap_or_default();
+    let original = fs::read(&input_path).unwrap();
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&parquet_path);
+    cleanup_test_file(&output_path);
+
+    let stats = result.unwrap();
+    assert_eq!(stats.header_file, parquet_path);
+    assert_eq!(rebuilt, original);
+}
+
+#[test]
+fn test_parquet_to_csfs_missing_header() {
+    use _rcsfs::csfs_conversion::parquet_to_csfs;
+    use arrow::array::StringArray;
+    use arrow::record_batch::RecordBatch;
+    use std::sync::Arc;
+
+    // A CSF table written by another tool: no rcsfs metadata, no header file
+    let parquet_path = temp_dir().join("test_rt_missing_foreign.parquet");
+    let output_path = temp_dir().join("test_rt_missing_rebuilt.csf");
+    let line = |s: &str| Arc::new(StringArray::from(vec![s])) as arrow::array::ArrayRef;
+    let batch = RecordBatch::try_from_iter([
+        ("line1", line("  5s ( 2)")),
+        ("line2", line("")),
+        ("line3", line("           0+")),
+    ]).unwrap();
+    let file = fs::File::create(&parquet_path).unwrap();
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let result = parquet_to_csfs(&parquet_path, &output_path, None);
+
+    cleanup_test_file(&parquet_path);
+    cleanup_test_file(&output_path);
+
+    assert!(
+        matches!(result, Err(CsfError::Header(_))),
+        "Missing header should be reported"
+    );
+}
+
+#[test]
+fn test_parquet_to_csfs_accepts_large_utf8_lines() {
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, parquet_to_csfs};
+    use arrow::datatypes::DataType;
+    use arrow::record_batch::RecordBatch;
+
+    let input_path = temp_dir().join("test_rt_large_utf8.csf");
+    let parquet_path = temp_dir().join("test_rt_large_utf8.parquet");
+    let header_path = temp_dir().join("test_rt_large_utf8_header.toml");
+    let large_path = temp_dir().join("test_rt_large_utf8_polars.parquet");
+    let output_path = temp_dir().join("test_rt_large_utf8_rebuilt.csf");
+    create_multi_block_csf(&input_path);
+    convert_csfs_to_parquet(&input_path, &parquet_path, 256, 1000, &WriterOptions::default()).unwrap();
+
+    // Rewrite the table the way Polars does: line columns as LargeUtf8
+    let batch = read_parquet_batch(&parquet_path);
+    let columns = batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, column)| {
+            let column = if field.name().starts_with("line") {
+                arrow::compute::cast(column, &DataType::LargeUtf8).unwrap()
+            } else {
+                column.clone()
+            };
+            (field.name().clone(), column)
+        })
+        .collect::<Vec<_>>();
+    let large = RecordBatch::try_from_iter(columns).unwrap();
+    assert_eq!(large.schema().field_with_name("line1").unwrap().data_type(), &DataType::LargeUtf8);
+    let file = fs::File::create(&large_path).unwrap();
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, large.schema(), None).unwrap();
+    writer.write(&large).unwrap();
+    writer.close().unwrap();
+
+    let result = parquet_to_csfs(&large_path, &output_path, Some(&header_path));
+    let rebuilt = fs::read(&output_path).unwrap_or_default();
+    let original = fs::read(&input_path).unwrap();
+
+    for path in [&input_path, &parquet_path, &header_path, &large_path, &output_path] {
+        cleanup_test_file(path);
+    }
+
+    result.unwrap();
+    assert_eq!(rebuilt, original);
+}
+
+#[test]
+fn test_parquet_to_csfs_schema_error_removes_output() {
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, parquet_to_csfs};
+    use arrow::array::{ArrayRef, Int32Array, StringArray};
+    use arrow::record_batch::RecordBatch;
+    use std::sync::Arc;
+
+    let input_path = temp_dir().join("test_rt_bad_schema.csf");
+    let parquet_path = temp_dir().join("test_rt_bad_schema.parquet");
+    let header_path = temp_dir().join("test_rt_bad_schema_header.toml");
+    let bad_path = temp_dir().join("test_rt_bad_schema_int.parquet");
+    let output_path = temp_dir().join("test_rt_bad_schema_rebuilt.csf");
+    create_minimal_csf(&input_path);
+    convert_csfs_to_parquet(&input_path, &parquet_path, 256, 1000, &WriterOptions::default()).unwrap();
+
+    // line3 is not text, so the export fails after the header was written
+    let batch = RecordBatch::try_from_iter([
+        ("line1", Arc::new(StringArray::from(vec!["  5s ( 2)"])) as ArrayRef),
+        ("line2", Arc::new(StringArray::from(vec![""])) as ArrayRef),
+        ("line3", Arc::new(Int32Array::from(vec![0])) as ArrayRef),
+    ]).unwrap();
+    let file = fs::File::create(&bad_path).unwrap();
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let result = parquet_to_csfs(&bad_path, &output_path, Some(&header_path));
+    let output_left = output_path.exists();
+
+    for path in [&input_path, &parquet_path, &header_path, &bad_path, &output_path] {
+        cleanup_test_file(path);
+    }
+
+    assert!(matches!(result, Err(CsfError::Schema(_))), "{:?}", result.err());
+    assert!(!output_left, "a failed export must not leave a truncated CSF file");
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Parquet Metadata Tests
+//////////////////////////////////////////////////////////////////////////////
+
+/// Key-value metadata of a Parquet file
+fn read_key_value_metadata(path: &Path) -> std::collections::HashMap<String, String> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let reader = SerializedFileReader::new(fs::File::open(path).unwrap()).unwrap();
+    reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .map(|kvs| kvs.iter().filter_map(|kv| Some((kv.key.clone(), kv.value.clone()?))).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_header_and_provenance_in_parquet_metadata() {
+    use _rcsfs::csf_header::CsfHeader;
+    use _rcsfs::csfs_conversion::{
+        build_thread_pool, convert_csfs_to_parquet_by_ranges, METADATA_CONVERSION_KEY, METADATA_HEADER_KEY,
+        METADATA_PEEL_SUBSHELLS_KEY, METADATA_VERSION_KEY,
+    };
+    use _rcsfs::csfs_descriptor::parquet_batch::{
+        generate_descriptors_from_parquet, read_peel_subshells_from_parquet, ErrorPolicy,
+    };
+
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let input_path = temp_dir().join("test_metadata_sample.csf");
+    let parquet_path = temp_dir().join("test_metadata_renamed.parquet");
+    let header_path = temp_dir().join("test_metadata_sample_header.toml");
+    let descriptor_path = temp_dir().join("test_metadata_descriptors.parquet");
+    fs::copy(&fixture, &input_path).unwrap();
+
+    let pool = build_thread_pool(Some(2)).unwrap();
+    convert_csfs_to_parquet_by_ranges(
+        &input_path,
+        &parquet_path,
+        256,
+        4096,
+        &pool,
+        &WriterOptions::default(),
+    ).unwrap();
+    fs::remove_file(&header_path).unwrap();
+
+    let metadata = read_key_value_metadata(&parquet_path);
+    let expected = CsfHeader::from_csf_file(&input_path).unwrap();
+    let from_parquet = CsfHeader::from_parquet(&parquet_path).unwrap();
+    let peel = read_peel_subshells_from_parquet(&parquet_path, None).unwrap();
+    let stats = generate_descriptors_from_parquet(
+        &parquet_path,
+        &descriptor_path,
+        None,
+        None,
+        &[],
+        ErrorPolicy::Fail,
+        &WriterOptions::default(),
+    );
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&parquet_path);
+    cleanup_test_file(&descriptor_path);
+
+    assert_eq!(metadata[METADATA_VERSION_KEY], env!("CARGO_PKG_VERSION"));
+    assert!(metadata[METADATA_HEADER_KEY].contains("[conversion_stats]"));
+    assert_eq!(metadata[METADATA_PEEL_SUBSHELLS_KEY], peel.join(" "));
+    let conversion = &metadata[METADATA_CONVERSION_KEY];
+    assert!(conversion.contains("mode = \"byte_ranges\""), "{}", conversion);
+    assert!(conversion.contains("range_bytes = 4096"), "{}", conversion);
+    assert!(conversion.contains("num_workers = 2"), "{}", conversion);
+
+    assert_eq!(from_parquet, expected);
+    assert_eq!(peel, expected.peel_labels());
+    assert_eq!(stats.unwrap().orbital_count, peel.len());
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Parquet Inspector Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_inspect_csf_and_descriptor_tables() {
+    use _rcsfs::csfs_conversion::convert_csfs_to_parquet;
+    use _rcsfs::csfs_descriptor::parquet_batch::{generate_descriptors_from_parquet, ErrorPolicy};
+    use _rcsfs::parquet_info::{inspect_parquet, StatValue, TableKind};
+    use _rcsfs::writer_options::Codec;
+
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.csf");
+    let input_path = temp_dir().join("test_inspect_sample.csf");
+    let parquet_path = temp_dir().join("test_inspect_sample.parquet");
+    let header_path = temp_dir().join("test_inspect_sample_header.toml");
+    let descriptor_path = temp_dir().join("test_inspect_descriptors.parquet");
+    fs::copy(&fixture, &input_path).unwrap();
+
+    let options = WriterOptions {
+        codec: Some(Codec::Zstd),
+        row_group_rows: Some(10),
+        ..Default::default()
+    };
+    let stats = convert_csfs_to_parquet(&input_path, &parquet_path, 256, 1000, &options).unwrap();
+    generate_descriptors_from_parquet(
+        &parquet_path,
+        &descriptor_path,
+        None,
+        None,
+        &[],
+        ErrorPolicy::Fail,
+        &WriterOptions::default(),
+    ).unwrap();
+
+    let csf_info = inspect_parquet(&parquet_path);
+    let descriptor_info = inspect_parquet(&descriptor_path);
+    let missing = inspect_parquet(&temp_dir().join("test_inspect_missing.parquet"));
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&parquet_path);
+    cleanup_test_file(&header_path);
+    cleanup_test_file(&descriptor_path);
+
+    let info = csf_info.unwrap();
+    assert_eq!(info.kind, TableKind::Csf);
+    assert_eq!(info.num_rows as usize, stats.csf_count);
+    assert_eq!(info.row_groups.len(), stats.csf_count.div_ceil(10));
+    assert_eq!(info.row_groups[0].num_rows, 10);
+    assert_eq!(info.codecs(), vec!["zstd"]);
+    assert!(info.created_by.is_some());
+
+    let idx = &info.schema[0];
+    assert_eq!((idx.name.as_str(), idx.data_type.as_str(), idx.nullable), ("idx", "UInt64", false));
+    assert_eq!(idx.codec.as_deref(), Some("zstd"));
+    assert!(!idx.encodings.is_empty());
+    let idx_chunk = &info.row_groups[1].columns[0];
+    assert_eq!(idx_chunk.name, "idx");
+    assert_eq!(idx_chunk.min, Some(StatValue::UInt(10)));
+    assert_eq!(idx_chunk.max, Some(StatValue::UInt(19)));
+    assert_eq!(idx_chunk.null_count, Some(0));
+    assert!(matches!(info.row_groups[0].columns[1].min, Some(StatValue::Str(_))));
+
+    let csf_metadata = info.csf_metadata.unwrap();
+    assert_eq!(csf_metadata.rcsfs_version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(csf_metadata.header_lines.len(), 5);
+    assert_eq!(csf_metadata.peel_subshells.first().map(String::as_str), Some("5s"));
+    assert_eq!(csf_metadata.conversion_stats.csf_count, stats.csf_count);
+    let conversion = csf_metadata.conversion.unwrap();
+    assert_eq!(conversion.mode, "sequential");
+    assert_eq!(conversion.row_group_rows, Some(10));
+
+    let info = descriptor_info.unwrap();
+    assert_eq!(info.kind, TableKind::Descriptor);
+    assert_eq!(info.descriptor_size(), Some(3 * csf_metadata.peel_subshells.len()));
+    assert_eq!(info.codecs(), vec!["zstd"]);
+    assert!(info.csf_metadata.is_none());
+
+    assert!(matches!(missing, Err(CsfError::Io { .. })));
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Error Type Tests
+//////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn test_error_variants() {
+    use _rcsfs::csfs_conversion::{convert_csfs_to_parquet, convert_csfs_to_parquet_parallel};
+    use _rcsfs::validation::validate_csfs_file;
+
+    // A block separator after the first line of the second CSF
+    let input_path = temp_dir().join("test_error_split_csf.csf");
+    let output_path = temp_dir().join("test_error_split_csf.parquet");
+    let content = "  Header line 1\n\
+                      Header line 2\n\
+                      Header line 3\n\
+                      Header line 4\n\
+                      Header line 5\n\
+                      5s ( 2)  5p-( 2)\n\
+                                      \n\
+                                   0+\n\
+                      5s ( 2)  5p-( 1)\n\
+                     *\n";
+    fs::write(&input_path, content).unwrap();
+
+    let err = convert_csfs_to_parquet(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap_err();
+    assert!(
+        matches!(err, CsfError::MalformedCsf { idx: Some(1), line: Some(10), .. }),
+        "{}",
+        err
+    );
+    let err = validate_csfs_file(&input_path).unwrap_err();
+    assert!(matches!(err, CsfError::MalformedCsf { idx: Some(1), .. }), "{}", err);
+
+    // The failed conversion leaves neither a partial table nor a header behind
+    let header_path = temp_dir().join("test_error_split_csf_header.toml");
+    assert!(!output_path.exists(), "Partial Parquet output should be removed");
+    assert!(!header_path.exists());
+
+    // A stale header from an earlier run is removed along with the output
+    fs::write(&header_path, "stale").unwrap();
+    assert!(convert_csfs_to_parquet_parallel(
+        &input_path,
+        &output_path,
+        256,
+        1000,
+        None,
+        &WriterOptions::default(),
+    ).is_err());
+    assert!(!output_path.exists() && !header_path.exists());
+
+    // Missing input files keep the path in the error and leave existing output alone
+    let missing = temp_dir().join("test_error_missing.csf");
+    fs::write(&output_path, "previous").unwrap();
+    let err = convert_csfs_to_parquet(
+        &missing,
+        &output_path,
+        256,
+        1000,
+        &WriterOptions::default(),
+    ).unwrap_err();
+    assert!(output_path.exists());
+
+    cleanup_test_file(&input_path);
+    cleanup_test_file(&output_path);
+
+    match err {
+        CsfError::Io { path, .. } => assert_eq!(path.as_deref(), Some(missing.as_path())),
+        other => panic!("expected an I/O error, got {}", other),
+    }
+}